@@ -43,5 +43,27 @@ fn conversions(c: &mut Criterion) {
     });
 }
 
-criterion_group!(benches, conversions);
+fn lookups(c: &mut Criterion) {
+    let mut group = c.benchmark_group("lookups");
+    let converter = Converter::default();
+
+    let units = black_box(["tsp", "tablespoons", "ml", "l", "g", "kilograms", "cups"]);
+
+    group.bench_with_input("find_unit", &units, |b, units| {
+        b.iter(|| {
+            for unit in units {
+                let _ = converter.find_unit(unit);
+            }
+        })
+    });
+    group.bench_with_input("convert_between", &units, |b, units| {
+        b.iter(|| {
+            for pair in units.windows(2) {
+                let _ = converter.convert_between(1.5, pair[0], pair[1]);
+            }
+        })
+    });
+}
+
+criterion_group!(benches, conversions, lookups);
 criterion_main!(benches);
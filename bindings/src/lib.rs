@@ -7,13 +7,23 @@ use cooklang::{Converter, Extensions};
 
 pub mod aisle;
 pub mod model;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 
 use aisle::*;
 use model::*;
 
 #[uniffi::export]
 pub fn parse_recipe(input: String) -> CooklangRecipe {
-    let extensions = Extensions::empty();
+    parse_recipe_with_precision(input, DEFAULT_PRECISION)
+}
+
+/// Same as [`parse_recipe`] but numeric values are rounded to `precision`
+/// decimals instead of [`DEFAULT_PRECISION`]
+#[uniffi::export]
+pub fn parse_recipe_with_precision(input: String, precision: u8) -> CooklangRecipe {
+    // inline quantities would be an error without it
+    let extensions = Extensions::INLINE_QUANTITIES;
     let converter = Converter::empty();
 
     let mut parser = PullParser::new(&input, extensions);
@@ -26,7 +36,7 @@ pub fn parse_recipe(input: String) -> CooklangRecipe {
     )
     .unwrap_output();
 
-    into_simple_recipe(&parsed)
+    into_simple_recipe(&parsed, &input, precision)
 }
 
 #[uniffi::export]
@@ -84,11 +94,21 @@ pub fn parse_aisle_config(input: String) -> Arc<AisleConf> {
 
 #[uniffi::export]
 pub fn combine_ingredient_lists(lists: Vec<IngredientList>) -> IngredientList {
+    combine_ingredient_lists_with_precision(lists, DEFAULT_PRECISION)
+}
+
+/// Same as [`combine_ingredient_lists`] but the sums are rounded to
+/// `precision` decimals instead of [`DEFAULT_PRECISION`]
+#[uniffi::export]
+pub fn combine_ingredient_lists_with_precision(
+    lists: Vec<IngredientList>,
+    precision: u8,
+) -> IngredientList {
     let mut combined: IngredientList = IngredientList::default();
 
     lists
         .iter()
-        .for_each(|l| merge_ingredient_lists(&mut combined, l));
+        .for_each(|l| merge_ingredient_lists(&mut combined, l, precision));
 
     combined
 }
@@ -100,7 +120,7 @@ mod tests {
 
     #[test]
     fn test_parse_recipe() {
-        use crate::{parse_recipe, Amount, Item, Value};
+        use crate::{parse_recipe, Amount, Item, Span, Value};
 
         let recipe = parse_recipe(
             r#"
@@ -109,31 +129,77 @@ a test @step @salt{1%mg} more text
             .to_string(),
         );
 
+        let span = |start, end, column| Span {
+            start,
+            end,
+            line: 2,
+            column,
+        };
         assert_eq!(
             recipe.steps.into_iter().nth(0).unwrap().items,
             vec![
                 Item::Text {
-                    value: "a test ".to_string()
+                    value: "a test ".to_string(),
+                    span: span(1, 8, 1),
                 },
                 Item::Ingredient {
                     name: "step".to_string(),
-                    amount: None
+                    amount: None,
+                    span: span(8, 13, 8),
                 },
                 Item::Text {
-                    value: " ".to_string()
+                    value: " ".to_string(),
+                    span: span(13, 14, 13),
                 },
                 Item::Ingredient {
                     name: "salt".to_string(),
                     amount: Some(Amount {
                         quantity: Value::Number { value: 1.0 },
                         units: Some("mg".to_string())
-                    })
+                    }),
+                    span: span(14, 25, 14),
+                },
+                Item::Text {
+                    value: " more text".to_string(),
+                    span: span(25, 35, 25),
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_inline_quantity() {
+        use crate::{parse_recipe, Amount, Item, Span, Value};
+
+        let recipe = parse_recipe("Add @{2%cups} of the mixture".to_string());
+
+        let span = |start, end| Span {
+            start,
+            end,
+            line: 1,
+            column: start + 1,
+        };
+        assert_eq!(
+            recipe.steps.into_iter().next().unwrap().items,
+            vec![
+                Item::Text {
+                    value: "Add ".to_string(),
+                    span: span(0, 4),
+                },
+                Item::InlineQuantity {
+                    amount: Amount {
+                        quantity: Value::Number { value: 2.0 },
+                        units: Some("cups".to_string())
+                    },
+                    span: span(4, 13),
                 },
                 Item::Text {
-                    value: " more text".to_string()
+                    value: " of the mixture".to_string(),
+                    span: span(13, 28),
                 }
             ]
         );
+        assert!(recipe.ingredients.is_empty());
     }
 
     #[test]
@@ -312,4 +378,96 @@ dried oregano
             ])
         );
     }
+
+    #[test]
+    fn test_combine_ingredient_lists_with_precision() {
+        use crate::{
+            combine_ingredient_lists_with_precision, GroupedQuantityKey, QuantityType, Value,
+        };
+        use std::collections::HashMap;
+
+        let key = GroupedQuantityKey {
+            name: "g".to_string(),
+            unit_type: QuantityType::Number,
+        };
+        let list = |value| {
+            HashMap::from([(
+                "salt".to_string(),
+                HashMap::from([(key.clone(), Value::Number { value })]),
+            )])
+        };
+
+        let combined = combine_ingredient_lists_with_precision(vec![list(0.1), list(0.33)], 1);
+        assert_eq!(combined["salt"][&key], Value::Number { value: 0.4 });
+    }
+
+    #[test]
+    fn test_parse_recipe_rounds_values() {
+        use crate::{parse_recipe, GroupedQuantityKey, QuantityType, Value};
+
+        let recipe = parse_recipe("Add @salt{0.1%g} and more @salt{0.2%g}".to_string());
+
+        assert_eq!(
+            recipe.ingredients["salt"][&GroupedQuantityKey {
+                name: "g".to_string(),
+                unit_type: QuantityType::Number,
+            }],
+            Value::Number { value: 0.3 }
+        );
+    }
+
+    #[test]
+    fn test_parse_recipe_with_precision() {
+        use crate::{parse_recipe_with_precision, Amount, Item, Span, Value};
+
+        let recipe = parse_recipe_with_precision("Add @salt{1.23456%g}".to_string(), 2);
+
+        assert_eq!(
+            recipe.steps.into_iter().next().unwrap().items[1],
+            Item::Ingredient {
+                name: "salt".to_string(),
+                amount: Some(Amount {
+                    quantity: Value::Number { value: 1.23 },
+                    units: Some("g".to_string())
+                }),
+                span: Span {
+                    start: 4,
+                    end: 20,
+                    line: 1,
+                    column: 5
+                }
+            }
+        );
+    }
+
+    #[test]
+    fn test_item_spans() {
+        use crate::{parse_recipe, Item};
+
+        let input =
+            ">> servings: 2\n\nPeel the @potatoes{2}.\n\nBoil them in a #pot for ~{20%min}.\n";
+        let recipe = parse_recipe(input.to_string());
+
+        let source = |item: &Item| {
+            let (Item::Text { span, .. }
+            | Item::Ingredient { span, .. }
+            | Item::Cookware { span, .. }
+            | Item::Timer { span, .. }
+            | Item::InlineQuantity { span, .. }) = item;
+            (
+                &input[span.start as usize..span.end as usize],
+                span.line,
+                span.column,
+            )
+        };
+
+        let potatoes = &recipe.steps[0].items[1];
+        assert!(matches!(potatoes, Item::Ingredient { name, .. } if name == "potatoes"));
+        assert_eq!(source(potatoes), ("@potatoes{2}", 3, 10));
+
+        let step = &recipe.steps[1].items;
+        assert_eq!(source(&step[1]), ("#pot", 5, 16));
+        assert_eq!(source(&step[3]), ("~{20%min}", 5, 25));
+        assert_eq!(source(&recipe.cookware[0]), ("#pot", 5, 16));
+    }
 }
@@ -4,9 +4,12 @@ use cooklang::model::Item as OriginalItem;
 use cooklang::quantity::{
     Quantity as OriginalQuantity, ScalableValue as OriginalScalableValue, Value as OriginalValue,
 };
+use cooklang::scale::ByServingsPolicy;
 use cooklang::ScalableRecipe as OriginalRecipe;
+use cooklang::Span as OriginalSpan;
 
 #[derive(uniffi::Record, Debug)]
+#[cfg_attr(feature = "wasm", derive(serde::Serialize))]
 pub struct CooklangRecipe {
     pub metadata: HashMap<String, String>,
     pub steps: Vec<Step>,
@@ -15,31 +18,77 @@ pub struct CooklangRecipe {
 }
 
 #[derive(uniffi::Record, Debug)]
+#[cfg_attr(feature = "wasm", derive(serde::Serialize))]
 pub struct Step {
     pub items: Vec<Item>,
 }
 
 #[derive(uniffi::Enum, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "wasm", derive(serde::Serialize))]
+#[cfg_attr(feature = "wasm", serde(tag = "type"))]
 pub enum Item {
     Text {
         value: String,
+        span: Span,
     },
     Ingredient {
         name: String,
         amount: Option<Amount>,
+        span: Span,
     },
     Cookware {
         name: String,
         amount: Option<Amount>,
+        span: Span,
     },
     Timer {
         name: Option<String>,
         amount: Option<Amount>,
+        span: Span,
     },
+    InlineQuantity {
+        amount: Amount,
+        span: Span,
+    },
+}
+
+/// Location of an item in the recipe source
+///
+/// `start` and `end` are byte offsets, `line` and `column` are where it
+/// starts, 1 based, and the column counts characters, not bytes. All of
+/// them are 0 if the location is not known.
+#[derive(uniffi::Record, Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "wasm", derive(serde::Serialize))]
+pub struct Span {
+    pub start: u32,
+    pub end: u32,
+    pub line: u32,
+    pub column: u32,
+}
+
+pub(crate) fn into_span(span: OriginalSpan, input: &str) -> Span {
+    let before = &input[..span.start()];
+    let line_start = before.rfind('\n').map(|i| i + 1).unwrap_or(0);
+    Span {
+        start: span.start() as u32,
+        end: span.end() as u32,
+        line: before.matches('\n').count() as u32 + 1,
+        column: before[line_start..].chars().count() as u32 + 1,
+    }
 }
 
 pub type IngredientList = HashMap<String, GroupedQuantity>;
 
+/// Decimals numeric values are rounded to by default
+pub const DEFAULT_PRECISION: u8 = 3;
+
+/// Round to `precision` decimals, so clients don't show float noise like
+/// `0.30000000000000004`
+pub(crate) fn round_to(value: f64, precision: u8) -> f64 {
+    let factor = 10f64.powi(precision as i32);
+    (value * factor).round() / factor
+}
+
 pub(crate) fn into_group_quantity(amount: &Option<Amount>) -> GroupedQuantity {
     // options here:
     // - same units:
@@ -71,6 +120,14 @@ pub(crate) fn into_group_quantity(amount: &Option<Amount>) -> GroupedQuantity {
                 name: units.to_string(),
                 unit_type: QuantityType::Range,
             },
+            Value::AtLeast { .. } => GroupedQuantityKey {
+                name: units.to_string(),
+                unit_type: QuantityType::AtLeast,
+            },
+            Value::AtMost { .. } => GroupedQuantityKey {
+                name: units.to_string(),
+                unit_type: QuantityType::AtMost,
+            },
             Value::Text { .. } => GroupedQuantityKey {
                 name: units.to_string(),
                 unit_type: QuantityType::Text,
@@ -97,14 +154,18 @@ pub(crate) fn into_group_quantity(amount: &Option<Amount>) -> GroupedQuantity {
 }
 
 #[derive(uniffi::Enum, Debug, Clone, Hash, Eq, PartialEq)]
+#[cfg_attr(feature = "wasm", derive(serde::Serialize))]
 pub enum QuantityType {
     Number,
     Range, // how to combine ranges?
+    AtLeast,
+    AtMost,
     Text,
     Empty,
 }
 
 #[derive(uniffi::Record, Debug, Clone, Hash, Eq, PartialEq)]
+#[cfg_attr(feature = "wasm", derive(serde::Serialize))]
 pub struct GroupedQuantityKey {
     pub name: String,
     pub unit_type: QuantityType,
@@ -113,15 +174,20 @@ pub struct GroupedQuantityKey {
 pub type GroupedQuantity = HashMap<GroupedQuantityKey, Value>;
 
 #[derive(uniffi::Record, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "wasm", derive(serde::Serialize))]
 pub struct Amount {
     pub(crate) quantity: Value,
     pub(crate) units: Option<String>,
 }
 
 #[derive(uniffi::Enum, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "wasm", derive(serde::Serialize))]
+#[cfg_attr(feature = "wasm", serde(tag = "type"))]
 pub enum Value {
     Number { value: f64 },
     Range { start: f64, end: f64 },
+    AtLeast { value: f64 },
+    AtMost { value: f64 },
     Text { value: String },
     Empty,
 }
@@ -129,12 +195,12 @@ pub enum Value {
 pub type CooklangMetadata = HashMap<String, String>;
 
 trait Amountable {
-    fn extract_amount(&self) -> Amount;
+    fn extract_amount(&self, precision: u8) -> Amount;
 }
 
 impl Amountable for OriginalQuantity<OriginalScalableValue> {
-    fn extract_amount(&self) -> Amount {
-        let quantity = extract_quantity(&self.value);
+    fn extract_amount(&self, precision: u8) -> Amount {
+        let quantity = extract_quantity(&self.value, precision);
 
         let units = self.unit().as_ref().map(|u| u.to_string());
 
@@ -143,8 +209,8 @@ impl Amountable for OriginalQuantity<OriginalScalableValue> {
 }
 
 impl Amountable for OriginalScalableValue {
-    fn extract_amount(&self) -> Amount {
-        let quantity = extract_quantity(self);
+    fn extract_amount(&self, precision: u8) -> Amount {
+        let quantity = extract_quantity(self, precision);
 
         Amount {
             quantity,
@@ -153,20 +219,28 @@ impl Amountable for OriginalScalableValue {
     }
 }
 
-fn extract_quantity(value: &OriginalScalableValue) -> Value {
-    match value {
-        OriginalScalableValue::Fixed(value) => extract_value(value),
-        OriginalScalableValue::Linear(value) => extract_value(value),
-        OriginalScalableValue::ByServings(values) => extract_value(values.first().unwrap()),
-    }
+fn extract_quantity(value: &OriginalScalableValue, precision: u8) -> Value {
+    // the recipe is not scaled, so it's the value for the first servings
+    let value = value
+        .value_for_servings(0, ByServingsPolicy::Clamp)
+        .expect("clamp policy never fails");
+    extract_value(&value, precision)
 }
 
-fn extract_value(value: &OriginalValue) -> Value {
+fn extract_value(value: &OriginalValue, precision: u8) -> Value {
     match value {
-        OriginalValue::Number(num) => Value::Number { value: num.value() },
+        OriginalValue::Number(num) => Value::Number {
+            value: round_to(num.value(), precision),
+        },
         OriginalValue::Range { start, end } => Value::Range {
-            start: start.value(),
-            end: end.value(),
+            start: round_to(start.value(), precision),
+            end: round_to(end.value(), precision),
+        },
+        OriginalValue::AtLeast(start) => Value::AtLeast {
+            value: round_to(start.value(), precision),
+        },
+        OriginalValue::AtMost(end) => Value::AtMost {
+            value: round_to(end.value(), precision),
         },
         OriginalValue::Text(value) => Value::Text {
             value: value.to_string(),
@@ -179,16 +253,17 @@ pub fn add_to_ingredient_list(
     list: &mut IngredientList,
     name: &String,
     quantity_to_add: &GroupedQuantity,
+    precision: u8,
 ) {
     if let Some(quantity) = list.get_mut(name) {
-        merge_grouped_quantities(quantity, quantity_to_add);
+        merge_grouped_quantities(quantity, quantity_to_add, precision);
     } else {
         list.insert(name.to_string(), quantity_to_add.clone());
     }
 }
 
 // O(n2)? find a better way
-pub fn merge_ingredient_lists(left: &mut IngredientList, right: &IngredientList) {
+pub fn merge_ingredient_lists(left: &mut IngredientList, right: &IngredientList, precision: u8) {
     right
         .iter()
         .for_each(|(ingredient_name, grouped_quantity)| {
@@ -196,13 +271,17 @@ pub fn merge_ingredient_lists(left: &mut IngredientList, right: &IngredientList)
                 .entry(ingredient_name.to_string())
                 .or_insert(GroupedQuantity::default());
 
-            merge_grouped_quantities(quantity, grouped_quantity);
+            merge_grouped_quantities(quantity, grouped_quantity, precision);
         });
 }
 
 // I(dubadub) haven't found a way to export these methods with mutable argument
 // Right should be always smaller?
-pub(crate) fn merge_grouped_quantities(left: &mut GroupedQuantity, right: &GroupedQuantity) {
+pub(crate) fn merge_grouped_quantities(
+    left: &mut GroupedQuantity,
+    right: &GroupedQuantity,
+    precision: u8,
+) {
     // options here:
     // - same units:
     //    - same value type
@@ -229,15 +308,21 @@ pub(crate) fn merge_grouped_quantities(left: &mut GroupedQuantity, right: &Group
                         let Value::Number { value: assignable } = value else { panic!("Unexpected type") };
                         let Value::Number { value: stored } = v else { panic!("Unexpected type") };
 
-                        *stored += assignable
+                        *stored = round_to(*stored + assignable, precision)
                     },
                     QuantityType::Range => {
                         let Value::Range { start, end } = value else { panic!("Unexpected type") };
                         let Value::Range { start: s, end: e } = v else { panic!("Unexpected type") };
 
                         // is it even correct?
-                        *s += start;
-                        *e += end;
+                        *s = round_to(*s + start, precision);
+                        *e = round_to(*e + end, precision);
+                    },
+                    QuantityType::AtLeast | QuantityType::AtMost => {
+                        let (Value::AtLeast { value: assignable } | Value::AtMost { value: assignable }) = value else { panic!("Unexpected type") };
+                        let (Value::AtLeast { value: stored } | Value::AtMost { value: stored }) = v else { panic!("Unexpected type") };
+
+                        *stored = round_to(*stored + assignable, precision)
                     },
                     QuantityType::Text => {
                         let Value::Text { value: ref assignable } = value else { panic!("Unexpected type") };
@@ -253,17 +338,27 @@ pub(crate) fn merge_grouped_quantities(left: &mut GroupedQuantity, right: &Group
     });
 }
 
-pub(crate) fn into_item(item: &OriginalItem, recipe: &OriginalRecipe) -> Item {
+pub(crate) fn into_item(
+    item: &OriginalItem,
+    recipe: &OriginalRecipe,
+    span: Span,
+    precision: u8,
+) -> Item {
     match item {
         OriginalItem::Text { value } => Item::Text {
             value: value.to_string(),
+            span,
         },
         OriginalItem::Ingredient { index } => {
             let ingredient = &recipe.ingredients[*index];
 
             Item::Ingredient {
                 name: ingredient.name.clone(),
-                amount: ingredient.quantity.as_ref().map(|q| q.extract_amount()),
+                amount: ingredient
+                    .quantity
+                    .as_ref()
+                    .map(|q| q.extract_amount(precision)),
+                span,
             }
         }
 
@@ -271,7 +366,11 @@ pub(crate) fn into_item(item: &OriginalItem, recipe: &OriginalRecipe) -> Item {
             let cookware = &recipe.cookware[*index];
             Item::Cookware {
                 name: cookware.name.clone(),
-                amount: cookware.quantity.as_ref().map(|q| q.extract_amount()),
+                amount: cookware
+                    .quantity
+                    .as_ref()
+                    .map(|q| q.extract_amount(precision)),
+                span,
             }
         }
 
@@ -280,18 +379,25 @@ pub(crate) fn into_item(item: &OriginalItem, recipe: &OriginalRecipe) -> Item {
 
             Item::Timer {
                 name: timer.name.clone(),
-                amount: timer.quantity.as_ref().map(|q| q.extract_amount()),
+                amount: timer.quantity.as_ref().map(|q| q.extract_amount(precision)),
+                span,
             }
         }
 
-        // returning an empty block of text as it's not supported by the spec
-        OriginalItem::InlineQuantity { index: _ } => Item::Text {
-            value: "".to_string(),
+        OriginalItem::InlineQuantity { index } => Item::InlineQuantity {
+            amount: recipe.inline_quantities[*index].extract_amount(precision),
+            span,
         },
     }
 }
 
-pub(crate) fn into_simple_recipe(recipe: &OriginalRecipe) -> CooklangRecipe {
+/// `input` is the source of the recipe, to get the line and column of the
+/// items
+pub(crate) fn into_simple_recipe(
+    recipe: &OriginalRecipe,
+    input: &str,
+    precision: u8,
+) -> CooklangRecipe {
     let mut metadata = CooklangMetadata::new();
     let mut steps: Vec<Step> = Vec::new();
     let mut ingredients: IngredientList = IngredientList::default();
@@ -301,17 +407,22 @@ pub(crate) fn into_simple_recipe(recipe: &OriginalRecipe) -> CooklangRecipe {
     recipe.sections.iter().for_each(|section| {
         section.content.iter().for_each(|content| {
             if let cooklang::Content::Step(step) = content {
-                step.items.iter().for_each(|i| {
-                    let item = into_item(i, recipe);
+                step.items.iter().enumerate().for_each(|(index, i)| {
+                    let span = step
+                        .item_span(index)
+                        .map(|span| into_span(span, input))
+                        .unwrap_or_default();
+                    let item = into_item(i, recipe, span, precision);
 
                     match item {
                         Item::Ingredient {
                             ref name,
                             ref amount,
+                            ..
                         } => {
                             let quantity = into_group_quantity(amount);
 
-                            add_to_ingredient_list(&mut ingredients, name, &quantity);
+                            add_to_ingredient_list(&mut ingredients, name, &quantity, precision);
                         }
                         Item::Cookware { .. } => {
                             cookware.push(item.clone());
@@ -0,0 +1,37 @@
+//! wasm-bindgen exports
+//!
+//! These return the same structs as the UniFFI exports, serialized to JS
+//! values, so both bindings stay in sync.
+
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+pub fn parse_recipe(input: &str) -> JsValue {
+    let recipe = crate::parse_recipe(input.to_string());
+    serde_wasm_bindgen::to_value(&recipe).unwrap_throw()
+}
+
+#[cfg(all(test, target_arch = "wasm32"))]
+mod tests {
+    use wasm_bindgen::JsValue;
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    #[wasm_bindgen_test]
+    fn test_parse_recipe() {
+        let recipe = super::parse_recipe("a test @step @salt{1%mg} more text");
+
+        let get = |value: &JsValue, key: &str| js_sys::Reflect::get(value, &key.into()).unwrap();
+
+        let steps = js_sys::Array::from(&get(&recipe, "steps"));
+        assert_eq!(steps.length(), 1);
+        let items = js_sys::Array::from(&get(&steps.get(0), "items"));
+        assert_eq!(items.length(), 5);
+
+        let salt = items.get(3);
+        assert_eq!(get(&salt, "type").as_string().unwrap(), "Ingredient");
+        assert_eq!(get(&salt, "name").as_string().unwrap(), "salt");
+        let amount = get(&salt, "amount");
+        assert_eq!(get(&amount, "units").as_string().unwrap(), "mg");
+        assert_eq!(get(&get(&amount, "quantity"), "value").as_f64(), Some(1.0));
+    }
+}
@@ -9,7 +9,7 @@ use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 use crate::{
-    error::{CowStr, Label, RichError},
+    error::{CowStr, Label, PassResult, RichError, Severity, SourceDiag, SourceReport, Stage},
     span::Span,
 };
 
@@ -73,16 +73,56 @@ impl AisleConf<'_> {
 }
 
 /// Parse an [`AisleConf`] with the cooklang shopping list format
+///
+/// Comments start with `//` or `--` and end at the end of the line.
 pub fn parse(input: &str) -> Result<AisleConf, AisleConfError> {
+    let (conf, mut duplicates) = parse_impl(input)?;
+    if duplicates.is_empty() {
+        Ok(conf)
+    } else {
+        Err(duplicates.swap_remove(0))
+    }
+}
+
+/// Same as [`parse`] but duplicates are warnings instead of errors
+///
+/// The ingredients of a repeated category are added to the first one and a
+/// repeated ingredient name is ignored.
+///
+/// ```
+/// let input = "[dairy]\nmilk\n[fridge]\nmilk|whole milk\n";
+/// let (conf, report) = cooklang::aisle::parse_lenient(input).into_result().unwrap();
+/// assert_eq!(report.warnings().count(), 1);
+/// assert_eq!(conf.categories[1].ingredients[0].names, ["whole milk"]);
+/// ```
+pub fn parse_lenient(input: &str) -> PassResult<AisleConf<'_>> {
+    let mut report = SourceReport::empty();
+    match parse_impl(input) {
+        Ok((conf, duplicates)) => {
+            for err in duplicates {
+                report.warn(to_diag(err, Severity::Warning));
+            }
+            PassResult::new(Some(conf), report)
+        }
+        Err(err) => {
+            report.error(to_diag(err, Severity::Error));
+            PassResult::new(None, report)
+        }
+    }
+}
+
+/// Parses the input, returning the duplicates found
+fn parse_impl(input: &str) -> Result<(AisleConf<'_>, Vec<AisleConfError>), AisleConfError> {
     let pairs =
         AisleConfParser::parse(Rule::shopping_list, input).map_err(|e| AisleConfError::Parse {
             span: e.location.into(),
             message: e.variant.message().to_string(),
         })?;
 
-    let mut categories = Vec::new();
+    let mut categories: Vec<Category> = Vec::new();
     let mut categories_span = HashMap::new();
     let mut names_span = HashMap::new();
+    let mut duplicates = Vec::new();
 
     for p in pairs.take_while(|p| p.as_rule() != Rule::EOI) {
         let mut pairs = p.into_inner();
@@ -90,14 +130,6 @@ pub fn parse(input: &str) -> Result<AisleConf, AisleConfError> {
         let name = name_pair.as_str().trim();
         let current_span = Span::from(name_pair.as_span());
 
-        if let Some(other) = categories_span.insert(name, current_span) {
-            return Err(AisleConfError::DuplicateCategory {
-                name: name.to_string(),
-                first_span: other,
-                second_span: current_span,
-            });
-        }
-
         let mut ingredients = Vec::new();
         for p in pairs {
             assert_eq!(p.as_rule(), Rule::ingredient, "expected ingredient");
@@ -106,26 +138,59 @@ pub fn parse(input: &str) -> Result<AisleConf, AisleConfError> {
                 assert_eq!(p.as_rule(), Rule::name, "expected name");
                 let name = p.as_str().trim();
                 let span = Span::from(p.as_span());
-                if let Some(other) = names_span.insert(name, span) {
-                    return Err(AisleConfError::DuplicateIngredient {
+                if let Some(&other) = names_span.get(name) {
+                    duplicates.push(AisleConfError::DuplicateIngredient {
                         name: name.to_string(),
                         first_span: other,
                         second_span: span,
                     });
+                    continue;
                 }
+                names_span.insert(name, span);
                 names.push(name);
             }
-            ingredients.push(Ingredient { names });
+            if !names.is_empty() {
+                ingredients.push(Ingredient { names });
+            }
         }
-        let category = Category { name, ingredients };
 
-        categories.push(category);
+        if let Some(&other) = categories_span.get(name) {
+            duplicates.push(AisleConfError::DuplicateCategory {
+                name: name.to_string(),
+                first_span: other,
+                second_span: current_span,
+            });
+            let first = categories.iter_mut().find(|c| c.name == name).unwrap();
+            first.ingredients.extend(ingredients);
+            continue;
+        }
+        categories_span.insert(name, current_span);
+        categories.push(Category { name, ingredients });
     }
 
-    Ok(AisleConf {
+    // keep the errors in the order they appear
+    duplicates.sort_by_key(|e| match e {
+        AisleConfError::DuplicateCategory { second_span, .. }
+        | AisleConfError::DuplicateIngredient { second_span, .. } => second_span.start(),
+        AisleConfError::Parse { span, .. } => span.start(),
+    });
+
+    let conf = AisleConf {
         categories,
         len: std::cell::Cell::new(names_span.len()),
-    })
+    };
+    Ok((conf, duplicates))
+}
+
+fn to_diag(err: AisleConfError, severity: Severity) -> SourceDiag {
+    let mut diag = SourceDiag::unlabeled(err.to_string(), severity, Stage::Parse);
+    for label in err.labels().iter() {
+        diag.add_label(label.clone());
+    }
+    for hint in err.hints().iter() {
+        diag.add_hint(hint.clone());
+    }
+    diag.set_source(err)
 }
 
 /// Write an [`AisleConf`] in the cooklang shopping list format
@@ -369,6 +434,64 @@ tuna|chicken of the sea
         )
     }
 
+    #[test]
+    fn comments() {
+        let input = r#"
+-- dairy products
+[dairy]
+milk|whole milk -- the usual
+// cheese
+butter
+"#;
+        let a = parse(input).unwrap();
+        assert_eq!(
+            a.categories,
+            vec![Category {
+                name: "dairy",
+                ingredients: vec![
+                    Ingredient {
+                        names: vec!["milk", "whole milk"],
+                    },
+                    Ingredient {
+                        names: vec!["butter"],
+                    },
+                ],
+            }]
+        )
+    }
+
+    #[test]
+    fn lenient() {
+        let input = "[dairy]\nmilk\n[produce]\npotatoes\n[dairy]\nbutter|milk\n";
+        assert!(matches!(
+            parse(input),
+            Err(AisleConfError::DuplicateCategory { .. })
+        ));
+        let (a, report) = parse_lenient(input).into_tuple();
+        let a = a.unwrap();
+        assert_eq!(report.warnings().count(), 2);
+        assert!(!report.has_errors());
+        assert_eq!(
+            a.categories[0],
+            Category {
+                name: "dairy",
+                ingredients: vec![
+                    Ingredient {
+                        names: vec!["milk"],
+                    },
+                    Ingredient {
+                        names: vec!["butter"],
+                    },
+                ],
+            }
+        );
+        assert_eq!(a.categories.len(), 2);
+
+        let (a, report) = parse_lenient("[unclosed\n").into_tuple();
+        assert!(a.is_none());
+        assert!(report.has_errors());
+    }
+
     const CONF: &str = r#"
 [produce]
 potatoes
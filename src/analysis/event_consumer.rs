@@ -1,10 +1,10 @@
+use std::borrow::Cow;
 use std::collections::HashMap;
-use std::str::FromStr;
 
 use regex::Regex;
 
 use crate::convert::{Converter, PhysicalQuantity};
-use crate::error::{label, CowStr, PassResult, SourceDiag, SourceReport};
+use crate::error::{label, CowStr, PassResult, Severity, SourceDiag, SourceReport};
 use crate::located::Located;
 use crate::metadata::SpecialKey;
 use crate::parser::{
@@ -12,11 +12,18 @@ use crate::parser::{
     Modifiers,
 };
 use crate::quantity::{Quantity, QuantityValue, ScalableValue, UnitInfo, Value};
+use crate::scale::linear_scale;
 use crate::span::Span;
 use crate::text::Text;
 use crate::{model::*, Extensions, ParseOptions};
 
-use super::{AnalysisResult, DefineMode, DuplicateMode};
+use super::{
+    AnalysisResult, DefineMode, DuplicateDefinition, DuplicateDefinitionPolicy,
+    DuplicateMetadataPolicy, DuplicateMode, ThousandsSeparator,
+};
+
+/// Unit of baker's percentages, see [`ParseOptions::baker_percentage`]
+const BAKER_UNIT: &str = "baker";
 
 macro_rules! error {
     ($msg:expr, $label:expr $(,)?) => {
@@ -76,6 +83,7 @@ pub fn parse_events<'i, 'c>(
             cookware: Default::default(),
             timers: Default::default(),
             inline_quantities: Default::default(),
+            unresolved: Default::default(),
             data: (),
         },
         current_section: Section::default(),
@@ -87,6 +95,7 @@ pub fn parse_events<'i, 'c>(
 
         locations: Default::default(),
         step_counter: 1,
+        description: None,
     };
     col.parse_events(events)
 }
@@ -108,6 +117,8 @@ struct RecipeCollector<'i, 'c> {
 
     locations: Locations<'i>,
     step_counter: u32,
+    /// Leading text, [`None`] once the first step is found
+    description: Option<String>,
 }
 
 #[derive(Default)]
@@ -115,6 +126,24 @@ struct Locations<'i> {
     ingredients: Vec<Located<parser::Ingredient<'i>>>,
     cookware: Vec<Located<parser::Cookware<'i>>>,
     metadata: HashMap<SpecialKey, (Text<'i>, Text<'i>)>,
+    metadata_entries: HashMap<String, Span>,
+    /// Timers that take the duration from the metadata, index and location
+    metadata_timers: Vec<(usize, Span)>,
+}
+
+/// Joins the items at `index - 1` and `index` if both are text
+fn join_text_items(items: &mut Vec<Item>, spans: &mut Vec<Span>, index: usize) {
+    if index == 0 || index >= items.len() {
+        return;
+    }
+    if let [Item::Text { value: before }, Item::Text { value: after }] =
+        &mut items[index - 1..=index]
+    {
+        before.push_str(after);
+        items.remove(index);
+        let span = spans.remove(index);
+        spans[index - 1] = Span::union(spans[index - 1], span);
+    }
 }
 
 const IMPLICIT_REF_WARN: &str = "The reference (&) is implicit";
@@ -122,16 +151,23 @@ const IMPLICIT_REF_WARN: &str = "The reference (&) is implicit";
 impl<'i, 'c> RecipeCollector<'i, 'c> {
     fn parse_events(mut self, mut events: impl Iterator<Item = Event<'i>>) -> AnalysisResult {
         enum BlockBuffer {
-            Step(Vec<Item>),
+            /// Items, their locations and media
+            Step(Vec<Item>, Vec<Span>, Vec<String>),
             Text(String),
         }
         let mut current_block = None;
+        // text around media is joined, as if the media was not there
+        let mut after_media = false;
+        if self.parse_options.leading_description {
+            self.description = Some(String::new());
+        }
 
         let events = events.by_ref();
         while let Some(event) = events.next() {
             match event {
                 Event::Metadata { key, value } => self.metadata(key, value),
                 Event::Section { name } => {
+                    self.end_description();
                     self.step_counter = 1;
                     if !self.current_section.is_empty() {
                         self.content.sections.push(self.current_section);
@@ -144,7 +180,9 @@ impl<'i, 'c> RecipeCollector<'i, 'c> {
                         BlockBuffer::Text(String::new())
                     } else {
                         match kind {
-                            BlockKind::Step => BlockBuffer::Step(Vec::new()),
+                            BlockKind::Step => {
+                                BlockBuffer::Step(Vec::new(), Vec::new(), Vec::new())
+                            }
                             BlockKind::Text => BlockBuffer::Text(String::new()),
                         }
                     };
@@ -152,12 +190,12 @@ impl<'i, 'c> RecipeCollector<'i, 'c> {
                 }
                 Event::End(kind) => {
                     let new_content = match current_block {
-                        Some(BlockBuffer::Step(items)) => {
+                        Some(BlockBuffer::Step(items, spans, media)) => {
                             assert_eq!(kind, BlockKind::Step);
-                            Content::Step(Step {
-                                items,
-                                number: self.step_counter,
-                            })
+                            let mut step = Step::new(items, self.step_counter);
+                            step.spans = spans;
+                            step.media = media;
+                            Content::Step(step)
                         }
                         Some(BlockBuffer::Text(text)) => {
                             assert!(
@@ -168,6 +206,11 @@ impl<'i, 'c> RecipeCollector<'i, 'c> {
                         None => panic!("End event without Start"),
                     };
 
+                    if self.description.is_some() && self.push_description(&new_content) {
+                        current_block = None;
+                        continue;
+                    }
+
                     // If define mode is ingredients, don't add the
                     // step to the section. The components should have been
                     // added to their lists
@@ -183,11 +226,27 @@ impl<'i, 'c> RecipeCollector<'i, 'c> {
                 item @ (Event::Text(_)
                 | Event::Ingredient(_)
                 | Event::Cookware(_)
-                | Event::Timer(_)) => match &mut current_block {
-                    Some(BlockBuffer::Step(items)) => self.in_step(item, items),
+                | Event::Timer(_)
+                | Event::InlineQuantity(_)) => match &mut current_block {
+                    Some(BlockBuffer::Step(items, spans, _)) => {
+                        let index = items.len();
+                        self.in_step(item, items, spans);
+                        if std::mem::take(&mut after_media) {
+                            join_text_items(items, spans, index);
+                        }
+                    }
                     Some(BlockBuffer::Text(text)) => self.in_text(item, text),
                     None => panic!("Content outside block"),
                 },
+                Event::Media(m) => match &mut current_block {
+                    Some(BlockBuffer::Step(_, _, media)) => {
+                        media.push(m.text_trimmed().into_owned());
+                        after_media = true;
+                    }
+                    Some(BlockBuffer::Text(text)) => self.in_text(Event::Media(m), text),
+                    None => panic!("Content outside block"),
+                },
+                Event::Comment { .. } => {}
 
                 Event::Error(e) => {
                     // on a parser error, collect all other parser errors and
@@ -199,21 +258,89 @@ impl<'i, 'c> RecipeCollector<'i, 'c> {
                     });
                     // discard non parser errors/warnings
                     self.ctx.retain(|e| e.stage == crate::error::Stage::Parse);
+                    if self.parse_options.strict {
+                        self.ctx.promote_warnings();
+                    }
                     // return no output
                     return PassResult::new(None, self.ctx);
                 }
                 Event::Warning(w) => self.ctx.warn(w),
             }
         }
+        self.end_description();
         if !self.current_section.is_empty() {
-            self.content.sections.push(self.current_section);
+            let section = std::mem::take(&mut self.current_section);
+            self.content.sections.push(section);
+        }
+        self.resolve_metadata_timers();
+        if let Some(factor) = self.content.metadata.scale() {
+            self.apply_scale(factor);
+        }
+        if let Some(den) = self.content.metadata.fraction_denominator() {
+            self.apply_fraction_denominator(den);
+        }
+        if self.parse_options.strict {
+            self.ctx.promote_warnings();
         }
         PassResult::new(Some(self.content), self.ctx)
     }
 
+    /// Add the content to the description if it's only text
+    fn push_description(&mut self, content: &Content) -> bool {
+        if self
+            .content
+            .metadata
+            .map
+            .contains_key(SpecialKey::Description.as_ref())
+        {
+            self.description = None;
+            return false;
+        }
+        let text = match content {
+            Content::Text(text) => text.clone(),
+            Content::Step(step) => {
+                let mut text = String::new();
+                for item in &step.items {
+                    match item {
+                        Item::Text { value } => text += value,
+                        _ => {
+                            self.end_description();
+                            return false;
+                        }
+                    }
+                }
+                text
+            }
+        };
+        let description = self.description.as_mut().unwrap();
+        if !description.is_empty() {
+            description.push('\n');
+        }
+        description.push_str(text.trim());
+        true
+    }
+
+    /// Stop collecting the description and store it
+    fn end_description(&mut self) {
+        let Some(description) = self.description.take() else {
+            return;
+        };
+        let key = SpecialKey::Description.as_ref();
+        if !description.is_empty() && !self.content.metadata.map.contains_key(key) {
+            self.content
+                .metadata
+                .map
+                .insert(key.to_string(), description);
+        }
+    }
+
     fn metadata(&mut self, key: Text<'i>, value: Text<'i>) {
         let key_t = key.text_trimmed();
-        let value_t = value.text_outer_trimmed();
+        // continuation lines are joined with a single space
+        let value_t = match value.rendered() {
+            Cow::Borrowed(s) => Cow::Borrowed(s.trim()),
+            Cow::Owned(s) => Cow::from(s.trim().to_owned()),
+        };
         let invalid_value = |possible| {
             error!(
                 format!("Invalid value for config key '{key_t}': {value_t}"),
@@ -280,14 +407,47 @@ impl<'i, 'c> RecipeCollector<'i, 'c> {
             }
         }
 
+        // check for duplicates
+        let entry_span = Span::new(key.span().start(), value.span().end());
+        let mut map_value = value_t.to_string();
+        if let Some(&prev_span) = self.locations.metadata_entries.get(key_t.as_ref()) {
+            match &self.parse_options.duplicate_metadata {
+                DuplicateMetadataPolicy::Overwrite => {}
+                DuplicateMetadataPolicy::FirstWins => return,
+                DuplicateMetadataPolicy::Append(separator) => {
+                    if let Some(current) = self.content.metadata.map.get(key_t.as_ref()) {
+                        map_value = format!("{current}{separator}{value_t}");
+                    }
+                }
+                DuplicateMetadataPolicy::Warn => self.ctx.warn(
+                    warning!(
+                        format!("Duplicate metadata key: {key_t}"),
+                        label!(entry_span, "this entry")
+                    )
+                    .label(label!(prev_span, "overrides this one"))
+                    .hint("Only the last value will be used"),
+                ),
+            }
+        }
+        self.locations
+            .metadata_entries
+            .insert(key_t.to_string(), entry_span);
+
         // insert the value into the map
         self.content
             .metadata
             .map
-            .insert(key_t.to_string(), value_t.to_string());
+            .insert(key_t.to_string(), map_value);
+        self.content.metadata.spans.insert(
+            key_t.to_string(),
+            (key.span_trimmed(), value.span_trimmed()),
+        );
+        self.content
+            .metadata
+            .insert_list(&key_t, &self.parse_options.list_keys);
 
         // check if it's a special key
-        if let Ok(sp_key) = SpecialKey::from_str(&key_t) {
+        if let Some(sp_key) = self.parse_options.special_keys.get(&key_t) {
             // always parse servings
             if sp_key != SpecialKey::Servings
                 && !self.extensions.contains(Extensions::SPECIAL_METADATA)
@@ -301,6 +461,16 @@ impl<'i, 'c> RecipeCollector<'i, 'c> {
                     .metadata
                     .insert_special(sp_key, value_t.to_string(), self.converter);
             if let Err(err) = res {
+                if sp_key == SpecialKey::Scale {
+                    self.ctx.error(
+                        error!(
+                            format!("Invalid scale: {value_t}"),
+                            label!(value.span(), "expected a positive number")
+                        )
+                        .set_source(err),
+                    );
+                    return;
+                }
                 self.ctx.warn(
                     warning!(
                         format!(
@@ -329,6 +499,84 @@ impl<'i, 'c> RecipeCollector<'i, 'c> {
         }
     }
 
+    /// Scales all linear values by the factor set in the metadata
+    fn apply_scale(&mut self, factor: f64) {
+        let scale_value = |value: &mut ScalableValue| {
+            if let ScalableValue::Linear(v) = value {
+                if let Ok(scaled) = linear_scale(v.clone(), factor) {
+                    *v = scaled;
+                }
+            }
+        };
+        let content = &mut self.content;
+        let quantities = content
+            .ingredients
+            .iter_mut()
+            .filter_map(|i| i.quantity.as_mut())
+            .chain(
+                content
+                    .timers
+                    .iter_mut()
+                    .filter_map(|t| t.quantity.as_mut()),
+            )
+            .chain(content.inline_quantities.iter_mut());
+        for q in quantities {
+            if matches!(q.value, ScalableValue::Linear(_)) {
+                q.source = None;
+            }
+            scale_value(&mut q.value);
+        }
+        for c in &mut content.cookware {
+            if let Some(value) = &mut c.quantity {
+                scale_value(value);
+            }
+        }
+    }
+
+    /// Rounds the values in units with fractions to multiples of `1/den`
+    fn apply_fraction_denominator(&mut self, den: u8) {
+        let converter = self.converter;
+        let content = &mut self.content;
+        let quantities = content
+            .ingredients
+            .iter_mut()
+            .filter_map(|i| i.quantity.as_mut())
+            .chain(
+                content
+                    .timers
+                    .iter_mut()
+                    .filter_map(|t| t.quantity.as_mut()),
+            )
+            .chain(content.inline_quantities.iter_mut());
+        for q in quantities {
+            if q.is_decimal() {
+                continue;
+            }
+            let Some(UnitInfo::Known(unit)) = q.unit().map(|u| u.unit_info_or_parse(converter))
+            else {
+                continue;
+            };
+            let cfg = converter.fractions_config(&unit);
+            if !cfg.enabled {
+                continue;
+            }
+            let written = q.value.clone();
+            match &mut q.value {
+                ScalableValue::Fixed(v) | ScalableValue::Linear(v) => {
+                    v.try_snap(den, cfg.max_whole);
+                }
+                ScalableValue::ByServings(values) => {
+                    for v in values {
+                        v.try_snap(den, cfg.max_whole);
+                    }
+                }
+            }
+            if q.value != written {
+                q.source = None;
+            }
+        }
+    }
+
     fn time_override_check(&mut self, new: SpecialKey) {
         let locs = |keys: &[SpecialKey]| {
             assert!(!keys.is_empty());
@@ -375,10 +623,10 @@ impl<'i, 'c> RecipeCollector<'i, 'c> {
         self.ctx.warn(warn);
     }
 
-    fn in_step(&mut self, item: Event<'i>, items: &mut Vec<Item>) {
+    fn in_step(&mut self, item: Event<'i>, items: &mut Vec<Item>, spans: &mut Vec<Span>) {
         match item {
             Event::Text(text) => {
-                let t = text.text();
+                let t = text.rendered();
                 if self.define_mode == DefineMode::Components {
                     // only issue warnings for alphanumeric characters
                     // so that the user can format the text with spaces,
@@ -396,42 +644,85 @@ impl<'i, 'c> RecipeCollector<'i, 'c> {
                 if let Some(re) = &self.temperature_regex {
                     debug_assert!(self.extensions.contains(Extensions::TEMPERATURE));
 
+                    // the parts can only be located if the text is the same
+                    // as the input, without escapes or line breaks
+                    let text_span = text.span();
+                    let exact = t == self.input[text_span.range()];
+                    let part_span = |start: usize, len: usize| {
+                        if exact {
+                            let start = text_span.start() + start;
+                            Span::new(start, start + len)
+                        } else {
+                            text_span
+                        }
+                    };
+
                     let mut haystack = t.as_ref();
+                    let mut offset = 0;
                     while let Some((before, temperature, after)) = find_temperature(haystack, re) {
                         if !before.is_empty() {
                             items.push(Item::Text {
                                 value: before.to_string(),
                             });
+                            spans.push(part_span(offset, before.len()));
                         }
 
                         items.push(Item::InlineQuantity {
                             index: self.content.inline_quantities.len(),
                         });
                         self.content.inline_quantities.push(temperature);
+                        let len = haystack.len() - before.len() - after.len();
+                        spans.push(part_span(offset + before.len(), len));
 
+                        offset += haystack.len() - after.len();
                         haystack = after;
                     }
                     if !haystack.is_empty() {
                         items.push(Item::Text {
                             value: haystack.to_string(),
                         });
+                        spans.push(part_span(offset, haystack.len()));
                     }
                 } else {
                     items.push(Item::Text {
                         value: t.into_owned(),
                     });
+                    spans.push(text.span());
                 }
             }
 
-            Event::Ingredient(i) => items.push(Item::Ingredient {
-                index: self.ingredient(i),
-            }),
-            Event::Cookware(i) => items.push(Item::Cookware {
-                index: self.cookware(i),
-            }),
-            Event::Timer(i) => items.push(Item::Timer {
-                index: self.timer(i),
-            }),
+            Event::Ingredient(i) => {
+                spans.push(i.span());
+                items.push(Item::Ingredient {
+                    index: self.ingredient(i),
+                })
+            }
+            Event::Cookware(i) => {
+                spans.push(i.span());
+                items.push(Item::Cookware {
+                    index: self.cookware(i),
+                })
+            }
+            Event::Timer(i) => {
+                spans.push(i.span());
+                items.push(Item::Timer {
+                    index: self.timer(i),
+                })
+            }
+            Event::InlineQuantity(q) => {
+                spans.push(q.span());
+                let mut quantity = self.quantity(q, true);
+                // always scaled, as if they had the auto scale marker
+                if let ScalableValue::Fixed(value) = &quantity.value {
+                    if !value.is_text() {
+                        quantity.value = ScalableValue::Linear(value.clone());
+                    }
+                }
+                items.push(Item::InlineQuantity {
+                    index: self.content.inline_quantities.len(),
+                });
+                self.content.inline_quantities.push(quantity);
+            }
 
             _ => panic!("Unexpected event in step: {item:?}"),
         };
@@ -439,8 +730,12 @@ impl<'i, 'c> RecipeCollector<'i, 'c> {
 
     fn in_text(&mut self, ev: Event<'i>, s: &mut String) {
         match ev {
-            Event::Text(t) => s.push_str(t.text().as_ref()),
-            Event::Ingredient(_) | Event::Cookware(_) | Event::Timer(_) => {
+            Event::Text(t) => s.push_str(t.rendered().as_ref()),
+            Event::Ingredient(_)
+            | Event::Cookware(_)
+            | Event::Timer(_)
+            | Event::InlineQuantity(_)
+            | Event::Media(_) => {
                 assert_eq!(
                     self.define_mode,
                     DefineMode::Text,
@@ -452,6 +747,8 @@ impl<'i, 'c> RecipeCollector<'i, 'c> {
                     Event::Ingredient(i) => ("ingredient", i.span()),
                     Event::Cookware(c) => ("cookware", c.span()),
                     Event::Timer(t) => ("timer", t.span()),
+                    Event::InlineQuantity(q) => ("inline quantity", q.span()),
+                    Event::Media(m) => ("media", m.span()),
                     _ => unreachable!(),
                 };
                 self.ctx
@@ -462,11 +759,24 @@ impl<'i, 'c> RecipeCollector<'i, 'c> {
         }
     }
 
+    /// Position of the step currently being built
+    fn origin(&self) -> ComponentOrigin {
+        ComponentOrigin {
+            section: self.content.sections.len(),
+            step: self.current_section.content.len(),
+        }
+    }
+
     fn ingredient(&mut self, ingredient: Located<parser::Ingredient<'i>>) -> usize {
         let located_ingredient = ingredient.clone();
         let (ingredient, location) = ingredient.take_pair();
 
         let name = ingredient.name.text_trimmed();
+        let pantry = self
+            .parse_options
+            .pantry_check
+            .as_mut()
+            .is_some_and(|check| check(&name));
 
         let mut new_igr = Ingredient {
             name: name.into_owned(),
@@ -478,8 +788,14 @@ impl<'i, 'c> RecipeCollector<'i, 'c> {
                 Vec::new(),
                 self.define_mode != DefineMode::Components,
             ),
+            origin: self.origin(),
+            pantry,
         };
 
+        if ingredient.intermediate_data.is_none() {
+            self.check_duplicate_definition(&mut new_igr, location);
+        }
+
         if let Some(inter_data) = ingredient.intermediate_data {
             assert!(new_igr.modifiers().contains(Modifiers::REF));
             let invalid_modifiers = Modifiers::RECIPE | Modifiers::HIDDEN | Modifiers::NEW;
@@ -549,7 +865,8 @@ impl<'i, 'c> RecipeCollector<'i, 'c> {
                                 } => {
                                     (label!(new, b_q.to_string()), label!(old, a_q.to_string()))
                                 }
-                                crate::quantity::IncompatibleUnits::UnknownDifferentUnits { .. } => {
+                                crate::quantity::IncompatibleUnits::UnknownDifferentUnits { .. }
+                                | crate::quantity::IncompatibleUnits::DifferentCountUnits { .. } => {
                                     (label!(new, "this unit"), label!(old, "differs from this"))
                                 }
                             };
@@ -637,6 +954,11 @@ impl<'i, 'c> RecipeCollector<'i, 'c> {
                 {
                     diag.add_label(label!(location));
                     self.ctx.push(diag);
+                    self.content.unresolved.push(Unresolved {
+                        kind: UnresolvedKind::Recipe,
+                        name: new_igr.name.clone(),
+                        span: location,
+                    });
                 }
             }
         }
@@ -646,6 +968,51 @@ impl<'i, 'c> RecipeCollector<'i, 'c> {
         self.content.ingredients.len() - 1
     }
 
+    /// Warns about an ingredient defined again without the reference modifier
+    ///
+    /// With [`DuplicateDefinitionPolicy::Merge`] it's marked as a reference.
+    fn check_duplicate_definition(&mut self, new: &mut Ingredient<ScalableValue>, location: Span) {
+        let policy = self.parse_options.duplicate_definition;
+        if policy == DuplicateDefinitionPolicy::Allow
+            || new.modifiers.intersects(Modifiers::REF | Modifiers::NEW)
+            || self.define_mode == DefineMode::Steps
+            || self.duplicate_mode == DuplicateMode::Reference
+        {
+            return;
+        }
+
+        let name = unicase::UniCase::new(new.name.as_str());
+        let Some(first) = self.content.ingredients.iter().position(|other| {
+            !other.modifiers.contains(Modifiers::REF)
+                && name == unicase::UniCase::new(other.name.as_str())
+        }) else {
+            return;
+        };
+        let first_span = self.locations.ingredients[first].span();
+
+        let mut warn = warning!(
+            format!("Duplicate ingredient definition: {}", new.name),
+            label!(location, "defined again here")
+        )
+        .label(label!(first_span, "first defined here"));
+        if policy == DuplicateDefinitionPolicy::Merge {
+            new.modifiers |= Modifiers::REF;
+            warn.add_hint("It's used as a reference to the first definition");
+        }
+        self.ctx.warn(
+            warn.hint(format!(
+                "Reference the first definition with '@&{}' or add new (+) to this",
+                new.name
+            ))
+            .set_code("duplicate-definition")
+            .set_source(DuplicateDefinition {
+                name: new.name.clone(),
+                first_span,
+                second_span: location,
+            }),
+        );
+    }
+
     fn resolve_intermediate_ref(
         &mut self,
         inter_data: Located<IntermediateData>,
@@ -776,14 +1143,41 @@ impl<'i, 'c> RecipeCollector<'i, 'c> {
             name: cookware.name.text_trimmed().into_owned(),
             alias: cookware.alias.map(|t| t.text_trimmed().into_owned()),
             quantity: cookware.quantity.map(|q| self.value(q.into_inner(), false)),
+            unit: cookware.unit.map(|t| t.text_trimmed().into_owned()),
             note: cookware.note.map(|n| n.text_trimmed().into_owned()),
             modifiers: cookware.modifiers.into_inner(),
             relation: ComponentRelation::Definition {
                 referenced_from: Vec::new(),
                 defined_in_step: self.define_mode != DefineMode::Components,
             },
+            origin: self.origin(),
         };
 
+        if let (Some(unit), false) = (
+            &new_cw.unit,
+            self.parse_options.allow_component_unit_mismatch,
+        ) {
+            if let UnitInfo::Known(unit) = UnitInfo::new(unit, self.converter) {
+                if unit.physical_quantity != PhysicalQuantity::Count {
+                    let unit_span = located_cookware.unit.as_ref().unwrap().span();
+                    self.ctx.warn(
+                        warning!(
+                            format!(
+                                "Component unit mismatch: cookware unit is not a count: {unit}"
+                            ),
+                            label!(
+                                unit_span,
+                                "expected a count, not {}",
+                                unit.physical_quantity
+                            )
+                        )
+                        .hint("Cookware quantities are usually how many items are needed")
+                        .set_code("component-unit-mismatch"),
+                    );
+                }
+            }
+        }
+
         if let Some((references_to, implicit)) =
             self.resolve_reference(&mut new_cw, location, located_cookware.modifiers.span())
         {
@@ -849,7 +1243,23 @@ impl<'i, 'c> RecipeCollector<'i, 'c> {
 
     fn timer(&mut self, timer: Located<parser::Timer<'i>>) -> usize {
         let located_timer = timer.clone();
-        let (timer, _span) = timer.take_pair();
+        let (timer, span) = timer.take_pair();
+        if let (None, Some(name), true) = (
+            &timer.quantity,
+            &timer.name,
+            self.extensions.contains(Extensions::TIMER_METADATA),
+        ) {
+            // point to the empty braces, like the parser does
+            let name_end = name.span().end();
+            let span = if span.end() > name_end {
+                Span::new(name_end, span.end())
+            } else {
+                Span::pos(name_end)
+            };
+            self.locations
+                .metadata_timers
+                .push((self.content.timers.len(), span));
+        }
         let quantity = timer.quantity.map(|q| {
             let quantity = self.quantity(q, false);
             if self.extensions.contains(Extensions::ADVANCED_UNITS) {
@@ -861,24 +1271,31 @@ impl<'i, 'c> RecipeCollector<'i, 'c> {
                     ));
                 }
                 if let Some(unit) = quantity.unit() {
-                    let unit_span = located_quantity.unit.as_ref().unwrap().span();
-                    match unit.unit_info_or_parse(self.converter) {
-                        UnitInfo::Known(unit) => {
-                            if unit.physical_quantity != PhysicalQuantity::Time {
-                                self.ctx.error(error!(
-                                    format!("Timer unit is not time: {unit}"),
-                                    label!(
-                                        unit_span,
-                                        "expected time, not {}",
-                                        unit.physical_quantity
-                                    )
-                                ));
-                            }
-                        }
-                        UnitInfo::Unknown => self.ctx.error(error!(
+                    if let UnitInfo::Unknown = unit.unit_info_or_parse(self.converter) {
+                        let unit_span = located_quantity.unit.as_ref().unwrap().span();
+                        self.ctx.error(error!(
                             format!("Unknown timer unit: {unit}"),
                             label!(unit_span, "expected time unit")
-                        )),
+                        ));
+                    }
+                }
+            }
+            if !self.parse_options.allow_component_unit_mismatch {
+                let located_quantity = located_timer.quantity.as_ref().unwrap();
+                if let Some(UnitInfo::Known(unit)) = quantity
+                    .unit()
+                    .map(|u| u.unit_info_or_parse(self.converter))
+                {
+                    if unit.physical_quantity != PhysicalQuantity::Time {
+                        let unit_span = located_quantity.unit.as_ref().unwrap().span();
+                        self.ctx.warn(
+                            warning!(
+                                format!("Component unit mismatch: timer unit is not time: {unit}"),
+                                label!(unit_span, "expected time, not {}", unit.physical_quantity)
+                            )
+                            .hint("Timers should have a time unit, like `min`")
+                            .set_code("component-unit-mismatch"),
+                        );
                     }
                 }
             }
@@ -888,30 +1305,157 @@ impl<'i, 'c> RecipeCollector<'i, 'c> {
         let new_timer = Timer {
             name: timer.name.map(|t| t.text_trimmed().into_owned()),
             quantity,
+            origin: self.origin(),
         };
 
         self.content.timers.push(new_timer);
         self.content.timers.len() - 1
     }
 
+    /// Set the duration of the timers without one from the metadata
+    ///
+    /// This is done at the end so the entry can be anywhere in the recipe.
+    fn resolve_metadata_timers(&mut self) {
+        for (index, span) in std::mem::take(&mut self.locations.metadata_timers) {
+            let name = self.content.timers[index].name.as_deref().unwrap();
+            let key = format!("{name} time");
+            let Some(value) = self.content.metadata.map.get(&key) else {
+                let hint = format!("Add the duration to the metadata: `>> {key}: 10 min`");
+                if self.extensions.contains(Extensions::TIMER_REQUIRES_TIME) {
+                    self.ctx.error(
+                        error!(
+                            "Invalid timer: missing quantity",
+                            label!(span, "no duration and no `{}` metadata entry", key)
+                        )
+                        .hint(hint)
+                        .set_code("timer-missing-quantity"),
+                    );
+                } else {
+                    self.ctx.warn(
+                        warning!(
+                            format!("Timer without duration: '{name}'"),
+                            label!(span, "no `{}` metadata entry", key)
+                        )
+                        .hint(hint),
+                    );
+                }
+                continue;
+            };
+            match crate::metadata::parse_time(value, self.converter) {
+                Ok(minutes) => {
+                    self.content.timers[index].quantity = Some(Quantity::new(
+                        ScalableValue::Fixed(Value::from(minutes as f64)),
+                        Some("min".to_string()),
+                    ));
+                }
+                Err(err) => {
+                    let value_span = self.content.metadata.value_span(&key).unwrap();
+                    self.ctx.warn(
+                        warning!(
+                            format!("Invalid duration for timer '{name}': {value}"),
+                            label!(value_span, "expected a time, like `30 min`")
+                        )
+                        .label(label!(span, "used here"))
+                        .set_source(err),
+                    );
+                }
+            }
+        }
+    }
+
     fn quantity(
         &mut self,
         quantity: Located<parser::Quantity<'i>>,
         is_ingredient: bool,
     ) -> Quantity<ScalableValue> {
-        let parser::Quantity { value, unit, .. } = quantity.into_inner();
-        Quantity::new(
-            self.value(value, is_ingredient),
-            unit.map(|t| t.text_trimmed().into_owned()),
-        )
+        let parser::Quantity { value, unit } = quantity.into_inner();
+        let decimal = matches!(
+            value,
+            parser::QuantityValue::Single {
+                decimal: Some(_),
+                ..
+            }
+        );
+        let source = match &value {
+            parser::QuantityValue::Single { value, .. } => {
+                Some(self.input[value.span().range()].trim().into())
+            }
+            parser::QuantityValue::Many(_) => None,
+        };
+        let value = self.value(value, is_ingredient);
+        let mut quantity = match unit
+            .as_ref()
+            .filter(|u| u.text_trimmed().eq_ignore_ascii_case(BAKER_UNIT))
+            .and_then(|u| self.baker_percentage(&value, u.span()))
+        {
+            Some(value) => Quantity::new(value, Some("g".to_string())),
+            None => {
+                let mut q = Quantity::new(value, unit.map(|t| t.text_trimmed().into_owned()));
+                q.source = source;
+                q
+            }
+        };
+        quantity.set_decimal(decimal);
+        quantity
+    }
+
+    /// Resolve a baker's percentage to grams
+    fn baker_percentage(&mut self, value: &ScalableValue, unit: Span) -> Option<ScalableValue> {
+        let Some(reference) = self.parse_options.baker_percentage else {
+            self.ctx.warn(
+                warning!(
+                    "Baker's percentage without a reference weight",
+                    label!(unit, "can't be resolved")
+                )
+                .hint("Set the reference weight to use baker's percentages"),
+            );
+            return None;
+        };
+        let factor = reference / 100.0;
+        let resolve = |v: &Value| match v {
+            Value::Number(n) => Some(Value::Number((n.value() * factor).into())),
+            Value::Range { start, end } => Some(Value::Range {
+                start: (start.value() * factor).into(),
+                end: (end.value() * factor).into(),
+            }),
+            Value::AtLeast(start) => Some(Value::AtLeast((start.value() * factor).into())),
+            Value::AtMost(end) => Some(Value::AtMost((end.value() * factor).into())),
+            Value::Text(_) => None,
+        };
+        match value {
+            ScalableValue::Fixed(v) => resolve(v).map(ScalableValue::Fixed),
+            ScalableValue::Linear(v) => resolve(v).map(ScalableValue::Linear),
+            ScalableValue::ByServings(v) => v
+                .iter()
+                .map(resolve)
+                .collect::<Option<_>>()
+                .map(ScalableValue::ByServings),
+        }
     }
 
-    fn value(&mut self, value: parser::QuantityValue, is_ingredient: bool) -> ScalableValue {
+    fn value(&mut self, mut value: parser::QuantityValue, is_ingredient: bool) -> ScalableValue {
+        if let Some(separator) = self.parse_options.thousands_separator {
+            match &mut value {
+                parser::QuantityValue::Single { value, .. } => {
+                    self.separated_number(value, separator)
+                }
+                parser::QuantityValue::Many(v) => v
+                    .iter_mut()
+                    .for_each(|v| self.separated_number(v, separator)),
+            }
+        }
+
+        match &value {
+            parser::QuantityValue::Single { value, .. } => self.check_non_positive(value),
+            parser::QuantityValue::Many(v) => v.iter().for_each(|v| self.check_non_positive(v)),
+        }
+
         let mut marker_span = None;
         match &value {
             parser::QuantityValue::Single {
                 value,
                 auto_scale: Some(auto_scale_marker),
+                ..
             } => {
                 marker_span = Some(*auto_scale_marker);
                 if value.is_text() {
@@ -979,6 +1523,67 @@ impl<'i, 'c> RecipeCollector<'i, 'c> {
         v
     }
 
+    /// Read a value with thousands separators, from the source as the parser
+    /// may have already read something like `1.000` as a decimal number
+    fn separated_number(&mut self, value: &mut Located<Value>, separator: ThousandsSeparator) {
+        if !matches!(value.value(), Value::Number(_) | Value::Text(_)) {
+            return;
+        }
+        let span = value.span();
+        match separator.parse(&self.input[span.range()]) {
+            Some(Ok(n)) => **value = Value::Number(n.into()),
+            Some(Err(())) => {
+                self.ctx.warn(
+                    warning!(
+                        "Ambiguous number with separators, it will be text",
+                        label!(span, "doesn't follow the configured separators")
+                    )
+                    .hint(match separator {
+                        ThousandsSeparator::Comma => "Use `,` for thousands and `.` for decimals",
+                        ThousandsSeparator::Dot => "Use `.` for thousands and `,` for decimals",
+                    })
+                    .set_code("ambiguous-number"),
+                );
+                **value = Value::Text(self.input[span.range()].trim().to_string());
+            }
+            None => {}
+        }
+    }
+
+    fn check_non_positive(&mut self, value: &Located<Value>) {
+        let min = match value.value() {
+            Value::Number(n) => n.value(),
+            Value::Range { start, end } => start.value().min(end.value()),
+            Value::AtLeast(start) => start.value(),
+            Value::AtMost(end) => end.value(),
+            Value::Text(_) => return,
+        };
+
+        let options = self.parse_options.non_positive_quantity;
+        let (severity, msg, hint) = if min < 0.0 {
+            (
+                options.negative,
+                "Non positive quantity: negative value",
+                "Quantities should be positive, check the value is correct",
+            )
+        } else if min == 0.0 {
+            (
+                options.zero,
+                "Non positive quantity: zero value",
+                "If the amount is not relevant, remove the quantity",
+            )
+        } else {
+            return;
+        };
+
+        let diag = match severity {
+            Severity::Error => error!(msg, label!(value.span())),
+            Severity::Warning => warning!(msg, label!(value.span())),
+        };
+        self.ctx
+            .push(diag.hint(hint).set_code("non-positive-quantity"));
+    }
+
     fn resolve_reference<C: RefComponent>(
         &mut self,
         new: &mut C,
@@ -1119,6 +1724,11 @@ impl<'i, 'c> RecipeCollector<'i, 'c> {
             // extra reference checks
             Some((references_to, implicit))
         } else {
+            self.content.unresolved.push(Unresolved {
+                kind: C::unresolved_kind(),
+                name: new.name().to_string(),
+                span: location,
+            });
             self.ctx.error({
                 let mut e = error!(
                     format!("Reference not found: {}", new.name()),
@@ -1146,6 +1756,7 @@ trait RefComponent: Sized {
     fn inherit_modifiers() -> Modifiers;
 
     fn container() -> &'static str;
+    fn unresolved_kind() -> UnresolvedKind;
 
     fn set_reference(&mut self, references_to: usize);
     fn set_referenced_from(all: &mut [Self], references_to: usize);
@@ -1179,6 +1790,11 @@ impl RefComponent for Ingredient<ScalableValue> {
         "ingredient"
     }
 
+    #[inline]
+    fn unresolved_kind() -> UnresolvedKind {
+        UnresolvedKind::Ingredient
+    }
+
     #[inline]
     fn set_reference(&mut self, references_to: usize) {
         self.relation =
@@ -1227,6 +1843,11 @@ impl RefComponent for Cookware<ScalableValue> {
         "cookware item"
     }
 
+    #[inline]
+    fn unresolved_kind() -> UnresolvedKind {
+        UnresolvedKind::Cookware
+    }
+
     #[inline]
     fn set_reference(&mut self, references_to: usize) {
         self.relation = ComponentRelation::Reference { references_to };
@@ -1248,7 +1869,10 @@ impl RefComponent for Cookware<ScalableValue> {
     }
 }
 
-fn find_temperature<'a>(text: &'a str, re: &Regex) -> Option<(&'a str, Quantity<Value>, &'a str)> {
+fn find_temperature<'a>(
+    text: &'a str,
+    re: &Regex,
+) -> Option<(&'a str, Quantity<ScalableValue>, &'a str)> {
     let Some(caps) = re.captures(text) else {
         return None;
     };
@@ -1256,7 +1880,10 @@ fn find_temperature<'a>(text: &'a str, re: &Regex) -> Option<(&'a str, Quantity<
     let value = caps[1].replace(',', ".").parse::<f64>().ok()?;
     let unit = caps.get(3).unwrap().range();
     let unit_text = text[unit].to_string();
-    let temperature = Quantity::new(Value::Number(value.into()), Some(unit_text));
+    let temperature = Quantity::new(
+        ScalableValue::Fixed(Value::Number(value.into())),
+        Some(unit_text),
+    );
 
     let range = caps.get(0).unwrap().range();
     let (before, after) = (&text[..range.start], &text[range.end..]);
@@ -3,7 +3,9 @@
 //! This is just if for some reason you want to split the parsing from the
 //! analysis.
 
-use crate::error::{CowStr, PassResult, SourceDiag};
+use crate::error::{CowStr, PassResult, Severity, SourceDiag};
+use crate::metadata::{ListKeys, SpecialKeyMap};
+use crate::span::Span;
 use crate::ScalableRecipe;
 
 mod event_consumer;
@@ -39,6 +41,193 @@ pub struct ParseOptions<'a> {
     /// The boolean returned indicates if the value should be included in the
     /// recipe.
     pub metadata_validator: Option<MetadataValidator<'a>>,
+    /// What to do when a metadata key is repeated
+    pub duplicate_metadata: DuplicateMetadataPolicy,
+    /// How to report quantities with a zero or negative value
+    pub non_positive_quantity: NonPositiveQuantity,
+    /// Report every warning as an error
+    ///
+    /// Useful to enforce clean recipes, the result is not valid if there is
+    /// any warning.
+    pub strict: bool,
+    /// Reference weight in grams for baker's percentages
+    ///
+    /// Quantities with the `baker` unit, like `@water{65%baker}`, are a
+    /// percentage of this weight and are resolved to grams. If it's not set,
+    /// those quantities are kept as is and a warning is emitted.
+    pub baker_percentage: Option<f64>,
+    /// Other names for the special metadata keys
+    pub special_keys: SpecialKeyMap,
+    /// Metadata keys whose values are lists
+    ///
+    /// See [`Metadata::list`](crate::Metadata::list).
+    pub list_keys: ListKeys,
+    /// Use the text before the first step as the recipe description
+    ///
+    /// Leading paragraphs and steps without components are removed from the
+    /// recipe and joined in the `description` metadata entry. See
+    /// [`Recipe::description`](crate::Recipe::description). It stops at the
+    /// first section or step with components. If the recipe already has a
+    /// description entry before the text, nothing changes.
+    pub leading_description: bool,
+    /// Parse numbers with thousands separators, like `{1,000%g}`
+    ///
+    /// Off by default. When set, quantity values written only with digits
+    /// and separators are read with the given convention. Values that don't
+    /// follow it, like `1,5` with [`ThousandsSeparator::Comma`], are
+    /// ambiguous and are kept as text with a warning.
+    pub thousands_separator: Option<ThousandsSeparator>,
+    /// Check if an ingredient is a pantry staple
+    ///
+    /// The function receives the ingredient name and the result is stored in
+    /// [`Ingredient::pantry`](crate::Ingredient::pantry). Pantry ingredients
+    /// are not added to an [`IngredientList`](crate::ingredient_list::IngredientList)
+    /// by default.
+    pub pantry_check: Option<PantryCheck<'a>>,
+    /// Don't warn when a component unit doesn't fit the component
+    ///
+    /// Timers are expected to have a time unit, like `~{5%min}`, and cookware
+    /// units, with [`COOKWARE_UNITS`](crate::Extensions::COOKWARE_UNITS), a
+    /// count unit. A known unit of another physical quantity, like `~{5%g}` or
+    /// `#pan{2%ml}`, is a warning with the `component-unit-mismatch` code.
+    /// Unknown units are not checked.
+    pub allow_component_unit_mismatch: bool,
+    /// What to do when an ingredient is defined more than once
+    ///
+    /// An ingredient written again without the reference modifier, like
+    /// `@flour{200%g}` twice, is a new definition and it's ambiguous when
+    /// calculating the total amount. Ingredients with the new modifier (`+`)
+    /// or in a mode where duplicates are references are not checked.
+    pub duplicate_definition: DuplicateDefinitionPolicy,
+}
+
+/// Policy for repeated metadata keys. See [`ParseOptions`]
+///
+/// Special keys that accumulate values, like `tags`, keep doing so unless
+/// [`Self::FirstWins`] is used.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum DuplicateMetadataPolicy {
+    /// The last value replaces the previous ones
+    #[default]
+    Overwrite,
+    /// The first value is kept and the rest are ignored
+    FirstWins,
+    /// The values are joined with the given separator
+    Append(String),
+    /// Like [`Self::Overwrite`] but a warning is emitted
+    Warn,
+}
+
+/// Policy for ingredients defined more than once. See [`ParseOptions`]
+///
+/// The warnings have the `duplicate-definition` code and a
+/// [`DuplicateDefinition`] as the source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DuplicateDefinitionPolicy {
+    /// Keep them as different ingredients without any warning
+    ///
+    /// This is the default, the
+    /// [`DUPLICATE_DEFINITION`](crate::lint::Lints::DUPLICATE_DEFINITION)
+    /// lint reports the same.
+    #[default]
+    Allow,
+    /// Keep them as different ingredients and emit a warning
+    Warn,
+    /// The duplicate is a reference to the first definition, as if it had the
+    /// reference modifier (`&`), and a warning is emitted
+    Merge,
+}
+
+/// An ingredient defined more than once. See [`DuplicateDefinitionPolicy`]
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("Ingredient '{name}' is defined more than once")]
+pub struct DuplicateDefinition {
+    /// Name of the ingredient
+    pub name: String,
+    /// Location of the first definition
+    pub first_span: Span,
+    /// Location of the duplicate
+    pub second_span: Span,
+}
+
+/// Severity of the diagnostic emitted for zero or negative quantity values.
+/// See [`ParseOptions`]
+///
+/// By default zero is a warning, as "0 salt" can be meaningful, and negative
+/// values are an error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NonPositiveQuantity {
+    /// Severity for values equal to zero
+    pub zero: Severity,
+    /// Severity for values lower than zero
+    pub negative: Severity,
+}
+
+impl Default for NonPositiveQuantity {
+    fn default() -> Self {
+        Self {
+            zero: Severity::Warning,
+            negative: Severity::Error,
+        }
+    }
+}
+
+/// Convention for numbers with thousands separators. See [`ParseOptions`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThousandsSeparator {
+    /// Comma to group thousands and dot for decimals, like `1,000.5`
+    Comma,
+    /// Dot to group thousands and comma for decimals, like `1.000,5`
+    Dot,
+}
+
+impl ThousandsSeparator {
+    fn chars(self) -> (char, char) {
+        match self {
+            ThousandsSeparator::Comma => (',', '.'),
+            ThousandsSeparator::Dot => ('.', ','),
+        }
+    }
+
+    /// Parse a number with separators
+    ///
+    /// Returns [`None`] if it's not a number with separators and `Some(Err)`
+    /// if it doesn't follow the convention.
+    pub(crate) fn parse(self, s: &str) -> Option<Result<f64, ()>> {
+        let s = s.trim();
+        let is_separator = |c: char| c == ',' || c == '.';
+        if !s.contains(is_separator) || !s.chars().all(|c| c.is_ascii_digit() || is_separator(c)) {
+            return None;
+        }
+        let (group, decimal) = self.chars();
+        let is_digits = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+
+        let (int_part, frac_part) = match s.split_once(decimal) {
+            Some((int_part, frac_part)) => (int_part, Some(frac_part)),
+            None => (s, None),
+        };
+        if frac_part.is_some_and(|f| !is_digits(f)) {
+            return Some(Err(()));
+        }
+        let mut groups = int_part.split(group);
+        let first = groups.next().unwrap();
+        let valid = if int_part.contains(group) {
+            is_digits(first) && first.len() <= 3 && groups.all(|g| g.len() == 3 && is_digits(g))
+        } else {
+            // `.5` is a valid number
+            is_digits(first) || first.is_empty() && frac_part.is_some()
+        };
+        if !valid {
+            return Some(Err(()));
+        }
+
+        let mut number = int_part.replace(group, "");
+        if let Some(frac_part) = frac_part {
+            number.push('.');
+            number.push_str(frac_part);
+        }
+        Some(number.parse().map_err(|_| ()))
+    }
 }
 
 /// Return type for check functions in [`ParseOptions`]
@@ -73,4 +262,27 @@ impl CheckResult {
 }
 
 pub type RecipeRefCheck<'a> = Box<dyn FnMut(&str) -> CheckResult + 'a>;
+pub type PantryCheck<'a> = Box<dyn FnMut(&str) -> bool + 'a>;
 pub type MetadataValidator<'a> = Box<dyn FnMut(&str, &str) -> (CheckResult, bool) + 'a>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test_case(ThousandsSeparator::Comma, "1,000" => Some(Ok(1000.0)); "comma")]
+    #[test_case(ThousandsSeparator::Comma, "12,345,678.5" => Some(Ok(12345678.5)); "comma many groups")]
+    #[test_case(ThousandsSeparator::Comma, "1.5" => Some(Ok(1.5)); "comma only decimal")]
+    #[test_case(ThousandsSeparator::Comma, "1,5" => Some(Err(())); "comma ambiguous")]
+    #[test_case(ThousandsSeparator::Comma, "1.000,5" => Some(Err(())); "comma other convention")]
+    #[test_case(ThousandsSeparator::Dot, "1.000,5" => Some(Ok(1000.5)); "dot")]
+    #[test_case(ThousandsSeparator::Dot, "1.000" => Some(Ok(1000.0)); "dot no decimals")]
+    #[test_case(ThousandsSeparator::Dot, "1,5" => Some(Ok(1.5)); "dot only decimal")]
+    #[test_case(ThousandsSeparator::Dot, "1.5" => Some(Err(())); "dot ambiguous")]
+    #[test_case(ThousandsSeparator::Comma, ".5" => Some(Ok(0.5)); "no integer part")]
+    #[test_case(ThousandsSeparator::Comma, "1000" => None; "no separators")]
+    #[test_case(ThousandsSeparator::Comma, "a pinch, or two" => None; "text")]
+    fn thousands_separator(sep: ThousandsSeparator, s: &str) -> Option<Result<f64, ()>> {
+        sep.parse(s)
+    }
+}
@@ -6,11 +6,20 @@
 //! [`Recipe`](crate::model::Recipe). So this is just an optional intermediate
 //! representation of the file and not a complete parsed recipe.
 
+use std::{fmt::Write, ops::Range};
+
 use serde::Serialize;
 
 use crate::{
     error::{PassResult, SourceReport},
-    parser::{Block, BlockKind, Event, Item},
+    located::Located,
+    parser::{
+        Block, BlockKind, Cookware, Event, Ingredient, Item, Modifiers, PullParser, Quantity,
+        QuantityValue, Timer,
+    },
+    span::Span,
+    text::Text,
+    Extensions,
 };
 
 /// Abstract syntax tree of a cooklang file
@@ -63,6 +72,9 @@ pub fn build_ast<'i>(events: impl Iterator<Item = Event<'i>>) -> PassResult<Ast<
             Event::Ingredient(c) => items.push(Item::Ingredient(Box::new(c))),
             Event::Cookware(c) => items.push(Item::Cookware(Box::new(c))),
             Event::Timer(c) => items.push(Item::Timer(Box::new(c))),
+            Event::InlineQuantity(q) => items.push(Item::InlineQuantity(Box::new(q))),
+            Event::Media(m) => items.push(Item::Media(Box::new(m))),
+            Event::Comment { .. } => {}
             Event::Error(e) => ctx.push(e),
             Event::Warning(w) => ctx.push(w),
         }
@@ -70,3 +82,931 @@ pub fn build_ast<'i>(events: impl Iterator<Item = Event<'i>>) -> PassResult<Ast<
     let ast = Ast { blocks };
     PassResult::new(Some(ast), ctx)
 }
+
+/// Same as [`build_ast`] with a [`PullParser`] but the output owns the input
+///
+/// [`Ast`] borrows from the input, so storing it in long lived structures
+/// means keeping the input around with the right lifetime. Use this when
+/// that's inconvenient, the [`OwnedAst`] keeps the input and all the spans are
+/// relative to [`OwnedAst::source`].
+///
+/// ```
+/// # use cooklang::{ast::{parse_owned, OwnedAst}, parser::{Block, Item}, Extensions};
+/// struct Cache {
+///     recipe: OwnedAst,
+/// }
+///
+/// let input = String::from("Add @salt.");
+/// let cache = Cache {
+///     recipe: parse_owned(input, Extensions::all()).unwrap_output(),
+/// };
+///
+/// let ast = cache.recipe.ast();
+/// let Block::Step { items } = &ast.blocks[0] else { panic!() };
+/// let Item::Ingredient(igr) = &items[1] else { panic!() };
+/// assert_eq!(&cache.recipe.source()[igr.span().range()], "@salt");
+/// ```
+pub fn parse_owned(input: impl Into<String>, extensions: Extensions) -> PassResult<OwnedAst> {
+    let source = input.into();
+    let report = build_ast(PullParser::new(&source, extensions)).into_report();
+    let owned = OwnedAst { source, extensions };
+    PassResult::new(Some(owned), report)
+}
+
+/// An [`Ast`] that owns its source code. See [`parse_owned`]
+///
+/// Only the source is stored, every call to [`OwnedAst::ast`] parses it
+/// again. The errors and warnings are only reported by [`parse_owned`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct OwnedAst {
+    source: String,
+    extensions: Extensions,
+}
+
+impl OwnedAst {
+    /// Get the source code
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Get the extensions used to parse the source
+    pub fn extensions(&self) -> Extensions {
+        self.extensions
+    }
+
+    /// Get the [`Ast`], borrowed from [`Self::source`]
+    pub fn ast(&self) -> Ast<'_> {
+        build_ast(PullParser::new(&self.source, self.extensions))
+            .into_output()
+            .unwrap_or(Ast { blocks: Vec::new() })
+    }
+}
+
+/// Parse a file with several recipes into an [`Ast`] for each one
+///
+/// Recipes are separated by a line with only 5 or more `=`, like `=====`.
+/// Because `=` is also the section marker, the rules are:
+/// - The separator line can have whitespace around the `=` but nothing
+///   else. `===== Dough =====` or `== Dough` are still sections.
+/// - A separator can't have a title, because `===== Dough` is also a
+///   section. Put the title in the metadata of the recipe after it, like
+///   `>> title: Dough`.
+/// - A line with less than 5 `=`, like `===`, is still a section without
+///   name.
+/// - There is no escape, a line of 5 or more `=` inside a block comment also
+///   separates recipes.
+///
+/// Parts with only whitespace are skipped, so separators at the start or end
+/// of the file or right after another do not create empty recipes. All the
+/// spans are relative to the whole `input`.
+///
+/// ```
+/// # use cooklang::{ast::parse_collection, Extensions};
+/// let input = "Fry @eggs{2}.\n=====\nToast the @bread{1%slice}.";
+/// let recipes = parse_collection(input, Extensions::all());
+/// assert_eq!(recipes.len(), 2);
+/// ```
+pub fn parse_collection(input: &str, extensions: Extensions) -> Vec<PassResult<Ast<'_>>> {
+    let mut ranges = Vec::new();
+    let mut start = 0;
+    let mut offset = 0;
+    for line in input.split_inclusive('\n') {
+        if is_recipe_separator(line) {
+            ranges.push(start..offset);
+            start = offset + line.len();
+        }
+        offset += line.len();
+    }
+    ranges.push(start..input.len());
+
+    ranges
+        .into_iter()
+        .filter(|range| !input[range.clone()].trim().is_empty())
+        .map(|range| build_ast(PullParser::with_range(input, range, extensions)))
+        .collect()
+}
+
+fn is_recipe_separator(line: &str) -> bool {
+    let line = line.trim();
+    line.len() >= 5 && line.bytes().all(|b| b == b'=')
+}
+
+/// Update an [`Ast`] after an edit without parsing the whole source again
+///
+/// `old` is the AST of `old_source` and `source` the text after the edit.
+/// `edited` is the replaced [`Span`] of `old_source`. Only the blocks around
+/// the edit are parsed again, the rest are reused.
+///
+/// Returns the new AST and the indices of the new blocks that were parsed
+/// again. The report only contains the errors and warnings of those blocks.
+///
+/// The whole `source` is parsed again when the edit adds or removes a block
+/// comment delimiter (`[-` or `-]`), because it can change blocks far from
+/// the edit, or when `old`, `old_source`, `source` and `edited` do not match.
+///
+/// The result is the same as parsing `source` from scratch, as long as
+/// `old` was built with the same `extensions` and without extra component
+/// markers.
+///
+/// ```
+/// # use cooklang::{ast::{build_ast, reparse_range}, parser::PullParser, span::Span, Extensions};
+/// let old_source = "Add @salt.\n\nAdd @pepper.\n\nMix.";
+/// let old = build_ast(PullParser::new(old_source, Extensions::all())).unwrap_output();
+///
+/// let source = "Add @salt.\n\nAdd @chili.\n\nMix.";
+/// let edited = Span::from(17..23);
+/// let (ast, changed) =
+///     reparse_range(&old, old_source, source, edited, Extensions::all()).unwrap_output();
+///
+/// let full = build_ast(PullParser::new(source, Extensions::all())).unwrap_output();
+/// assert_eq!(format!("{ast:?}"), format!("{full:?}"));
+/// assert!(changed.contains(&1));
+/// ```
+pub fn reparse_range<'a>(
+    old: &Ast<'_>,
+    old_source: &str,
+    source: &'a str,
+    edited: Span,
+    extensions: Extensions,
+) -> PassResult<(Ast<'a>, Range<usize>)> {
+    let delta = source.len() as isize - old_source.len() as isize;
+    let Some(new_end) = edited.end().checked_add_signed(delta) else {
+        return full_reparse(source, extensions);
+    };
+    let unchanged = new_end >= edited.start()
+        && old_source.get(..edited.start()) == source.get(..edited.start())
+        && old_source.get(edited.end()..).is_some()
+        && old_source.get(edited.end()..) == source.get(new_end..);
+    if !unchanged
+        || has_block_comment_delimiter(old_source, edited.range())
+        || has_block_comment_delimiter(source, edited.start()..new_end)
+    {
+        return full_reparse(source, extensions);
+    }
+
+    let spans: Vec<Option<Span>> = old.blocks.iter().map(block_span).collect();
+    if spans
+        .iter()
+        .flatten()
+        .any(|s| old_source.get(s.range()).is_none())
+    {
+        return full_reparse(source, extensions);
+    }
+    // Blocks right next to the edit are parsed again too, because the edit
+    // could join them or split them.
+    let first = spans
+        .iter()
+        .rposition(|s| matches!(s, Some(s) if s.end() < edited.start()));
+    let last = spans
+        .iter()
+        .position(|s| matches!(s, Some(s) if s.start() > edited.end()));
+
+    let prefix = first.unwrap_or(0);
+    let start = match first {
+        Some(i) => line_start(source, spans[i].unwrap().start()),
+        None => 0,
+    };
+    let end = match last {
+        Some(i) => line_end(source, spans[i].unwrap().shift(delta).end()),
+        None => source.len(),
+    };
+    let suffix = last.map(|i| i + 1).unwrap_or(old.blocks.len());
+
+    let rebased = old.blocks[..prefix]
+        .iter()
+        .map(|b| rebase_block(b, source, 0))
+        .collect::<Option<Vec<_>>>();
+    let rebased_suffix = old.blocks[suffix..]
+        .iter()
+        .map(|b| rebase_block(b, source, delta))
+        .collect::<Option<Vec<_>>>();
+    let (Some(mut blocks), Some(rebased_suffix)) = (rebased, rebased_suffix) else {
+        return full_reparse(source, extensions);
+    };
+
+    let parser = PullParser::with_range(source, start..end, extensions);
+    let (reparsed, report) = build_ast(parser).into_tuple();
+    let reparsed = reparsed.expect("build_ast always has output").blocks;
+
+    let changed = blocks.len()..blocks.len() + reparsed.len();
+    blocks.extend(reparsed);
+    blocks.extend(rebased_suffix);
+
+    PassResult::new(Some((Ast { blocks }, changed)), report)
+}
+
+/// Parse all `source`, every block changed
+fn full_reparse(source: &str, extensions: Extensions) -> PassResult<(Ast<'_>, Range<usize>)> {
+    let (ast, report) = build_ast(PullParser::new(source, extensions)).into_tuple();
+    let ast = ast.expect("build_ast always has output");
+    let changed = 0..ast.blocks.len();
+    PassResult::new(Some((ast, changed)), report)
+}
+
+/// Checks for `[-` or `-]` touching `range`, one byte around it included
+/// because the edit can complete a delimiter
+fn has_block_comment_delimiter(source: &str, range: Range<usize>) -> bool {
+    let bytes = source.as_bytes();
+    let start = range.start.saturating_sub(1);
+    let end = (range.end + 1).min(bytes.len());
+    bytes
+        .get(start..end)
+        .is_some_and(|b| b.windows(2).any(|w| w == b"[-" || w == b"-]"))
+}
+
+fn line_start(source: &str, pos: usize) -> usize {
+    source[..pos].rfind('\n').map(|i| i + 1).unwrap_or(0)
+}
+
+fn line_end(source: &str, pos: usize) -> usize {
+    source[pos..]
+        .find('\n')
+        .map(|i| pos + i)
+        .unwrap_or(source.len())
+}
+
+/// Location of a block, a section without name has none
+fn block_span(block: &Block) -> Option<Span> {
+    match block {
+        Block::Metadata { key, value } => Some(Span::union(key.span(), value.span())),
+        Block::Section { name } => name.as_ref().map(Text::span),
+        Block::Step { items } => Some(Span::union(items.first()?.span(), items.last()?.span())),
+        Block::TextBlock(texts) => Some(Span::union(texts.first()?.span(), texts.last()?.span())),
+    }
+}
+
+fn rebase_located<T: Clone>(l: &Located<T>, delta: isize) -> Located<T> {
+    Located::new(l.value().clone(), l.span().shift(delta))
+}
+
+fn rebase_quantity_value(value: &QuantityValue, delta: isize) -> QuantityValue {
+    match value {
+        QuantityValue::Single {
+            value,
+            auto_scale,
+            decimal,
+        } => QuantityValue::Single {
+            value: rebase_located(value, delta),
+            auto_scale: auto_scale.map(|s| s.shift(delta)),
+            decimal: decimal.map(|s| s.shift(delta)),
+        },
+        QuantityValue::Many(values) => {
+            QuantityValue::Many(values.iter().map(|v| rebase_located(v, delta)).collect())
+        }
+    }
+}
+
+fn rebase_quantity<'b>(
+    q: &Located<Quantity>,
+    source: &'b str,
+    delta: isize,
+) -> Option<Located<Quantity<'b>>> {
+    let unit = match &q.unit {
+        Some(t) => Some(t.rebase(source, delta)?),
+        None => None,
+    };
+    let quantity = Quantity {
+        value: rebase_quantity_value(&q.value, delta),
+        unit,
+    };
+    Some(Located::new(quantity, q.span().shift(delta)))
+}
+
+/// Same block borrowed from `source`, where it starts `delta` bytes later
+///
+/// Returns [`None`] if the block is not in `source` at that position.
+fn rebase_block<'b>(block: &Block, source: &'b str, delta: isize) -> Option<Block<'b>> {
+    let text = |t: &Text| t.rebase(source, delta);
+    let opt_text = |t: &Option<Text>| match t {
+        Some(t) => text(t).map(Some),
+        None => Some(None),
+    };
+    let quantity = |q: &Option<Located<Quantity>>| match q {
+        Some(q) => rebase_quantity(q, source, delta).map(Some),
+        None => Some(None),
+    };
+    let block = match block {
+        Block::Metadata { key, value } => Block::Metadata {
+            key: text(key)?,
+            value: text(value)?,
+        },
+        Block::Section { name } => Block::Section {
+            name: opt_text(name)?,
+        },
+        Block::Step { items } => Block::Step {
+            items: items
+                .iter()
+                .map(|item| {
+                    let item = match item {
+                        Item::Text(t) => Item::Text(text(t)?),
+                        Item::Ingredient(c) => {
+                            let igr = Ingredient {
+                                modifiers: rebase_located(&c.modifiers, delta),
+                                intermediate_data: c
+                                    .intermediate_data
+                                    .as_ref()
+                                    .map(|d| rebase_located(d, delta)),
+                                name: text(&c.name)?,
+                                alias: opt_text(&c.alias)?,
+                                quantity: quantity(&c.quantity)?,
+                                note: opt_text(&c.note)?,
+                            };
+                            Item::Ingredient(Box::new(Located::new(igr, c.span().shift(delta))))
+                        }
+                        Item::Cookware(c) => {
+                            let cw = Cookware {
+                                modifiers: rebase_located(&c.modifiers, delta),
+                                name: text(&c.name)?,
+                                alias: opt_text(&c.alias)?,
+                                quantity: c.quantity.as_ref().map(|q| {
+                                    Located::new(
+                                        rebase_quantity_value(q, delta),
+                                        q.span().shift(delta),
+                                    )
+                                }),
+                                unit: opt_text(&c.unit)?,
+                                note: opt_text(&c.note)?,
+                            };
+                            Item::Cookware(Box::new(Located::new(cw, c.span().shift(delta))))
+                        }
+                        Item::Timer(c) => {
+                            let timer = Timer {
+                                name: opt_text(&c.name)?,
+                                quantity: quantity(&c.quantity)?,
+                            };
+                            Item::Timer(Box::new(Located::new(timer, c.span().shift(delta))))
+                        }
+                        Item::InlineQuantity(q) => {
+                            Item::InlineQuantity(Box::new(rebase_quantity(q, source, delta)?))
+                        }
+                        Item::Media(m) => {
+                            Item::Media(Box::new(Located::new(text(m)?, m.span().shift(delta))))
+                        }
+                    };
+                    Some(item)
+                })
+                .collect::<Option<_>>()?,
+        },
+        Block::TextBlock(texts) => Block::TextBlock(texts.iter().map(text).collect::<Option<_>>()?),
+    };
+    Some(block)
+}
+
+/// Read only traversal of an [`Ast`]
+///
+/// Every method has a default implementation that does nothing, so only
+/// implement the ones you need and then call [`Ast::walk`].
+///
+/// ```
+/// # use cooklang::{ast::{build_ast, Visitor}, parser::{Ingredient, PullParser}, Extensions, Located};
+/// struct Names(Vec<String>);
+///
+/// impl<'a> Visitor<'a> for Names {
+///     fn visit_ingredient(&mut self, ingredient: &Located<Ingredient<'a>>) {
+///         self.0.push(ingredient.name.text_trimmed().to_lowercase());
+///     }
+/// }
+///
+/// let input = "Add @Flour and @WATER.";
+/// let ast = build_ast(PullParser::new(input, Extensions::all())).unwrap_output();
+/// let mut names = Names(Vec::new());
+/// ast.walk(&mut names);
+/// assert_eq!(names.0, ["flour", "water"]);
+/// ```
+pub trait Visitor<'a> {
+    /// Called for each metadata entry
+    fn visit_metadata(&mut self, _key: &Text<'a>, _value: &Text<'a>) {}
+    /// Called for each section divider
+    fn visit_section(&mut self, _name: Option<&Text<'a>>) {}
+    /// Called for each text, both in steps and text blocks
+    fn visit_text(&mut self, _text: &Text<'a>) {}
+    /// Called for each ingredient
+    fn visit_ingredient(&mut self, _ingredient: &Located<Ingredient<'a>>) {}
+    /// Called for each cookware item
+    fn visit_cookware(&mut self, _cookware: &Located<Cookware<'a>>) {}
+    /// Called for each timer
+    fn visit_timer(&mut self, _timer: &Located<Timer<'a>>) {}
+    /// Called for each inline quantity
+    fn visit_inline_quantity(&mut self, _quantity: &Located<Quantity<'a>>) {}
+    /// Called for each media item
+    fn visit_media(&mut self, _media: &Located<Text<'a>>) {}
+}
+
+/// Mutable traversal of an [`Ast`]
+///
+/// Like [`Visitor`] but the elements can be changed in place. Then call
+/// [`Ast::walk_mut`].
+///
+/// [`Text`] is borrowed from the input, so it can't be edited, but it can be
+/// replaced with other [`Text`] or removed where it's optional.
+///
+/// ```
+/// # use cooklang::{ast::{build_ast, VisitorMut}, parser::{Ingredient, PullParser}, Extensions, Located};
+/// struct RemoveNotes;
+///
+/// impl<'a> VisitorMut<'a> for RemoveNotes {
+///     fn visit_ingredient(&mut self, ingredient: &mut Located<Ingredient<'a>>) {
+///         ingredient.note = None;
+///     }
+/// }
+///
+/// let input = "Add @flour{}(sifted).";
+/// let mut ast = build_ast(PullParser::new(input, Extensions::all())).unwrap_output();
+/// ast.walk_mut(&mut RemoveNotes);
+/// # use cooklang::parser::{Block, Item};
+/// # let Block::Step { items } = &ast.blocks[0] else { panic!() };
+/// # let Item::Ingredient(igr) = &items[1] else { panic!() };
+/// assert!(igr.note.is_none());
+/// ```
+pub trait VisitorMut<'a> {
+    /// Called for each metadata entry
+    fn visit_metadata(&mut self, _key: &mut Text<'a>, _value: &mut Text<'a>) {}
+    /// Called for each section divider
+    fn visit_section(&mut self, _name: &mut Option<Text<'a>>) {}
+    /// Called for each text, both in steps and text blocks
+    fn visit_text(&mut self, _text: &mut Text<'a>) {}
+    /// Called for each ingredient
+    fn visit_ingredient(&mut self, _ingredient: &mut Located<Ingredient<'a>>) {}
+    /// Called for each cookware item
+    fn visit_cookware(&mut self, _cookware: &mut Located<Cookware<'a>>) {}
+    /// Called for each timer
+    fn visit_timer(&mut self, _timer: &mut Located<Timer<'a>>) {}
+    /// Called for each inline quantity
+    fn visit_inline_quantity(&mut self, _quantity: &mut Located<Quantity<'a>>) {}
+    /// Called for each media item
+    fn visit_media(&mut self, _media: &mut Located<Text<'a>>) {}
+}
+
+impl<'a> Ast<'a> {
+    /// Walk the tree in order calling the [`Visitor`] methods
+    pub fn walk(&self, visitor: &mut impl Visitor<'a>) {
+        for block in &self.blocks {
+            match block {
+                Block::Metadata { key, value } => visitor.visit_metadata(key, value),
+                Block::Section { name } => visitor.visit_section(name.as_ref()),
+                Block::Step { items } => {
+                    for item in items {
+                        match item {
+                            Item::Text(t) => visitor.visit_text(t),
+                            Item::Ingredient(c) => visitor.visit_ingredient(c),
+                            Item::Cookware(c) => visitor.visit_cookware(c),
+                            Item::Timer(c) => visitor.visit_timer(c),
+                            Item::InlineQuantity(q) => visitor.visit_inline_quantity(q),
+                            Item::Media(m) => visitor.visit_media(m),
+                        }
+                    }
+                }
+                Block::TextBlock(texts) => texts.iter().for_each(|t| visitor.visit_text(t)),
+            }
+        }
+    }
+
+    /// Walk the tree in order calling the [`VisitorMut`] methods
+    pub fn walk_mut(&mut self, visitor: &mut impl VisitorMut<'a>) {
+        for block in &mut self.blocks {
+            match block {
+                Block::Metadata { key, value } => visitor.visit_metadata(key, value),
+                Block::Section { name } => visitor.visit_section(name),
+                Block::Step { items } => {
+                    for item in items {
+                        match item {
+                            Item::Text(t) => visitor.visit_text(t),
+                            Item::Ingredient(c) => visitor.visit_ingredient(c),
+                            Item::Cookware(c) => visitor.visit_cookware(c),
+                            Item::Timer(c) => visitor.visit_timer(c),
+                            Item::InlineQuantity(q) => visitor.visit_inline_quantity(q),
+                            Item::Media(m) => visitor.visit_media(m),
+                        }
+                    }
+                }
+                Block::TextBlock(texts) => texts.iter_mut().for_each(|t| visitor.visit_text(t)),
+            }
+        }
+    }
+
+    /// Tree representation to debug the parser output
+    ///
+    /// Each block, item and part of an item is in its own line, indented
+    /// under its parent, followed by its location in the input. Texts are
+    /// quoted as they are in the AST, with all the whitespace.
+    ///
+    /// This is not cooklang, it's meant to understand the structure the
+    /// parser found, like when reporting a bug.
+    ///
+    /// ```
+    /// # use cooklang::{ast::build_ast, parser::PullParser, Extensions};
+    /// let ast = build_ast(PullParser::new("Add @salt.", Extensions::all())).unwrap_output();
+    /// assert_eq!(
+    ///     ast.tree_string(),
+    ///     "Step\n  Text \"Add \" 0..4\n  Ingredient 4..9\n    name \"salt\" 5..9\n  Text \".\" 9..10\n"
+    /// );
+    /// ```
+    pub fn tree_string(&self) -> String {
+        let mut tree = Tree::default();
+        for block in &self.blocks {
+            tree.block(block);
+        }
+        tree.out
+    }
+}
+
+#[derive(Default)]
+struct Tree {
+    out: String,
+    depth: usize,
+}
+
+impl Tree {
+    fn line(&mut self, label: &str, span: Span) {
+        let indent = self.depth * 2;
+        writeln!(self.out, "{:indent$}{label} {span:?}", "").unwrap();
+    }
+
+    fn text(&mut self, label: &str, text: &Text) {
+        let indent = self.depth * 2;
+        let (t, span) = (text.text(), text.span());
+        writeln!(self.out, "{:indent$}{label} {t:?} {span:?}", "").unwrap();
+    }
+
+    fn opt_text(&mut self, label: &str, text: &Option<Text>) {
+        if let Some(text) = text {
+            self.text(label, text);
+        }
+    }
+
+    fn nested(&mut self, f: impl FnOnce(&mut Self)) {
+        self.depth += 1;
+        f(self);
+        self.depth -= 1;
+    }
+
+    fn block(&mut self, block: &Block) {
+        let indent = self.depth * 2;
+        match block {
+            Block::Metadata { key, value } => {
+                writeln!(self.out, "{:indent$}Metadata", "").unwrap();
+                self.nested(|t| {
+                    t.text("key", key);
+                    t.text("value", value);
+                });
+            }
+            Block::Section { name } => {
+                writeln!(self.out, "{:indent$}Section", "").unwrap();
+                self.nested(|t| t.opt_text("name", name));
+            }
+            Block::Step { items } => {
+                writeln!(self.out, "{:indent$}Step", "").unwrap();
+                self.nested(|t| items.iter().for_each(|i| t.item(i)));
+            }
+            Block::TextBlock(texts) => {
+                writeln!(self.out, "{:indent$}TextBlock", "").unwrap();
+                self.nested(|t| texts.iter().for_each(|text| t.text("Text", text)));
+            }
+        }
+    }
+
+    fn item(&mut self, item: &Item) {
+        match item {
+            Item::Text(text) => self.text("Text", text),
+            Item::Ingredient(c) => {
+                self.line("Ingredient", c.span());
+                self.nested(|t| {
+                    t.modifiers(&c.modifiers);
+                    if let Some(data) = &c.intermediate_data {
+                        let label = format!(
+                            "intermediate {:?} {:?} {}",
+                            data.ref_mode, data.target_kind, data.val
+                        );
+                        t.line(&label, data.span());
+                    }
+                    t.text("name", &c.name);
+                    t.opt_text("alias", &c.alias);
+                    if let Some(q) = &c.quantity {
+                        t.quantity(q);
+                    }
+                    t.opt_text("note", &c.note);
+                });
+            }
+            Item::Cookware(c) => {
+                self.line("Cookware", c.span());
+                self.nested(|t| {
+                    t.modifiers(&c.modifiers);
+                    t.text("name", &c.name);
+                    t.opt_text("alias", &c.alias);
+                    if let Some(q) = &c.quantity {
+                        t.line("quantity", q.span());
+                        t.nested(|t| t.quantity_value(q));
+                    }
+                    t.opt_text("unit", &c.unit);
+                    t.opt_text("note", &c.note);
+                });
+            }
+            Item::Timer(c) => {
+                self.line("Timer", c.span());
+                self.nested(|t| {
+                    t.opt_text("name", &c.name);
+                    if let Some(q) = &c.quantity {
+                        t.quantity(q);
+                    }
+                });
+            }
+            Item::InlineQuantity(q) => {
+                self.line("InlineQuantity", q.span());
+                self.nested(|t| t.quantity_fields(q));
+            }
+            Item::Media(m) => {
+                self.line("Media", m.span());
+                self.nested(|t| t.text("path", m));
+            }
+        }
+    }
+
+    fn modifiers(&mut self, modifiers: &Located<Modifiers>) {
+        if modifiers.is_empty() {
+            return;
+        }
+        let names = modifiers
+            .iter_names()
+            .map(|(name, _)| name)
+            .collect::<Vec<_>>()
+            .join(" | ");
+        self.line(&format!("modifiers {names}"), modifiers.span());
+    }
+
+    fn quantity(&mut self, quantity: &Located<Quantity>) {
+        self.line("quantity", quantity.span());
+        self.nested(|t| t.quantity_fields(quantity));
+    }
+
+    fn quantity_fields(&mut self, quantity: &Quantity) {
+        self.quantity_value(&quantity.value);
+        self.opt_text("unit", &quantity.unit);
+    }
+
+    fn quantity_value(&mut self, value: &QuantityValue) {
+        match value {
+            QuantityValue::Single {
+                value,
+                auto_scale,
+                decimal,
+            } => {
+                self.line(&format!("value {}", value.value()), value.span());
+                if let Some(decimal) = decimal {
+                    self.line("decimal", *decimal);
+                }
+                if let Some(auto_scale) = auto_scale {
+                    self.line("auto scale", *auto_scale);
+                }
+            }
+            QuantityValue::Many(values) => {
+                for value in values {
+                    self.line(&format!("value {}", value.value()), value.span());
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use test_case::test_case;
+
+    const SOURCE: &str = indoc! {r#"
+        >> servings: 2
+
+        Add @flour{200%g} and
+        @water{120%ml}.
+
+        = Bake
+
+        Put it in the #oven for ~{20%min}.
+
+        > Enjoy.
+    "#};
+
+    fn parse(source: &str) -> Ast<'_> {
+        build_ast(PullParser::new(source, Extensions::all())).unwrap_output()
+    }
+
+    #[test]
+    fn tree_string() {
+        let input = ">> servings: 2\n\n= Bake\n\nAdd @flour{200%g} to the #bowl{}.\n\n> Enjoy.";
+        let expected = indoc! {r#"
+            Metadata
+              key " servings" 2..11
+              value " 2" 12..14
+            Section
+              name " Bake" 17..22
+            Step
+              Text "Add " 24..28
+              Ingredient 28..41
+                name "flour" 29..34
+                quantity 35..40
+                  value 200 35..38
+                  unit "g" 39..40
+              Text " to the " 41..49
+              Cookware 49..56
+                name "bowl" 50..54
+              Text "." 56..57
+            TextBlock
+              Text "Enjoy." 61..67
+        "#};
+        assert_eq!(parse(input).tree_string(), expected);
+
+        let ast = parse("Add @?flour{2*%kg}(sifted) and @{1|2%cups}.");
+        let tree = ast.tree_string();
+        assert!(tree.contains("    modifiers OPT 5..6\n"), "{tree}");
+        assert!(tree.contains("      auto scale 13..14\n"), "{tree}");
+        assert!(tree.contains("    note \"sifted\" 19..25\n"), "{tree}");
+        assert!(tree.contains("  InlineQuantity 31..42\n"), "{tree}");
+        assert!(
+            tree.contains("    value 1 33..34\n    value 2 35..36\n"),
+            "{tree}"
+        );
+    }
+
+    #[test]
+    fn collection_two_recipes() {
+        let input = indoc! {r#"
+            >> servings: 2
+
+            Fry @eggs{2}.
+
+            =====
+
+            = Toast
+
+            Toast the @bread{1%slice}.
+        "#};
+        let recipes = parse_collection(input, Extensions::all());
+        assert_eq!(recipes.len(), 2);
+        let first = recipes[0].output().unwrap();
+        assert!(matches!(first.blocks[0], Block::Metadata { .. }));
+        assert_eq!(first.blocks.len(), 2);
+        let second = recipes[1].output().unwrap();
+        let Block::Section { name: Some(name) } = &second.blocks[0] else {
+            panic!("not a section")
+        };
+        assert_eq!(name.text_trimmed(), "Toast");
+        let Block::Step { items } = &second.blocks[1] else {
+            panic!("not a step")
+        };
+        // spans are relative to the whole input
+        assert_eq!(&input[items[1].span().range()], "@bread{1%slice}");
+    }
+
+    #[test]
+    fn collection_three_recipes() {
+        let input = indoc! {r#"
+            =====
+            First @a.
+            ==========
+            Second @b.
+              =======
+            == Section ==
+            Third @c.
+            =====
+        "#};
+        let recipes = parse_collection(input, Extensions::all());
+        assert_eq!(recipes.len(), 3);
+        assert!(recipes.iter().all(|r| r.is_valid()));
+        let last = recipes[2].output().unwrap();
+        assert!(matches!(last.blocks[0], Block::Section { name: Some(_) }));
+    }
+
+    #[test]
+    fn collection_separator_with_title() {
+        let input = "First @a.\n===== Second\nSecond @b.\n=====\n>> title: Third\nThird @c.";
+        let recipes = parse_collection(input, Extensions::all());
+        assert_eq!(recipes.len(), 2);
+        let first = recipes[0].output().unwrap();
+        let Block::Section { name: Some(name) } = &first.blocks[1] else {
+            panic!("not a section")
+        };
+        assert_eq!(name.text_trimmed(), "Second");
+        let second = recipes[1].output().unwrap();
+        let Block::Metadata { key, value } = &second.blocks[0] else {
+            panic!("not metadata")
+        };
+        assert_eq!(key.text_trimmed(), "title");
+        assert_eq!(value.text_trimmed(), "Third");
+    }
+
+    #[test]
+    fn owned_clone() {
+        let owned = parse_owned(SOURCE.to_string(), Extensions::all()).unwrap_output();
+        let cloned = owned.clone();
+        drop(owned);
+        assert_eq!(
+            format!("{:?}", cloned.ast()),
+            format!("{:?}", parse(SOURCE))
+        );
+        let Block::Step { items } = &cloned.ast().blocks[1] else {
+            panic!("not a step")
+        };
+        assert_eq!(&cloned.source()[items[1].span().range()], "@flour{200%g}");
+    }
+
+    #[test_case("=====" => true)]
+    #[test_case("  ======  \r\n" => true; "whitespace")]
+    #[test_case("====" => false; "too short")]
+    #[test_case("===== Title =====" => false; "section")]
+    #[test_case("===== Title" => false; "title")]
+    #[test_case("= Title" => false; "short section")]
+    fn recipe_separator(line: &str) -> bool {
+        is_recipe_separator(line)
+    }
+
+    #[test_case("flour", "rye flour" ; "inside a component")]
+    #[test_case("2", "4" ; "metadata")]
+    #[test_case("and\n", "and\n\n" ; "split a step")]
+    #[test_case("\n\n= Bake", " = Bake" ; "join blocks")]
+    #[test_case("= Bake\n", "" ; "remove a section")]
+    #[test_case("Enjoy.\n", "Enjoy.\n\nNew @step.\n" ; "at the end")]
+    #[test_case(">> servings", "Start.\n\n>> servings" ; "at the start")]
+    fn reparse(old_text: &str, new_text: &str) {
+        let start = SOURCE.find(old_text).unwrap();
+        let edit = Span::new(start, start + old_text.len());
+        let mut source = SOURCE.to_string();
+        source.replace_range(edit.range(), new_text);
+
+        let old = parse(SOURCE);
+        let (ast, changed) =
+            reparse_range(&old, SOURCE, &source, edit, Extensions::all()).unwrap_output();
+        let full = parse(&source);
+        assert_eq!(format!("{ast:?}"), format!("{full:?}"));
+        assert!(changed.len() < full.blocks.len());
+    }
+
+    #[test_case("Add @pepper.", "[- Add @pepper." ; "open")]
+    #[test_case("@a.", "@a. -]" ; "close")]
+    fn reparse_block_comment(old_text: &str, new_text: &str) {
+        let old_source = "Add @salt.\n\nAdd @pepper.\n\nMix @a.\n\nStir @b.\n\nEnd @c.";
+        let start = old_source.find(old_text).unwrap();
+        let edit = Span::new(start, start + old_text.len());
+        let mut source = old_source.to_string();
+        source.replace_range(edit.range(), new_text);
+
+        let old = parse(old_source);
+        let (ast, changed) =
+            reparse_range(&old, old_source, &source, edit, Extensions::all()).unwrap_output();
+        let full = parse(&source);
+        assert_eq!(format!("{ast:?}"), format!("{full:?}"));
+        assert_eq!(changed, 0..full.blocks.len());
+
+        // and removing it again
+        let edit = Span::new(start, start + new_text.len());
+        let (ast, _) =
+            reparse_range(&full, &source, old_source, edit, Extensions::all()).unwrap_output();
+        assert_eq!(format!("{ast:?}"), format!("{old:?}"));
+    }
+
+    #[test_case("a [- b", 2..4 => true ; "inside")]
+    #[test_case("a [-b", 2..3 => true ; "complete the opener")]
+    #[test_case("a -] b", 3..4 => true ; "complete the closer")]
+    #[test_case("a - [ b", 2..5 => false ; "not together")]
+    #[test_case("a [- b -]", 5..6 => false ; "outside")]
+    fn block_comment_delimiter(source: &str, range: Range<usize>) -> bool {
+        has_block_comment_delimiter(source, range)
+    }
+
+    #[test]
+    fn reparse_comment_before_pepper() {
+        let old_source = "Add @salt.\n\nAdd @pepper.\n\nMix @a.\n\nStir @b.\n\nEnd @c.";
+        let start = old_source.find("pepper").unwrap();
+        let mut source = old_source.to_string();
+        source.insert_str(start, "[- ");
+
+        let old = parse(old_source);
+        let edit = Span::pos(start);
+        let (ast, _) =
+            reparse_range(&old, old_source, &source, edit, Extensions::all()).unwrap_output();
+        assert_eq!(ast.blocks.len(), 2);
+    }
+
+    #[test]
+    fn reparse_inconsistent_edit() {
+        let old = parse(SOURCE);
+        let source = "Something @else.";
+        let (ast, changed) =
+            reparse_range(&old, SOURCE, source, Span::new(3, 5), Extensions::all()).unwrap_output();
+        assert_eq!(format!("{ast:?}"), format!("{:?}", parse(source)));
+        assert_eq!(changed, 0..1);
+
+        // the old ast is not from the old source
+        let (ast, _) = reparse_range(
+            &old,
+            source,
+            "Something @new.",
+            Span::new(11, 15),
+            Extensions::all(),
+        )
+        .unwrap_output();
+        assert_eq!(
+            format!("{ast:?}"),
+            format!("{:?}", parse("Something @new."))
+        );
+    }
+}
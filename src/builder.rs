@@ -0,0 +1,264 @@
+//! Build a recipe without parsing it
+//!
+//! Useful for editors that need to create a [`ScalableRecipe`] from their own
+//! data. The builder creates the model types with their indices and relations
+//! like the parser does, so they can't get out of sync.
+//!
+//! ```
+//! # use cooklang::{builder::RecipeBuilder, Converter, Quantity, ScalableValue};
+//! let flour = Quantity::new(ScalableValue::Linear(200.0.into()), Some("g".into()));
+//! let recipe = RecipeBuilder::new()
+//!     .step()
+//!     .text("Add ")
+//!     .ingredient("flour", Some(flour))
+//!     .text(" and ")
+//!     .ingredient("water", None)
+//!     .text(".")
+//!     .finish(&Converter::bundled())
+//!     .unwrap();
+//! let recipe = recipe.default_scale();
+//! assert_eq!(recipe.plain_text(true), "Add 200 g flour and water.");
+//! ```
+
+use thiserror::Error;
+
+use crate::{
+    metadata::{ListKeys, Metadata},
+    model::{ComponentOrigin, ComponentRelation, IngredientRelation},
+    Content, Converter, Cookware, Ingredient, Item, Modifiers, ScalableQuantity, ScalableRecipe,
+    ScalableValue, Section, Step, Timer,
+};
+
+/// Builder to create a [`ScalableRecipe`]
+///
+/// Items are added to the current step, [`Self::step`] starts a new one and
+/// [`Self::section`] a new section. If no step was started, the first item
+/// starts one.
+///
+/// Every component added is a new definition, there are no references.
+#[derive(Debug, Default)]
+pub struct RecipeBuilder {
+    metadata: Metadata,
+    sections: Vec<Section>,
+    current_section: Section,
+    current_step: Option<Vec<Item>>,
+    ingredients: Vec<Ingredient<ScalableValue>>,
+    cookware: Vec<Cookware<ScalableValue>>,
+    timers: Vec<Timer<ScalableValue>>,
+}
+
+/// Error from [`RecipeBuilder::finish`]
+#[derive(Debug, Error, PartialEq)]
+pub enum BuildError {
+    /// A component name is empty
+    #[error("Empty {kind} name")]
+    EmptyName {
+        /// `ingredient` or `cookware`
+        kind: &'static str,
+    },
+    /// A metadata value is not valid for its special key
+    #[error("Invalid value for metadata key '{key}': {value}")]
+    InvalidMetadata { key: String, value: String },
+    /// The number of values in a quantity doesn't match the servings
+    #[error("{servings} servings defined but {values} values in the quantity")]
+    ServingsMismatch { servings: usize, values: usize },
+    /// A quantity has a value for each serving but the recipe has no servings
+    #[error("No servings defined but {values} values in the quantity")]
+    MissingServings { values: usize },
+}
+
+impl RecipeBuilder {
+    /// New empty builder
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a metadata entry
+    ///
+    /// Special keys are parsed when the recipe is finished.
+    pub fn metadata(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.metadata.map.insert(key.into(), value.into());
+        self
+    }
+
+    /// Start a new section
+    pub fn section(mut self, name: impl Into<String>) -> Self {
+        self.end_section();
+        self.current_section = Section::new(Some(name.into()));
+        self
+    }
+
+    /// Start a new step
+    pub fn step(mut self) -> Self {
+        self.end_step();
+        self.current_step = Some(Vec::new());
+        self
+    }
+
+    /// Add a text paragraph, not a step
+    pub fn paragraph(mut self, text: impl Into<String>) -> Self {
+        self.end_step();
+        self.current_section
+            .content
+            .push(Content::Text(text.into()));
+        self
+    }
+
+    /// Add text to the current step
+    pub fn text(mut self, text: impl Into<String>) -> Self {
+        let text = text.into();
+        let items = self.current_step.get_or_insert_with(Vec::new);
+        if let Some(Item::Text { value }) = items.last_mut() {
+            value.push_str(&text);
+        } else {
+            items.push(Item::Text { value: text });
+        }
+        self
+    }
+
+    /// Add an ingredient to the current step
+    pub fn ingredient(
+        mut self,
+        name: impl Into<String>,
+        quantity: Option<ScalableQuantity>,
+    ) -> Self {
+        let ingredient = Ingredient {
+            name: name.into(),
+            alias: None,
+            quantity,
+            note: None,
+            relation: IngredientRelation::definition(Vec::new(), true),
+            origin: self.origin(),
+            pantry: false,
+            modifiers: Modifiers::empty(),
+        };
+        self.ingredients.push(ingredient);
+        let index = self.ingredients.len() - 1;
+        self.push_item(Item::Ingredient { index })
+    }
+
+    /// Add a cookware item to the current step
+    pub fn cookware(mut self, name: impl Into<String>, quantity: Option<ScalableValue>) -> Self {
+        let cookware = Cookware {
+            name: name.into(),
+            alias: None,
+            quantity,
+            unit: None,
+            note: None,
+            relation: ComponentRelation::Definition {
+                referenced_from: Vec::new(),
+                defined_in_step: true,
+            },
+            origin: self.origin(),
+            modifiers: Modifiers::empty(),
+        };
+        self.cookware.push(cookware);
+        let index = self.cookware.len() - 1;
+        self.push_item(Item::Cookware { index })
+    }
+
+    /// Add a timer to the current step
+    pub fn timer(mut self, name: Option<&str>, quantity: Option<ScalableQuantity>) -> Self {
+        let timer = Timer {
+            name: name.map(str::to_string),
+            quantity,
+            origin: self.origin(),
+        };
+        self.timers.push(timer);
+        let index = self.timers.len() - 1;
+        self.push_item(Item::Timer { index })
+    }
+
+    /// Validate and create the recipe
+    ///
+    /// The converter is used to parse the special metadata values with units,
+    /// like `time: 1 hour`.
+    pub fn finish(mut self, converter: &Converter) -> Result<ScalableRecipe, BuildError> {
+        self.end_section();
+
+        if let Err(errors) = self.metadata.parse_special(converter, &ListKeys::default()) {
+            let (key, value) = errors.into_iter().next().unwrap();
+            return Err(BuildError::InvalidMetadata { key, value });
+        }
+
+        if self.ingredients.iter().any(|i| i.name.trim().is_empty()) {
+            return Err(BuildError::EmptyName { kind: "ingredient" });
+        }
+        if self.cookware.iter().any(|cw| cw.name.trim().is_empty()) {
+            return Err(BuildError::EmptyName { kind: "cookware" });
+        }
+
+        let servings = self.metadata.servings().map(|s| s.len());
+        let values = self
+            .ingredients
+            .iter()
+            .filter_map(|i| i.quantity.as_ref().map(|q| &q.value))
+            .chain(self.cookware.iter().filter_map(|cw| cw.quantity.as_ref()))
+            .chain(
+                self.timers
+                    .iter()
+                    .filter_map(|t| t.quantity.as_ref().map(|q| &q.value)),
+            );
+        for value in values {
+            if let ScalableValue::ByServings(v) = value {
+                match servings {
+                    Some(servings) if servings != v.len() => {
+                        return Err(BuildError::ServingsMismatch {
+                            servings,
+                            values: v.len(),
+                        })
+                    }
+                    Some(_) => {}
+                    None => return Err(BuildError::MissingServings { values: v.len() }),
+                }
+            }
+        }
+
+        Ok(ScalableRecipe {
+            metadata: self.metadata,
+            sections: self.sections,
+            ingredients: self.ingredients,
+            cookware: self.cookware,
+            timers: self.timers,
+            inline_quantities: Vec::new(),
+            unresolved: Vec::new(),
+            data: (),
+        })
+    }
+
+    fn push_item(mut self, item: Item) -> Self {
+        self.current_step.get_or_insert_with(Vec::new).push(item);
+        self
+    }
+
+    fn origin(&self) -> ComponentOrigin {
+        // the current step is pushed at the end of the section
+        ComponentOrigin {
+            section: self.sections.len(),
+            step: self.current_section.content.len(),
+        }
+    }
+
+    fn end_step(&mut self) {
+        if let Some(items) = self.current_step.take().filter(|i| !i.is_empty()) {
+            let number = self
+                .current_section
+                .content
+                .iter()
+                .filter(|c| c.is_step())
+                .count() as u32
+                + 1;
+            self.current_section
+                .content
+                .push(Content::Step(Step::new(items, number)));
+        }
+    }
+
+    fn end_section(&mut self) {
+        self.end_step();
+        let section = std::mem::take(&mut self.current_section);
+        if !section.is_empty() {
+            self.sections.push(section);
+        }
+    }
+}
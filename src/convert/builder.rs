@@ -10,6 +10,20 @@ use super::{
     UnitIndex, UnknownUnit,
 };
 
+/// What to do when more than one unit has the same name, symbol or alias
+///
+/// Like `t` for tonne and teaspoon.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AmbiguousUnits {
+    /// Fail with [`ConverterBuilderError::DuplicateUnit`]
+    #[default]
+    Error,
+    /// Use the unit added first
+    PreferFirst,
+    /// Use the unit added last
+    PreferLast,
+}
+
 /// Builder to create a custom [`Converter`]
 ///
 /// The builder uses [`UnitsFile`] to configure the converter. More than one
@@ -72,12 +86,79 @@ impl ConverterBuilder {
         Ok(self)
     }
 
+    /// Set what to do with ambiguous units
+    ///
+    /// By default it's an error. This has to be set before adding any units.
+    pub fn with_ambiguous_units(mut self, ambiguous: AmbiguousUnits) -> Self {
+        self.set_ambiguous_units(ambiguous);
+        self
+    }
+
+    /// Set what to do with ambiguous units
+    ///
+    /// By default it's an error. This has to be set before adding any units.
+    pub fn set_ambiguous_units(&mut self, ambiguous: AmbiguousUnits) -> &mut Self {
+        self.unit_index.ambiguous = ambiguous;
+        self
+    }
+
     /// Add a [`UnitsFile`] to the builder
     pub fn with_units_file(mut self, units: UnitsFile) -> Result<Self, ConverterBuilderError> {
         self.add_units_file(units)?;
         Ok(self)
     }
 
+    /// Add all the TOML units files in a directory to the builder
+    ///
+    /// Every `*.toml` file in `dir` (not recursive) is added in order of
+    /// their file names, so `01-mass.toml` is layered before `02-volume.toml`.
+    ///
+    /// This is only available with the `units_toml` feature.
+    #[cfg(feature = "units_toml")]
+    pub fn with_units_dir(
+        mut self,
+        dir: impl AsRef<std::path::Path>,
+    ) -> Result<Self, ConverterBuilderError> {
+        self.add_units_dir(dir)?;
+        Ok(self)
+    }
+
+    /// Add all the TOML units files in a directory to the builder
+    ///
+    /// Every `*.toml` file in `dir` (not recursive) is added in order of
+    /// their file names, so `01-mass.toml` is layered before `02-volume.toml`.
+    /// Errors reading or parsing a file include its path.
+    ///
+    /// This is only available with the `units_toml` feature.
+    #[cfg(feature = "units_toml")]
+    pub fn add_units_dir(
+        &mut self,
+        dir: impl AsRef<std::path::Path>,
+    ) -> Result<&mut Self, ConverterBuilderError> {
+        let dir = dir.as_ref();
+        let io_err = |path: &std::path::Path| {
+            let path = path.to_path_buf();
+            move |source| ConverterBuilderError::Io { path, source }
+        };
+        let mut paths = std::fs::read_dir(dir)
+            .map_err(io_err(dir))?
+            .map(|entry| entry.map(|e| e.path()))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(io_err(dir))?;
+        paths.retain(|p| p.is_file() && p.extension().is_some_and(|ext| ext == "toml"));
+        paths.sort();
+
+        for path in paths {
+            let content = std::fs::read_to_string(&path).map_err(io_err(&path))?;
+            let units = match UnitsFile::from_toml(&content) {
+                Ok(units) => units,
+                Err(source) => return Err(ConverterBuilderError::UnitsFile { path, source }),
+            };
+            self.add_units_file(units)?;
+        }
+        Ok(self)
+    }
+
     /// Add a [`UnitsFile`] to the builder
     pub fn add_units_file(&mut self, units: UnitsFile) -> Result<&mut Self, ConverterBuilderError> {
         for group in units.quantity {
@@ -188,6 +269,12 @@ impl ConverterBuilder {
             q =>  {
                 if let Some(best_units) = &self.best_units[q] {
                     BestConversionsStore::new(best_units, &self.unit_index, &self.all_units)?
+                } else if q == PhysicalQuantity::Count
+                    || !self.all_units.iter().any(|u| u.physical_quantity == q)
+                {
+                    // counts are never fitted and without units there is
+                    // nothing to fit
+                    BestConversionsStore::default()
                 } else {
                     return Err(ConverterBuilderError::EmptyBest { reason: "no best units given", quantity: q })
                 }
@@ -311,7 +398,7 @@ fn apply_extend_groups(
         // Then apply updates
         for (id, entry) in to_update {
             // remove all entries from the unit and expansions from the index
-            unit_index.remove_unit_rec(all_units, &all_units[id]);
+            unit_index.remove_unit_rec(all_units, id);
             let unit = &mut all_units[id];
 
             // edit the unit
@@ -417,7 +504,11 @@ fn build_fractions_config(
     })
 }
 
-fn join_alias_vec(target: &mut Vec<Arc<str>>, mut src: Vec<Arc<str>>, src_precedence: Precedence) {
+pub(super) fn join_alias_vec(
+    target: &mut Vec<Arc<str>>,
+    mut src: Vec<Arc<str>>,
+    src_precedence: Precedence,
+) {
     match src_precedence {
         Precedence::Before => {
             src.append(target);
@@ -432,7 +523,7 @@ fn join_alias_vec(target: &mut Vec<Arc<str>>, mut src: Vec<Arc<str>>, src_preced
     }
 }
 
-fn join_prefixes(
+pub(super) fn join_prefixes(
     a: &mut Option<EnumMap<SIPrefix, Vec<String>>>,
     b: Option<EnumMap<SIPrefix, Vec<String>>>,
     b_precedence: Precedence,
@@ -498,22 +589,32 @@ fn expand_si(
 }
 
 impl UnitIndex {
-    fn remove_unit(&mut self, unit: &Unit) {
+    fn remove_unit(&mut self, unit: &Unit, id: usize) {
         for key in unit.all_keys() {
-            self.0.remove(key);
+            if let Some(ids) = self.units.get_mut(key) {
+                ids.retain(|&other| other != id);
+                if ids.is_empty() {
+                    self.units.remove(key);
+                }
+            }
         }
     }
 
-    fn remove_unit_rec(&mut self, all_units: &[UnitBuilder], unit: &UnitBuilder) {
+    fn remove_unit_rec(&mut self, all_units: &[UnitBuilder], id: usize) {
+        let unit = &all_units[id];
         if let Some(expanded_units) = &unit.expanded_units {
             for (_, expanded) in expanded_units {
-                self.remove_unit_rec(all_units, &all_units[*expanded]);
+                self.remove_unit_rec(all_units, *expanded);
             }
         }
-        self.remove_unit(unit);
+        self.remove_unit(unit, id);
     }
 
-    fn add_unit(&mut self, unit: &Unit, id: usize) -> Result<usize, ConverterBuilderError> {
+    pub(super) fn add_unit(
+        &mut self,
+        unit: &Unit,
+        id: usize,
+    ) -> Result<usize, ConverterBuilderError> {
         let mut added = 0;
         for key in unit.all_keys() {
             if key.trim().is_empty() {
@@ -521,12 +622,16 @@ impl UnitIndex {
                     unit: unit.clone().into(),
                 });
             }
-            let maybe_other = self.0.insert(Arc::clone(key), id);
-            if maybe_other.is_some() {
+            let ids = self.units.entry(Arc::clone(key)).or_default();
+            if !ids.is_empty() && self.ambiguous == AmbiguousUnits::Error {
                 return Err(ConverterBuilderError::DuplicateUnit {
                     name: key.to_string(),
                 });
             }
+            // keep the order when a unit is added again after an extend
+            if let Err(pos) = ids.binary_search(&id) {
+                ids.insert(pos, id);
+            }
             added += 1;
         }
         if added == 0 {
@@ -567,4 +672,200 @@ pub enum ConverterBuilderError {
 
     #[error("No SI prefixes found when expandind SI on a unit")]
     EmptySIPrefixes,
+
+    #[error("Error reading '{}'", path.display())]
+    Io {
+        path: std::path::PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("Error in units file '{}'", path.display())]
+    UnitsFile {
+        path: std::path::PathBuf,
+        source: units_file::UnitsFileError,
+    },
+}
+
+#[cfg(all(test, feature = "units_toml"))]
+mod tests {
+    use super::*;
+    use crate::convert::UnitResolution;
+    use indoc::indoc;
+
+    const AMBIGUOUS: &str = indoc! {r#"
+        [[quantity]]
+        quantity = "mass"
+        best = ["t"]
+        [quantity.units]
+        metric = [
+            { names = ["tonne"], symbols = ["t"], ratio = 1000000 },
+        ]
+
+        [[quantity]]
+        quantity = "volume"
+        best = ["tsp"]
+        [quantity.units]
+        metric = [
+            { names = ["teaspoon"], symbols = ["tsp"], aliases = ["t"], ratio = 5 },
+        ]
+    "#};
+
+    const AMBIGUOUS_FREE: &str = indoc! {r#"
+        [[quantity]]
+        quantity = "volume"
+        best = ["tsp"]
+        [quantity.units]
+        metric = [
+            { names = ["teaspoon"], symbols = ["tsp"], ratio = 5 },
+        ]
+    "#};
+
+    fn build(ambiguous: AmbiguousUnits) -> Result<Converter, ConverterBuilderError> {
+        Converter::builder()
+            .with_ambiguous_units(ambiguous)
+            .with_units_file(UnitsFile::from_toml(AMBIGUOUS).unwrap())?
+            .finish()
+    }
+
+    #[test]
+    fn ambiguous_error() {
+        let err = build(AmbiguousUnits::Error).unwrap_err();
+        assert!(matches!(err, ConverterBuilderError::DuplicateUnit { name } if name == "t"));
+    }
+
+    #[test]
+    fn ambiguous_prefer() {
+        let first = build(AmbiguousUnits::PreferFirst).unwrap();
+        assert_eq!(first.find_unit("t").unwrap().names[0].as_ref(), "tonne");
+        let UnitResolution::Ambiguous(units) = first.resolve_unit("t") else {
+            panic!("not ambiguous");
+        };
+        let names: Vec<_> = units.iter().map(|u| u.names[0].as_ref()).collect();
+        assert_eq!(names, ["tonne", "teaspoon"]);
+
+        let last = build(AmbiguousUnits::PreferLast).unwrap();
+        assert_eq!(last.find_unit("t").unwrap().names[0].as_ref(), "teaspoon");
+        let UnitResolution::Ambiguous(units) = last.resolve_unit("t") else {
+            panic!("not ambiguous");
+        };
+        assert_eq!(units[0].names[0].as_ref(), "teaspoon");
+
+        assert!(
+            matches!(last.resolve_unit("tsp"), UnitResolution::Unique(u) if u.names[0].as_ref() == "teaspoon")
+        );
+        assert!(matches!(last.resolve_unit("cup"), UnitResolution::NotFound));
+    }
+
+    #[test]
+    fn ambiguous_symbol_identity() {
+        // the first symbol of teaspoon is also the symbol of tonne
+        let units = indoc! {r#"
+            [[quantity]]
+            quantity = "mass"
+            best = ["tonne"]
+            [quantity.units]
+            metric = [
+                { names = ["tonne"], symbols = ["t"], ratio = 1000000 },
+            ]
+
+            [[quantity]]
+            quantity = "volume"
+            best = ["teaspoon"]
+            [quantity.units]
+            metric = [
+                { names = ["teaspoon"], symbols = ["t", "tsp"], ratio = 5 },
+            ]
+
+            [extend.units]
+            tonne = { aliases = ["ton"] }
+        "#};
+        let converter = Converter::builder()
+            .with_ambiguous_units(AmbiguousUnits::PreferFirst)
+            .with_units_file(UnitsFile::from_toml(units).unwrap())
+            .unwrap()
+            .finish()
+            .unwrap();
+        let teaspoon = converter.find_unit("tsp").unwrap();
+        assert!(converter.is_best_unit(&teaspoon));
+        assert!(converter.is_best_unit(&converter.find_unit("tonne").unwrap()));
+        // extending tonne doesn't change which one is preferred
+        assert_eq!(converter.find_unit("t").unwrap().names[0].as_ref(), "tonne");
+    }
+
+    #[test]
+    fn register_unit() {
+        let mut converter = build(AmbiguousUnits::PreferFirst).unwrap();
+        let count = converter.unit_count();
+        let pinch = Unit {
+            names: vec!["pinch".into()],
+            symbols: vec![],
+            aliases: vec![],
+            ratio: 0.3,
+            difference: 0.0,
+            physical_quantity: PhysicalQuantity::Volume,
+            system: None,
+        };
+        converter.register_unit(pinch).unwrap();
+        assert_eq!(converter.unit_count(), count + 1);
+        let listed = converter.units().find(|u| u.name == "pinch").unwrap();
+        assert_eq!(listed.physical_quantity, PhysicalQuantity::Volume);
+        assert!(!converter.is_best_unit(&converter.find_unit("pinch").unwrap()));
+
+        let mut strict = Converter::builder()
+            .with_units_file(UnitsFile::from_toml(AMBIGUOUS_FREE).unwrap())
+            .unwrap()
+            .finish()
+            .unwrap();
+        let before = strict.clone();
+        let conflict = Unit {
+            names: vec!["new".into()],
+            symbols: vec!["tsp".into()],
+            aliases: vec![],
+            ratio: 1.0,
+            difference: 0.0,
+            physical_quantity: PhysicalQuantity::Volume,
+            system: None,
+        };
+        assert!(matches!(
+            strict.register_unit(conflict),
+            Err(ConverterBuilderError::DuplicateUnit { name }) if name == "tsp"
+        ));
+        assert_eq!(strict, before);
+        assert!(strict.find_unit("new").is_none());
+    }
+
+    #[test]
+    fn units_dir() {
+        let dir = std::env::temp_dir().join(format!("cooklang-units-dir-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("01-volume.toml"), AMBIGUOUS_FREE).unwrap();
+        std::fs::write(
+            dir.join("02-mass.toml"),
+            indoc! {r#"
+                [[quantity]]
+                quantity = "mass"
+                best = ["g"]
+                [quantity.units]
+                metric = [
+                    { names = ["gram"], symbols = ["g"], ratio = 1 },
+                ]
+            "#},
+        )
+        .unwrap();
+        std::fs::write(dir.join("notes.txt"), "not a units file").unwrap();
+
+        let converter = Converter::builder().with_units_dir(&dir).unwrap().finish();
+
+        std::fs::write(dir.join("03-broken.toml"), "[[quantity]").unwrap();
+        let err = Converter::builder().with_units_dir(&dir).unwrap_err();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let converter = converter.unwrap();
+        assert_eq!(converter.unit_count(), 2);
+        assert!(converter.find_unit("tsp").is_some());
+        assert!(converter.find_unit("g").is_some());
+        assert!(
+            matches!(err, ConverterBuilderError::UnitsFile { path, .. } if path.ends_with("03-broken.toml"))
+        );
+    }
 }
@@ -15,12 +15,12 @@ use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 use crate::{
-    quantity::{Number, Quantity, ScaledQuantity, Value},
-    ScaledRecipe, UnitInfo,
+    quantity::{round_float, Number, Quantity, ScaledQuantity, Value},
+    Cookware, Item, ScaledRecipe, UnitInfo,
 };
 
-pub use builder::{ConverterBuilder, ConverterBuilderError};
-pub use units_file::UnitsFile;
+pub use builder::{AmbiguousUnits, ConverterBuilder, ConverterBuilderError};
+pub use units_file::{UnitsFile, UnitsFileError};
 
 mod builder;
 pub mod units_file;
@@ -107,6 +107,50 @@ impl Converter {
         self.all_units.iter().map(|u| u.as_ref())
     }
 
+    /// Get a description of every known unit.
+    ///
+    /// This is the final set of units, after all the layers of the
+    /// [`ConverterBuilder`] are merged. Useful to list or suggest units.
+    ///
+    /// ```
+    /// # use cooklang::{Converter, convert::PhysicalQuantity};
+    /// let converter = Converter::bundled();
+    /// let gram = converter.units().find(|u| u.name == "gram").unwrap();
+    /// assert_eq!(gram.physical_quantity, PhysicalQuantity::Mass);
+    /// assert!(gram.all_keys().any(|k| k == "g"));
+    /// assert_eq!(converter.units().count(), converter.unit_count());
+    /// ```
+    pub fn units(&self) -> impl Iterator<Item = UnitEntry<'_>> {
+        self.all_units.iter().map(|u| UnitEntry::new(u))
+    }
+
+    /// Get the best units of a [`PhysicalQuantity`] in a [`System`].
+    ///
+    /// These are the units the converter chooses from when fitting or
+    /// converting to a system, sorted from smallest to biggest. Useful to
+    /// only offer sensible units. If the best units are not split by system,
+    /// both systems give the same units.
+    ///
+    /// ```
+    /// # use cooklang::{Converter, convert::{PhysicalQuantity, System}};
+    /// let converter = Converter::bundled();
+    /// let names = converter
+    ///     .best_units_for(PhysicalQuantity::Volume, System::Metric)
+    ///     .map(|u| u.name)
+    ///     .collect::<Vec<_>>();
+    /// assert_eq!(names, ["milliliter", "liter"]);
+    /// ```
+    pub fn best_units_for(
+        &self,
+        physical_quantity: PhysicalQuantity,
+        system: System,
+    ) -> impl Iterator<Item = UnitEntry<'_>> {
+        self.best[physical_quantity]
+            .conversions(system)
+            .all_units(self)
+            .map(|u| UnitEntry::new(u))
+    }
+
     /// Check if a unit is one of the possible conversions in it's units system.
     ///
     /// When a unit is a *best unit*, the converter can choose it when trying
@@ -115,10 +159,7 @@ impl Converter {
     /// # Panics
     /// If the unit is not known.
     pub fn is_best_unit(&self, unit: &Unit) -> bool {
-        let unit_id = self
-            .unit_index
-            .get_unit_id(unit.symbol())
-            .expect("unit not found");
+        let unit_id = self.unit_id(unit).expect("unit not found");
         let Some(system) = unit.system else {
             return false;
         };
@@ -150,20 +191,122 @@ impl Converter {
         Some(self.all_units[uid].clone())
     }
 
+    /// First symbol of a unit given by any of its names, symbols or aliases
+    ///
+    /// Returns [`None`] if the unit is unknown or has no symbols.
+    ///
+    /// ```
+    /// # use cooklang::Converter;
+    /// let converter = Converter::bundled();
+    /// assert_eq!(converter.canonical_symbol("grams"), Some("g"));
+    /// ```
+    pub fn canonical_symbol(&self, unit: &str) -> Option<&str> {
+        let uid = self.unit_index.get_unit_id(unit).ok()?;
+        self.all_units[uid].symbols.first().map(AsRef::as_ref)
+    }
+
+    /// First name of a unit given by any of its names, symbols or aliases
+    ///
+    /// Returns [`None`] if the unit is unknown or has no names.
+    ///
+    /// ```
+    /// # use cooklang::Converter;
+    /// let converter = Converter::bundled();
+    /// assert_eq!(converter.canonical_name("g"), Some("gram"));
+    /// ```
+    pub fn canonical_name(&self, unit: &str) -> Option<&str> {
+        let uid = self.unit_index.get_unit_id(unit).ok()?;
+        self.all_units[uid].names.first().map(AsRef::as_ref)
+    }
+
+    /// Add a new unit to the converter
+    ///
+    /// Useful to add units at runtime without building the converter again
+    /// from the units files. The unit will not be one of the best units, so
+    /// values are never converted to it, but it is known and other units of
+    /// the same [`PhysicalQuantity`] can be converted from it.
+    ///
+    /// If any of its names, symbols or aliases is already used, the converter
+    /// is not modified and an error is returned, unless the converter was
+    /// built to allow [`AmbiguousUnits`].
+    ///
+    /// ```
+    /// # use cooklang::{Converter, convert::{PhysicalQuantity, Unit}};
+    /// let mut converter = Converter::bundled();
+    /// let unit = Unit {
+    ///     names: vec!["pinch".into(), "pinches".into()],
+    ///     symbols: vec![],
+    ///     aliases: vec![],
+    ///     ratio: 0.3,
+    ///     difference: 0.0,
+    ///     physical_quantity: PhysicalQuantity::Volume,
+    ///     system: None,
+    /// };
+    /// converter.register_unit(unit.clone()).unwrap();
+    /// assert!(converter.find_unit("pinches").is_some());
+    /// assert!(converter.register_unit(unit).is_err());
+    /// ```
+    pub fn register_unit(&mut self, unit: Unit) -> Result<Arc<Unit>, ConverterBuilderError> {
+        let id = self.all_units.len();
+        // work on a copy so nothing changes on error
+        let mut unit_index = self.unit_index.clone();
+        unit_index.add_unit(&unit, id)?;
+        self.unit_index = unit_index;
+
+        let unit = Arc::new(unit);
+        self.quantity_index[unit.physical_quantity].push(id);
+        if unit.physical_quantity == PhysicalQuantity::Temperature {
+            // the regex has to include the new unit
+            self.temperature_regex = OnceCell::new();
+        }
+        self.all_units.push(Arc::clone(&unit));
+        Ok(unit)
+    }
+
+    /// Find all the units with a name, symbol or alias
+    ///
+    /// Useful to know why a unit resolved to something unexpected. A key can
+    /// only be ambiguous if the converter was built with
+    /// [`AmbiguousUnits::PreferFirst`] or [`AmbiguousUnits::PreferLast`].
+    pub fn resolve_unit(&self, unit: &str) -> UnitResolution {
+        let ids = self.unit_index.candidates(unit);
+        match ids.len() {
+            0 => UnitResolution::NotFound,
+            1 => UnitResolution::Unique(self.all_units[ids[0]].clone()),
+            _ => UnitResolution::Ambiguous(
+                ids.into_iter()
+                    .map(|id| self.all_units[id].clone())
+                    .collect(),
+            ),
+        }
+    }
+
     /// Gets the fractions configuration for the given unit
     ///
     /// # Panics
     /// If the unit is not known.
     #[tracing::instrument(level = "trace", skip_all, fields(unit = %unit), ret)]
     pub(crate) fn fractions_config(&self, unit: &Unit) -> FractionsConfig {
-        let unit_id = self
-            .unit_index
-            .get_unit_id(unit.symbol())
-            .expect("unit not found");
+        let unit_id = self.unit_id(unit).expect("unit not found");
         self.fractions
             .config(unit.system, unit.physical_quantity, unit_id)
     }
 
+    /// Id of a unit
+    ///
+    /// The symbol may be ambiguous, so the unit is looked up by identity
+    /// between the ones with its symbol. A copy of a unit, not from this
+    /// converter, is the preferred unit with its symbol.
+    fn unit_id(&self, unit: &Unit) -> Option<usize> {
+        let symbol = unit.symbol();
+        self.unit_index
+            .ids(symbol)
+            .iter()
+            .copied()
+            .find(|&id| std::ptr::eq(Arc::as_ptr(&self.all_units[id]), unit))
+            .or_else(|| self.unit_index.get_unit_id(symbol).ok())
+    }
+
     /// Determines if the unit should be tried to be converted into a fraction
     ///
     /// # Panics
@@ -249,15 +392,52 @@ impl Default for FractionsConfig {
     }
 }
 
+/// Result of [`Converter::resolve_unit`]
+#[derive(Debug, Clone)]
+pub enum UnitResolution {
+    /// Only one unit has the key
+    Unique(Arc<Unit>),
+    /// More than one unit has the key
+    ///
+    /// The first one is the unit the converter uses.
+    Ambiguous(Vec<Arc<Unit>>),
+    /// No unit has the key
+    NotFound,
+}
+
 #[derive(Debug, Default, Clone, PartialEq)]
-pub(crate) struct UnitIndex(HashMap<Arc<str>, usize>);
+pub(crate) struct UnitIndex {
+    /// Units with each key in the order they were added, that is, sorted by id
+    units: HashMap<Arc<str>, Vec<usize>>,
+    ambiguous: AmbiguousUnits,
+}
 
 impl UnitIndex {
+    /// Preferred unit with the key
+    ///
+    /// This is called for every conversion, so unlike [`Self::candidates`]
+    /// it doesn't allocate.
     fn get_unit_id(&self, key: &str) -> Result<usize, UnknownUnit> {
-        self.0
-            .get(key)
-            .copied()
-            .ok_or_else(|| UnknownUnit(key.to_string()))
+        let ids = self.ids(key);
+        let id = match self.ambiguous {
+            AmbiguousUnits::PreferLast => ids.last(),
+            AmbiguousUnits::Error | AmbiguousUnits::PreferFirst => ids.first(),
+        };
+        id.copied().ok_or_else(|| UnknownUnit(key.to_string()))
+    }
+
+    /// Units with the key in the order they were added
+    fn ids(&self, key: &str) -> &[usize] {
+        self.units.get(key).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// Units with the key, the preferred one first
+    fn candidates(&self, key: &str) -> Vec<usize> {
+        let mut ids = self.units.get(key).cloned().unwrap_or_default();
+        if self.ambiguous == AmbiguousUnits::PreferLast {
+            ids.reverse();
+        }
+        ids
     }
 }
 
@@ -329,6 +509,46 @@ impl std::fmt::Display for Unit {
     }
 }
 
+/// Description of a known unit. See [`Converter::units`].
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct UnitEntry<'a> {
+    /// Canonical name of the unit. This is the first name or, if it has none,
+    /// the [`Unit::symbol`]
+    pub name: &'a str,
+    /// All the names
+    pub names: &'a [Arc<str>],
+    /// All the symbols
+    pub symbols: &'a [Arc<str>],
+    /// All the aliases
+    pub aliases: &'a [Arc<str>],
+    /// The unit [`System`] this unit belongs to, if any
+    pub system: Option<System>,
+    /// The [`PhysicalQuantity`] this unit belongs to
+    pub physical_quantity: PhysicalQuantity,
+}
+
+impl<'a> UnitEntry<'a> {
+    fn new(u: &'a Unit) -> Self {
+        Self {
+            name: u.names.first().map(|n| n.as_ref()).unwrap_or(u.symbol()),
+            names: &u.names,
+            symbols: &u.symbols,
+            aliases: &u.aliases,
+            system: u.system,
+            physical_quantity: u.physical_quantity,
+        }
+    }
+
+    /// Iterate over every string that can be used to write this unit
+    pub fn all_keys(&self) -> impl Iterator<Item = &str> {
+        self.names
+            .iter()
+            .chain(self.symbols)
+            .chain(self.aliases)
+            .map(|k| k.as_ref())
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 enum BestConversionsStore {
     Unified(BestConversions),
@@ -373,6 +593,7 @@ impl BestConversions {
         let value = match value {
             ConvertValue::Number(n) => n.abs(),
             ConvertValue::Range(r) => r.start().abs(),
+            ConvertValue::AtLeast(n) | ConvertValue::AtMost(n) => n.abs(),
         };
         let base_unit_id = self.base()?;
         let base_unit = &converter.all_units[base_unit_id];
@@ -388,7 +609,39 @@ impl BestConversions {
         Some(Arc::clone(&converter.all_units[best_id]))
     }
 
-    fn all_units<'c>(&'c self, converter: &'c Converter) -> impl Iterator<Item = &Arc<Unit>> {
+    fn nice_unit(
+        &self,
+        converter: &Converter,
+        value: &ConvertValue,
+        unit: &Unit,
+    ) -> Option<Arc<Unit>> {
+        let value = match value {
+            ConvertValue::Number(n) => n.abs(),
+            ConvertValue::Range(r) => r.start().abs(),
+            ConvertValue::AtLeast(n) | ConvertValue::AtMost(n) => n.abs(),
+        };
+        let digits = |v: f64| {
+            round_float(v)
+                .to_string()
+                .chars()
+                .filter(char::is_ascii_digit)
+                .count()
+        };
+
+        // from the bigger unit to the smaller, so ties pick the bigger unit
+        self.all_units(converter)
+            .rev()
+            .min_by_key(|u| {
+                let v = converter.convert_f64(value, unit, u);
+                (v < 1.0, digits(v))
+            })
+            .cloned()
+    }
+
+    fn all_units<'c>(
+        &'c self,
+        converter: &'c Converter,
+    ) -> impl DoubleEndedIterator<Item = &Arc<Unit>> {
         self.0.iter().map(|(_, uid)| &converter.all_units[*uid])
     }
 }
@@ -416,6 +669,13 @@ pub enum PhysicalQuantity {
     Length,
     Temperature,
     Time,
+    /// Things that are counted, like `clove` or `slice`
+    ///
+    /// Unlike the others, different count units are not convertible to each
+    /// other and count units are never changed when fitting or converting to
+    /// another system. Quantities without unit are also counts, see
+    /// [`Quantity::physical_quantity`].
+    Count,
 }
 
 impl ScaledRecipe {
@@ -442,7 +702,9 @@ impl ScaledRecipe {
             }
         }
 
-        // cookware can't have units
+        for cookware in &mut self.cookware {
+            convert_descriptor(cookware, &mut conv);
+        }
 
         for timer in &mut self.timers {
             if let Some(q) = &mut timer.quantity {
@@ -456,15 +718,124 @@ impl ScaledRecipe {
 
         errors
     }
+
+    /// Convert every quantity of the recipe to another [`System`]
+    ///
+    /// Unlike [`Self::convert`], this returns a new recipe and `self` is not
+    /// changed, so the original quantities are still available with the same
+    /// indices.
+    ///
+    /// Quantities without unit are left as they are. Quantities that can't be
+    /// converted are also left as they are and a [`ConvertWarning`] is
+    /// returned.
+    pub fn convert_all(&self, to: System, converter: &Converter) -> (Self, Vec<ConvertWarning>) {
+        self.clone().to_system(to, converter)
+    }
+
+    /// Converts the recipe to another [`System`] to display it
+    ///
+    /// Every quantity with a known unit ends in the best unit of the system
+    /// for its value, like `250 ml` to cups in [`System::Imperial`].
+    /// Quantities without unit are left as they are. The ones that can't be
+    /// converted, like unknown units, are also left as they are and a
+    /// [`ConvertWarning`] is returned for each.
+    ///
+    /// Same as [`Self::convert_all`] but consuming the recipe.
+    ///
+    /// ```
+    /// # use cooklang::{convert::System, CooklangParser};
+    /// let parser = CooklangParser::extended();
+    /// let recipe = parser.parse("Add @milk{500%ml}.").unwrap_output().default_scale();
+    /// let (recipe, warnings) = recipe.to_system(System::Imperial, parser.converter());
+    /// let milk = recipe.ingredients[0].quantity.as_ref().unwrap();
+    /// assert_eq!(milk.unit_text(), Some("c"));
+    /// assert!(warnings.is_empty());
+    /// ```
+    pub fn to_system(mut self, to: System, converter: &Converter) -> (Self, Vec<ConvertWarning>) {
+        let mut warnings = Vec::new();
+
+        let to = ConvertTo::from(to);
+
+        let mut conv = |q: &mut ScaledQuantity, item: Item| match q.convert(to, converter) {
+            Ok(()) | Err(ConvertError::NoUnit(_)) => {}
+            Err(error) => warnings.push(ConvertWarning { item, error }),
+        };
+
+        for (index, igr) in self.ingredients.iter_mut().enumerate() {
+            if let Some(q) = &mut igr.quantity {
+                conv(q, Item::Ingredient { index });
+            }
+        }
+        for (index, cookware) in self.cookware.iter_mut().enumerate() {
+            convert_descriptor(cookware, |q| conv(q, Item::Cookware { index }));
+        }
+
+        for (index, timer) in self.timers.iter_mut().enumerate() {
+            if let Some(q) = &mut timer.quantity {
+                conv(q, Item::Timer { index });
+            }
+        }
+        for (index, q) in self.inline_quantities.iter_mut().enumerate() {
+            conv(q, Item::InlineQuantity { index });
+        }
+
+        (self, warnings)
+    }
+}
+
+/// Converts the quantity of a cookware item with a unit, like `9 inch`
+///
+/// Cookware without unit is a count and it's left as it is.
+fn convert_descriptor(cookware: &mut Cookware<Value>, conv: impl FnOnce(&mut ScaledQuantity)) {
+    let (Some(value), Some(unit)) = (&cookware.quantity, &cookware.unit) else {
+        return;
+    };
+    let mut q = Quantity::new(value.clone(), Some(unit.clone()));
+    conv(&mut q);
+    cookware.unit = q.unit_text().map(str::to_string);
+    cookware.quantity = Some(q.value);
+}
+
+/// A quantity that could not be converted in [`ScaledRecipe::to_system`]
+#[derive(Debug)]
+pub struct ConvertWarning {
+    /// Where the quantity is in the recipe
+    pub item: Item,
+    /// Why it could not be converted
+    pub error: ConvertError,
 }
 
 impl ScaledQuantity {
+    /// Converts the quantity to a unit or to the best unit of a system
+    ///
+    /// The value is converted as a decimal number, so a fraction in the input
+    /// is not kept as written. After converting, the value is approximated to
+    /// a fraction if the [fractions](UnitsFile::fractions) are enabled for
+    /// the resulting unit and it fits in the limits configured for it.
+    /// Otherwise the value stays a decimal.
+    ///
+    /// - [`ConvertTo::Unit`] always ends in that unit, even if the value
+    ///   can't be a fraction in it.
+    /// - [`ConvertTo::Best`] and [`ConvertTo::SameSystem`] prefer the best
+    ///   unit that can represent the value as a fraction, like [`Self::fit`].
+    ///
+    /// So a later [`Self::fit`] may change the unit again to get a fraction.
+    ///
+    /// ```
+    /// # use cooklang::{Converter, Quantity, Value};
+    /// let converter = Converter::bundled();
+    /// let mut q = Quantity::new(Value::from(1.5), Some("cups".into()));
+    /// q.convert("tbsp", &converter).unwrap();
+    /// assert_eq!(q.to_string(), "24 tbsp");
+    /// q.fit(&converter).unwrap();
+    /// assert_eq!(q.to_string(), "1 1/2 c");
+    /// ```
     pub fn convert<'a>(
         &mut self,
         to: impl Into<ConvertTo<'a>>,
         converter: &Converter,
     ) -> Result<(), ConvertError> {
-        self.convert_impl(to.into(), converter)
+        self.keeping_source(|q| q.convert_impl(to.into(), converter))
     }
 
     #[tracing::instrument(level = "trace", name = "convert", skip_all)]
@@ -486,7 +857,12 @@ impl ScaledQuantity {
         let value = ConvertValue::try_from(&self.value)?;
 
         let (new_value, new_unit) = converter.convert(value, unit, to)?;
+        let decimal = self.decimal;
         *self = Quantity::with_known_unit(new_value.into(), Arc::clone(&new_unit));
+        self.decimal = decimal;
+        if decimal {
+            return Ok(());
+        }
         match to {
             ConvertTo::Unit(_) => {
                 self.try_fraction(converter);
@@ -504,27 +880,170 @@ impl ScaledQuantity {
     /// Converts the unit to the best possible match in the same unit system.
     ///
     /// For example, `1000 ml` would be converted to `1 l`.
-    #[tracing::instrument(level = "trace", skip_all)]
+    ///
+    /// This uses the default [`FitStrategy`], see [`Self::fit_with`].
     pub fn fit(&mut self, converter: &Converter) -> Result<(), ConvertError> {
+        self.fit_with(converter, FitStrategy::default())
+    }
+
+    /// Same as [`Self::fit`] but choosing how the best unit is selected.
+    ///
+    /// ```
+    /// # use cooklang::{convert::FitStrategy, Converter, Quantity, Value};
+    /// let converter = Converter::bundled();
+    /// let mut q = Quantity::new(Value::from(1500.0), Some("g".into()));
+    /// q.fit_with(&converter, FitStrategy::NiceNumber).unwrap();
+    /// assert_eq!(q.to_string(), "1.5 kg");
+    ///
+    /// let mut q = Quantity::new(Value::from(950.0), Some("g".into()));
+    /// q.fit_with(&converter, FitStrategy::NiceNumber).unwrap();
+    /// assert_eq!(q.to_string(), "950 g");
+    /// ```
+    #[tracing::instrument(level = "trace", skip_all)]
+    pub fn fit_with(
+        &mut self,
+        converter: &Converter,
+        strategy: FitStrategy,
+    ) -> Result<(), ConvertError> {
+        self.fit_impl(converter, strategy, None)
+    }
+
+    /// Same as [`Self::fit`] but returns a [`FitTrace`] explaining the result
+    ///
+    /// This is meant to debug units files, the fitted quantity is the same as
+    /// the one from [`Self::fit`].
+    ///
+    /// ```
+    /// # use cooklang::{convert::FitReason, Converter, Quantity, Value};
+    /// let converter = Converter::bundled();
+    /// let q = Quantity::new(Value::from(1500.0), Some("ml".into()));
+    /// let (fitted, trace) = q.fit_verbose(&converter).unwrap();
+    /// assert_eq!(fitted.to_string(), "1.5 l");
+    /// assert_eq!(trace.reason, FitReason::Magnitude);
+    /// assert_eq!(trace.chosen.unwrap().symbols[0].as_ref(), "l");
+    /// let (ml, value) = &trace.candidates[0];
+    /// assert_eq!((ml.symbols[0].as_ref(), *value), ("ml", 1500.0));
+    ///
+    /// let q = Quantity::new(Value::from(0.5), Some("cup".into()));
+    /// let (_, trace) = q.fit_verbose(&converter).unwrap();
+    /// assert_eq!(trace.reason, FitReason::Fraction);
+    /// ```
+    pub fn fit_verbose(&self, converter: &Converter) -> Result<(Self, FitTrace), ConvertError> {
+        let mut fitted = self.clone();
+        let mut trace = FitTrace {
+            system: None,
+            from_default_system: false,
+            candidates: Vec::new(),
+            chosen: None,
+            reason: FitReason::UnknownUnit,
+        };
+        fitted.fit_impl(converter, FitStrategy::default(), Some(&mut trace))?;
+        Ok((fitted, trace))
+    }
+
+    fn fit_impl(
+        &mut self,
+        converter: &Converter,
+        strategy: FitStrategy,
+        trace: Option<&mut FitTrace>,
+    ) -> Result<(), ConvertError> {
+        self.keeping_source(|q| q.fit_inner(converter, strategy, trace))
+    }
+
+    /// Runs `f` and keeps the value as written if the value didn't change
+    fn keeping_source<T>(&mut self, f: impl FnOnce(&mut Self) -> T) -> T {
+        let value = self.value.clone();
+        let source = self.source.clone();
+        let res = f(self);
+        if self.value == value {
+            self.source = source;
+        }
+        res
+    }
+
+    fn fit_inner(
+        &mut self,
+        converter: &Converter,
+        strategy: FitStrategy,
+        mut trace: Option<&mut FitTrace>,
+    ) -> Result<(), ConvertError> {
         // only known units can be fitted
         let Some(UnitInfo::Known(unit)) = self.unit().map(|u| u.unit_info_or_parse(converter))
         else {
             return Ok(());
         };
 
+        if unit.physical_quantity == PhysicalQuantity::Count {
+            if let Some(trace) = trace {
+                trace.reason = FitReason::Count;
+                trace.chosen = Some(unit);
+            }
+            return Ok(());
+        }
+
+        if let Some(trace) = trace.as_deref_mut() {
+            let system = unit.system.unwrap_or(converter.default_system);
+            let value = match ConvertValue::try_from(&self.value)? {
+                ConvertValue::Number(n) => n,
+                ConvertValue::Range(r) => *r.start(),
+                ConvertValue::AtLeast(n) | ConvertValue::AtMost(n) => n,
+            };
+            trace.system = Some(system);
+            trace.from_default_system = unit.system.is_none();
+            trace.candidates = converter.best[unit.physical_quantity]
+                .conversions(system)
+                .all_units(converter)
+                .map(|u| (Arc::clone(u), converter.convert_f64(value, &unit, u)))
+                .collect();
+        }
+
         // If configured, try fitting as a fraction
-        if converter.should_fit_fraction(&unit)
+        if !self.decimal
+            && converter.should_fit_fraction(&unit)
             && self.fit_fraction(&unit, unit.system, converter)?
         {
+            if let Some(trace) = trace {
+                trace.reason = FitReason::Fraction;
+                trace.chosen = self.known_unit(converter);
+            }
             return Ok(());
         }
 
-        // convert to the best in the same system
-        self.convert(ConvertTo::SameSystem, converter)?;
+        match strategy {
+            // convert to the best in the same system
+            FitStrategy::Threshold => self.convert(ConvertTo::SameSystem, converter)?,
+            FitStrategy::NiceNumber => {
+                let value = ConvertValue::try_from(&self.value)?;
+                let system = unit.system.unwrap_or(converter.default_system);
+                let new_unit = converter.best[unit.physical_quantity]
+                    .conversions(system)
+                    .nice_unit(converter, &value, &unit)
+                    .ok_or(ConvertError::BestUnitNotFound {
+                        physical_quantity: unit.physical_quantity,
+                        system: unit.system,
+                    })?;
+                self.convert(&new_unit, converter)?;
+            }
+        }
+
+        if let Some(trace) = trace {
+            trace.reason = match strategy {
+                FitStrategy::Threshold => FitReason::Magnitude,
+                FitStrategy::NiceNumber => FitReason::NiceNumber,
+            };
+            trace.chosen = self.known_unit(converter);
+        }
 
         Ok(())
     }
 
+    fn known_unit(&self, converter: &Converter) -> Option<Arc<Unit>> {
+        match self.unit()?.unit_info_or_parse(converter) {
+            UnitInfo::Known(unit) => Some(unit),
+            UnitInfo::Unknown => None,
+        }
+    }
+
     /// Fits the quantity as an approximation.
     ///
     /// - Finds all the conversions where an approximation is possible
@@ -548,7 +1067,8 @@ impl ScaledQuantity {
 
         let value = match self.value {
             Value::Number(n) => n.value(),
-            Value::Range { start, .. } => start.value(),
+            Value::Range { start, .. } | Value::AtLeast(start) => start.value(),
+            Value::AtMost(end) => end.value(),
             Value::Text(ref t) => return Err(ConvertError::TextValue(t.clone())),
         };
 
@@ -598,6 +1118,8 @@ impl ScaledQuantity {
                     end: end_frac,
                 }
             }
+            Value::AtLeast(_) => Value::AtLeast(new_value),
+            Value::AtMost(_) => Value::AtMost(new_value),
             Value::Text(_) => unreachable!(),
         };
         *self = Quantity::with_known_unit(new_value, Arc::clone(new_unit));
@@ -609,6 +1131,20 @@ impl ScaledQuantity {
     /// It respects the converter configuration for the unit.
     #[tracing::instrument(level = "trace", skip_all)]
     pub fn try_fraction(&mut self, converter: &Converter) -> bool {
+        self.try_fraction_impl(converter, None)
+    }
+
+    /// With a `denominator`, the value is rounded to the closest multiple of
+    /// `1/denominator` instead of approximated with the converter accuracy
+    /// and max denominator.
+    pub(crate) fn try_fraction_impl(
+        &mut self,
+        converter: &Converter,
+        denominator: Option<u8>,
+    ) -> bool {
+        if self.decimal {
+            return false;
+        }
         // only known units can be fitted
         let Some(UnitInfo::Known(unit)) = self.unit().map(|u| u.unit_info_or_parse(converter))
         else {
@@ -619,6 +1155,9 @@ impl ScaledQuantity {
         if !cfg.enabled {
             return false;
         }
+        if let Some(den) = denominator {
+            return self.value.try_snap(den, cfg.max_whole);
+        }
 
         match &mut self.value {
             Value::Number(n) => n.try_approx(cfg.accuracy, cfg.max_denominator, cfg.max_whole),
@@ -626,6 +1165,9 @@ impl ScaledQuantity {
                 start.try_approx(cfg.accuracy, cfg.max_denominator, cfg.max_whole)
                     || end.try_approx(cfg.accuracy, cfg.max_denominator, cfg.max_whole)
             }
+            Value::AtLeast(n) | Value::AtMost(n) => {
+                n.try_approx(cfg.accuracy, cfg.max_denominator, cfg.max_whole)
+            }
             Value::Text(_) => false,
         }
     }
@@ -641,6 +1183,13 @@ impl Converter {
     ) -> Result<(ConvertValue, Arc<Unit>), ConvertError> {
         let unit = self.get_unit(&unit)?;
 
+        // counts have no best units, they stay the same
+        if unit.physical_quantity == PhysicalQuantity::Count
+            && matches!(to, ConvertTo::Best(_) | ConvertTo::SameSystem)
+        {
+            return Ok((value, Arc::clone(unit)));
+        }
+
         let (value, unit) = match to {
             ConvertTo::Unit(target_unit) => {
                 let to = self.get_unit(&target_unit)?;
@@ -655,6 +1204,27 @@ impl Converter {
         Ok((value, unit))
     }
 
+    /// Convert a number between two units given by name, symbol or alias
+    ///
+    /// A shorthand for [`Converter::convert`] when there is no [`Quantity`].
+    /// Both units must be known and of the same [`PhysicalQuantity`].
+    ///
+    /// ```
+    /// # use cooklang::Converter;
+    /// let converter = Converter::bundled();
+    /// assert_eq!(converter.convert_between(1.5, "kg", "g").unwrap(), 1500.0);
+    /// assert!(converter.convert_between(1.0, "kg", "l").is_err());
+    /// ```
+    pub fn convert_between(&self, value: f64, from: &str, to: &str) -> Result<f64, ConvertError> {
+        let (from, to) = (ConvertUnit::Key(from), ConvertUnit::Key(to));
+        let from = self.get_unit(&from)?;
+        let to = self.get_unit(&to)?;
+        match self.convert_to_unit(ConvertValue::Number(value), from, to)? {
+            ConvertValue::Number(n) => Ok(n),
+            _ => unreachable!(),
+        }
+    }
+
     fn convert_to_unit(
         &self,
         value: ConvertValue,
@@ -667,6 +1237,12 @@ impl Converter {
                 to: target_unit.physical_quantity,
             });
         }
+        if unit.physical_quantity == PhysicalQuantity::Count && unit != target_unit {
+            return Err(ConvertError::DifferentCountUnits {
+                from: unit.to_string(),
+                to: target_unit.to_string(),
+            });
+        }
         Ok(self.convert_value(value, unit, target_unit))
     }
 
@@ -697,6 +1273,8 @@ impl Converter {
                 let e = self.convert_f64(*r.end(), from, to);
                 ConvertValue::Range(s..=e)
             }
+            ConvertValue::AtLeast(n) => ConvertValue::AtLeast(self.convert_f64(n, from, to)),
+            ConvertValue::AtMost(n) => ConvertValue::AtMost(self.convert_f64(n, from, to)),
         }
     }
 
@@ -729,6 +1307,59 @@ pub(crate) fn convert_f64(value: f64, from: &Unit, to: &Unit) -> f64 {
     (norm / to.ratio) - to.difference
 }
 
+/// How [`ScaledQuantity::fit_with`] selects the best unit
+///
+/// Only the best units configured for the physical quantity and system are
+/// considered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FitStrategy {
+    /// The biggest unit in which the value is at least 1
+    #[default]
+    Threshold,
+    /// The unit that writes the value with the least digits, while keeping it
+    /// equal or greater than 1 if possible
+    ///
+    /// For example, `1500 g` becomes `1.5 kg` but `950 g` stays the same.
+    NiceNumber,
+}
+
+/// Explanation of the result of [`ScaledQuantity::fit_verbose`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct FitTrace {
+    /// System of the units considered, if the unit is known
+    pub system: Option<System>,
+    /// If [`Self::system`] is the converter default because the unit has no
+    /// system
+    pub from_default_system: bool,
+    /// Best units of the system and the original value converted to each one
+    ///
+    /// For ranges, the start of the range.
+    pub candidates: Vec<(Arc<Unit>, f64)>,
+    /// Unit of the fitted quantity, if the unit is known
+    pub chosen: Option<Arc<Unit>>,
+    /// Why [`Self::chosen`] was selected
+    pub reason: FitReason,
+}
+
+/// See [`FitTrace`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FitReason {
+    /// No unit or an unknown unit, so the quantity was not changed
+    UnknownUnit,
+    /// The value fits a fraction in the unit, see
+    /// [`FractionsConfigHelper`](crate::convert::units_file::FractionsConfigHelper)
+    Fraction,
+    /// The biggest unit in which the value is at least 1, see
+    /// [`FitStrategy::Threshold`]
+    ///
+    /// The value may still be written as a fraction in this unit.
+    Magnitude,
+    /// The unit with the least digits, see [`FitStrategy::NiceNumber`]
+    NiceNumber,
+    /// A [count](PhysicalQuantity::Count) unit, so the quantity was not changed
+    Count,
+}
+
 /// Error when try to convert an unknown unit
 #[derive(Debug, Error)]
 #[error("Unknown unit: '{0}'")]
@@ -741,6 +1372,10 @@ pub enum ConvertValue {
     /// It will convert the range as if start and end were 2 calls to convert as
     /// a number
     Range(RangeInclusive<f64>),
+    /// Only the lower bound is converted
+    AtLeast(f64),
+    /// Only the upper bound is converted
+    AtMost(f64),
 }
 
 /// Input unit for [`Converter::convert`]
@@ -825,6 +1460,8 @@ impl From<ConvertValue> for Value {
                 start: (*r.start()).into(),
                 end: (*r.end()).into(),
             },
+            ConvertValue::AtLeast(n) => Self::AtLeast(n.into()),
+            ConvertValue::AtMost(n) => Self::AtMost(n.into()),
         }
     }
 }
@@ -835,6 +1472,8 @@ impl TryFrom<&Value> for ConvertValue {
         let value = match value {
             Value::Number(n) => ConvertValue::Number(n.value()),
             Value::Range { start, end } => ConvertValue::Range(start.value()..=end.value()),
+            Value::AtLeast(start) => ConvertValue::AtLeast(start.value()),
+            Value::AtMost(end) => ConvertValue::AtMost(end.value()),
             Value::Text(t) => return Err(ConvertError::TextValue(t.clone())),
         };
         Ok(value)
@@ -859,6 +1498,7 @@ impl PartialOrd<Self> for ConvertValue {
             match v {
                 ConvertValue::Number(n) => *n,
                 ConvertValue::Range(r) => *r.start(),
+                ConvertValue::AtLeast(n) | ConvertValue::AtMost(n) => *n,
             }
         }
         let this = extract(self);
@@ -882,6 +1522,9 @@ pub enum ConvertError {
         to: PhysicalQuantity,
     },
 
+    #[error("Different count units can't be converted: {from} {to}")]
+    DifferentCountUnits { from: String, to: String },
+
     #[error("Could not find best unit for a {physical_quantity} unit. System: {system:?}")]
     BestUnitNotFound {
         physical_quantity: PhysicalQuantity,
@@ -918,3 +1561,113 @@ impl Converter {
         })
     }
 }
+
+#[cfg(all(test, feature = "bundled_units"))]
+mod tests {
+    use super::*;
+    use crate::quantity::{IncompatibleUnits, QuantityAddError};
+    use test_case::test_case;
+
+    #[test]
+    fn shared_converter() {
+        // lookups only need `&self`, so one converter can be shared between threads
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Converter>();
+
+        let converter = Converter::bundled();
+        std::thread::scope(|s| {
+            for _ in 0..4 {
+                s.spawn(|| {
+                    assert_eq!(converter.convert_between(1.0, "kg", "g").unwrap(), 1000.0);
+                    assert!(converter.find_unit("tbsp").is_some());
+                });
+            }
+        });
+    }
+
+    #[test_case(1.5, "kg", "g" => 1500.0; "mass")]
+    #[test_case(250.0, "ml", "l" => 0.25; "volume")]
+    #[test_case(2.0, "cups", "ml" => 473.176; "volume imperial")]
+    #[test_case(100.0, "°C", "°F" => 212.0; "temperature")]
+    #[test_case(32.0, "fahrenheit", "celsius" => 0.0; "temperature names")]
+    fn convert_between(value: f64, from: &str, to: &str) -> f64 {
+        let converter = Converter::bundled();
+        round_float(converter.convert_between(value, from, to).unwrap())
+    }
+
+    #[test]
+    fn convert_between_errors() {
+        let converter = Converter::bundled();
+        assert!(matches!(
+            converter.convert_between(1.0, "kg", "ml"),
+            Err(ConvertError::MixedQuantities { .. })
+        ));
+        assert!(matches!(
+            converter.convert_between(1.0, "kg", "handful"),
+            Err(ConvertError::UnknownUnit(_))
+        ));
+    }
+
+    #[test]
+    fn best_units_for() {
+        let converter = Converter::bundled();
+        let symbols = |q, s| {
+            converter
+                .best_units_for(q, s)
+                .map(|u| u.symbols[0].to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            symbols(PhysicalQuantity::Mass, System::Metric),
+            ["mg", "g", "kg"]
+        );
+        assert_eq!(
+            symbols(PhysicalQuantity::Mass, System::Imperial),
+            ["oz", "lb"]
+        );
+        assert_eq!(
+            symbols(PhysicalQuantity::Time, System::Metric),
+            symbols(PhysicalQuantity::Time, System::Imperial)
+        );
+        assert!(symbols(PhysicalQuantity::Count, System::Metric).is_empty());
+    }
+
+    #[test]
+    fn count_quantities() {
+        let converter = Converter::bundled();
+        let q = |v: f64, u: Option<&str>| {
+            Quantity::new_and_parse(Value::from(v), u.map(String::from), &converter)
+        };
+
+        let eggs = q(2.0, None).try_add(&q(3.0, None), &converter).unwrap();
+        assert_eq!(eggs.value, Value::from(5.0));
+        assert_eq!(
+            q(2.0, None).physical_quantity(&converter),
+            Some(PhysicalQuantity::Count)
+        );
+
+        let cloves = q(2.0, Some("cloves"))
+            .try_add(&q(3.0, Some("cloves")), &converter)
+            .unwrap();
+        assert_eq!(cloves.value, Value::from(5.0));
+        assert_eq!(cloves.unit_text(), Some("cloves"));
+
+        assert!(q(2.0, None)
+            .try_add(&q(100.0, Some("g")), &converter)
+            .is_err());
+        assert!(q(2.0, Some("cloves"))
+            .try_add(&q(100.0, Some("g")), &converter)
+            .is_err());
+        assert!(matches!(
+            q(2.0, Some("cloves")).try_add(&q(1.0, Some("slice")), &converter),
+            Err(QuantityAddError::IncompatibleUnits(
+                IncompatibleUnits::DifferentCountUnits { .. }
+            ))
+        ));
+
+        let mut fitted = q(3.0, Some("cloves"));
+        fitted.fit(&converter).unwrap();
+        assert_eq!(fitted.value, Value::from(3.0));
+        assert_eq!(fitted.unit_text(), Some("cloves"));
+    }
+}
@@ -5,12 +5,17 @@ use once_cell::sync::Lazy;
 use serde::Deserialize;
 use std::{collections::HashMap, fmt::Debug, sync::Arc};
 
-use super::{FractionsConfig, PhysicalQuantity, System};
+use super::{
+    builder::{join_alias_vec, join_prefixes},
+    FractionsConfig, PhysicalQuantity, System,
+};
 
 /// Configuration struct for units used in [`ConverterBuilder`](super::ConverterBuilder)
 ///
 /// This structure is designed for deserializing [TOML](https://toml.io/en/),
-/// but you can try other formats supported by serde.
+/// but you can try other formats supported by serde. JSON and YAML are
+/// supported out of the box with the `units_json` and `units_yaml` features,
+/// see [`UnitsFile::from_str_auto`].
 #[derive(Debug, Deserialize, Clone)]
 #[serde(deny_unknown_fields)]
 pub struct UnitsFile {
@@ -139,7 +144,13 @@ impl FractionsConfigWrapper {
 pub struct FractionsConfigHelper {
     /// If fractions are enabled. Defaults to `false`
     pub enabled: Option<bool>,
-    /// Max percent of error allowed (0 to 1). Defaults to `0.05` (5%).
+    /// Max error allowed (0 to 1) relative to the value. Defaults to `0.05`
+    /// (5%).
+    ///
+    /// A value is only shown as a fraction if it's within this error of a
+    /// fraction with a denominator up to `max_denominator`. With the defaults,
+    /// `0.26` is `1/4` but `0.3` is kept as a decimal. Values out of range are
+    /// clamped.
     pub accuracy: Option<f32>,
     /// Max denominator allowed (1 to 16). Defaults to 4.
     #[serde(alias = "max_den")]
@@ -236,7 +247,8 @@ pub struct QuantityGroup {
     /// List of best units
     ///
     /// This is optional by at least one [`QuantityGroup`] of the quantity in
-    /// any [`UnitsFile`] in a converter has to define it and not be empty.
+    /// any [`UnitsFile`] in a converter has to define it and not be empty,
+    /// unless the quantity has no units or is [`PhysicalQuantity::Count`].
     ///
     /// **This will always replace the configuration from [`UnitsFile`] before**
     #[serde(default)]
@@ -337,6 +349,352 @@ pub struct UnitEntry {
     pub expand_si: bool,
 }
 
+/// Error loading a [`UnitsFile`] from text
+#[derive(Debug, thiserror::Error)]
+pub enum UnitsFileError {
+    #[cfg(feature = "units_toml")]
+    #[error("Invalid TOML units file")]
+    Toml(#[from] toml::de::Error),
+    #[cfg(feature = "units_json")]
+    #[error("Invalid JSON units file")]
+    Json(#[from] serde_json::Error),
+    #[cfg(feature = "units_yaml")]
+    #[error("Invalid YAML units file")]
+    Yaml(#[from] serde_yaml::Error),
+    #[error("Support for {0} units files is not enabled")]
+    FormatNotEnabled(&'static str),
+}
+
+impl UnitsFile {
+    /// Parse a TOML units file
+    ///
+    /// This is only available with the `units_toml` feature.
+    #[cfg(feature = "units_toml")]
+    pub fn from_toml(s: &str) -> Result<Self, UnitsFileError> {
+        Ok(toml::from_str(s)?)
+    }
+
+    /// Parse a JSON units file
+    ///
+    /// This is only available with the `units_json` feature.
+    #[cfg(feature = "units_json")]
+    pub fn from_json(s: &str) -> Result<Self, UnitsFileError> {
+        Ok(serde_json::from_str(s)?)
+    }
+
+    /// Parse a YAML units file
+    ///
+    /// This is only available with the `units_yaml` feature.
+    #[cfg(feature = "units_yaml")]
+    pub fn from_yaml(s: &str) -> Result<Self, UnitsFileError> {
+        Ok(serde_yaml::from_str(s)?)
+    }
+
+    /// Parse a units file detecting its format
+    ///
+    /// - JSON if it starts with `{`.
+    /// - TOML if the first line that is not empty or a comment is a table
+    ///   header or a `key = value` pair.
+    /// - YAML otherwise.
+    ///
+    /// If the support for the detected format is not enabled,
+    /// [`UnitsFileError::FormatNotEnabled`] is returned.
+    pub fn from_str_auto(s: &str) -> Result<Self, UnitsFileError> {
+        let first_line = s
+            .lines()
+            .map(str::trim)
+            .find(|l| !l.is_empty() && !l.starts_with('#'))
+            .unwrap_or_default();
+
+        if first_line.starts_with('{') {
+            #[cfg(feature = "units_json")]
+            return Self::from_json(s);
+            #[cfg(not(feature = "units_json"))]
+            return Err(UnitsFileError::FormatNotEnabled("JSON"));
+        }
+
+        let is_toml = first_line.starts_with('[')
+            || first_line
+                .find('=')
+                .is_some_and(|eq| first_line.find(':').is_none_or(|colon| eq < colon));
+        if is_toml {
+            #[cfg(feature = "units_toml")]
+            return Self::from_toml(s);
+            #[cfg(not(feature = "units_toml"))]
+            return Err(UnitsFileError::FormatNotEnabled("TOML"));
+        }
+
+        #[cfg(feature = "units_yaml")]
+        {
+            Self::from_yaml(s)
+        }
+        #[cfg(not(feature = "units_yaml"))]
+        {
+            Err(UnitsFileError::FormatNotEnabled("YAML"))
+        }
+    }
+
+    /// Merge another units file into this one
+    ///
+    /// `other` is joined to `self` with `precedence`, the same way the
+    /// [`ConverterBuilder`](super::ConverterBuilder) joins lists from different
+    /// layers:
+    ///
+    /// - [`Precedence::Before`]: `other` takes priority. Its names, symbols and
+    ///   aliases are placed first and its values are used.
+    /// - [`Precedence::After`]: `self` takes priority.
+    /// - [`Precedence::Override`]: what `other` defines replaces `self`.
+    ///
+    /// Units are merged when they belong to the same quantity and share a
+    /// name, symbol or alias. The `quantity` groups of the result are
+    /// regrouped, one per [`PhysicalQuantity`]. `extend` entries are merged by
+    /// key and keep the precedence of `self`.
+    ///
+    /// ```
+    /// # use cooklang::convert::{UnitsFile, units_file::Precedence};
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let base = UnitsFile::from_toml(r#"default_system = "metric""#)?;
+    /// let user = UnitsFile::from_toml(r#"default_system = "imperial""#)?;
+    /// let merged = base.merge(user, Precedence::Before);
+    /// assert_eq!(merged.default_system, Some(cooklang::convert::System::Imperial));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn merge(self, other: UnitsFile, precedence: Precedence) -> UnitsFile {
+        // (high priority, low priority)
+        fn prioritize<T>(a: T, b: T, precedence: Precedence) -> (T, T) {
+            match precedence {
+                Precedence::Before | Precedence::Override => (b, a),
+                Precedence::After => (a, b),
+            }
+        }
+
+        let (high, low) = prioritize(self.default_system, other.default_system, precedence);
+        let default_system = high.or(low);
+
+        let si = match (self.si, other.si) {
+            (Some(mut a), Some(b)) if precedence != Precedence::Override => Some(SI {
+                prefixes: join_prefixes(&mut a.prefixes, b.prefixes, precedence),
+                symbol_prefixes: join_prefixes(
+                    &mut a.symbol_prefixes,
+                    b.symbol_prefixes,
+                    precedence,
+                ),
+                precedence: a.precedence,
+            }),
+            (a, b) => b.or(a),
+        };
+
+        let fractions = match (self.fractions, other.fractions) {
+            (Some(a), Some(b)) if precedence != Precedence::Override => {
+                let (mut high, low) = prioritize(a, b, precedence);
+                high.all = high.all.or(low.all);
+                high.metric = high.metric.or(low.metric);
+                high.imperial = high.imperial.or(low.imperial);
+                for (q, cfg) in low.quantity {
+                    high.quantity.entry(q).or_insert(cfg);
+                }
+                for (u, cfg) in low.unit {
+                    high.unit.entry(u).or_insert(cfg);
+                }
+                Some(high)
+            }
+            (a, b) => b.or(a),
+        };
+
+        let extend = match (self.extend, other.extend) {
+            (Some(mut a), Some(b)) if precedence != Precedence::Override => {
+                for (key, entry) in b.units {
+                    let entry = match a.units.remove(&key) {
+                        Some(current) => merge_extend_entry(current, entry, precedence),
+                        None => entry,
+                    };
+                    a.units.insert(key, entry);
+                }
+                Some(a)
+            }
+            (a, b) => b.or(a),
+        };
+
+        let mut groups: Vec<MergeGroup> = Vec::new();
+        for group in self.quantity {
+            MergeGroup::add(&mut groups, group, Precedence::After, true);
+        }
+        for group in other.quantity {
+            MergeGroup::add(&mut groups, group, precedence, false);
+        }
+        let quantity = groups.into_iter().map(MergeGroup::finish).collect();
+
+        UnitsFile {
+            default_system,
+            si,
+            fractions,
+            extend,
+            quantity,
+        }
+    }
+}
+
+/// All the units of a quantity while merging [`UnitsFile`]s
+struct MergeGroup {
+    quantity: PhysicalQuantity,
+    best: Option<BestUnits>,
+    units: Vec<(Option<System>, UnitEntry)>,
+}
+
+impl MergeGroup {
+    fn add(groups: &mut Vec<Self>, group: QuantityGroup, precedence: Precedence, base: bool) {
+        let index = match groups.iter().position(|g| g.quantity == group.quantity) {
+            Some(index) => index,
+            None => {
+                groups.push(Self {
+                    quantity: group.quantity,
+                    best: None,
+                    units: Vec::new(),
+                });
+                groups.len() - 1
+            }
+        };
+        let target = &mut groups[index];
+
+        // best units always replace, unless the current file has priority
+        let replace = group.best.is_some() && (base || precedence != Precedence::After);
+        if replace || target.best.is_none() {
+            target.best = group.best;
+        }
+
+        let units = match group.units {
+            None => vec![],
+            Some(Units::Unified(units)) => units.into_iter().map(|u| (None, u)).collect(),
+            Some(Units::BySystem {
+                metric,
+                imperial,
+                unspecified,
+            }) => metric
+                .into_iter()
+                .map(|u| (Some(System::Metric), u))
+                .chain(imperial.into_iter().map(|u| (Some(System::Imperial), u)))
+                .chain(unspecified.into_iter().map(|u| (None, u)))
+                .collect(),
+        };
+
+        for (system, entry) in units {
+            let overlap = if base {
+                None
+            } else {
+                target.units.iter().position(|(_, u)| overlaps(u, &entry))
+            };
+            let Some(pos) = overlap else {
+                target.units.push((system, entry));
+                continue;
+            };
+            let (current_system, current) = &mut target.units[pos];
+            match precedence {
+                Precedence::Override => {
+                    *current_system = system;
+                    *current = entry;
+                }
+                Precedence::Before => {
+                    *current_system = system;
+                    current.ratio = entry.ratio;
+                    current.difference = entry.difference;
+                    current.expand_si = entry.expand_si;
+                    join_unique(&mut current.names, entry.names, precedence);
+                    join_unique(&mut current.symbols, entry.symbols, precedence);
+                    join_unique(&mut current.aliases, entry.aliases, precedence);
+                }
+                Precedence::After => {
+                    join_unique(&mut current.names, entry.names, precedence);
+                    join_unique(&mut current.symbols, entry.symbols, precedence);
+                    join_unique(&mut current.aliases, entry.aliases, precedence);
+                }
+            }
+        }
+    }
+
+    fn finish(self) -> QuantityGroup {
+        let units = if self.units.is_empty() {
+            None
+        } else if self.units.iter().all(|(s, _)| s.is_none()) {
+            Some(Units::Unified(
+                self.units.into_iter().map(|(_, u)| u).collect(),
+            ))
+        } else {
+            let mut metric = Vec::new();
+            let mut imperial = Vec::new();
+            let mut unspecified = Vec::new();
+            for (system, unit) in self.units {
+                match system {
+                    Some(System::Metric) => metric.push(unit),
+                    Some(System::Imperial) => imperial.push(unit),
+                    None => unspecified.push(unit),
+                }
+            }
+            Some(Units::BySystem {
+                metric,
+                imperial,
+                unspecified,
+            })
+        };
+        QuantityGroup {
+            quantity: self.quantity,
+            best: self.best,
+            units,
+        }
+    }
+}
+
+fn overlaps(a: &UnitEntry, b: &UnitEntry) -> bool {
+    let keys = |u: &UnitEntry| -> Vec<Arc<str>> {
+        u.names
+            .iter()
+            .chain(&u.symbols)
+            .chain(&u.aliases)
+            .cloned()
+            .collect()
+    };
+    let a = keys(a);
+    keys(b).iter().any(|k| a.contains(k))
+}
+
+fn join_unique(target: &mut Vec<Arc<str>>, src: Vec<Arc<str>>, precedence: Precedence) {
+    join_alias_vec(target, src, precedence);
+    let mut seen = std::collections::HashSet::new();
+    target.retain(|v| seen.insert(Arc::clone(v)));
+}
+
+fn merge_extend_entry(
+    a: ExtendUnitEntry,
+    b: ExtendUnitEntry,
+    precedence: Precedence,
+) -> ExtendUnitEntry {
+    fn join(
+        a: Option<Vec<Arc<str>>>,
+        b: Option<Vec<Arc<str>>>,
+        precedence: Precedence,
+    ) -> Option<Vec<Arc<str>>> {
+        match (a, b) {
+            (Some(mut a), Some(b)) => {
+                join_unique(&mut a, b, precedence);
+                Some(a)
+            }
+            (a, b) => a.or(b),
+        }
+    }
+
+    let (high, low) = match precedence {
+        Precedence::After => (&a, &b),
+        _ => (&b, &a),
+    };
+    ExtendUnitEntry {
+        ratio: high.ratio.or(low.ratio),
+        difference: high.difference.or(low.difference),
+        names: join(a.names, b.names, precedence),
+        symbols: join(a.symbols, b.symbols, precedence),
+        aliases: join(a.aliases, b.aliases, precedence),
+    }
+}
+
 #[cfg(feature = "bundled_units")]
 impl UnitsFile {
     /// Get the bundled units file
@@ -348,3 +706,117 @@ impl UnitsFile {
         FILE.clone()
     }
 }
+
+#[cfg(all(
+    test,
+    feature = "units_toml",
+    feature = "units_json",
+    feature = "units_yaml"
+))]
+mod tests {
+    use super::*;
+    use crate::Converter;
+    use indoc::indoc;
+    use test_case::test_case;
+
+    const TOML: &str = indoc! {r#"
+        # comment
+        default_system = "metric"
+
+        [[quantity]]
+        quantity = "mass"
+        best = ["g", "kg"]
+        [quantity.units]
+        metric = [
+            { names = ["gram", "grams"], symbols = ["g"], ratio = 1 },
+            { names = ["kilogram", "kilograms"], symbols = ["kg"], ratio = 1000 },
+        ]
+    "#};
+
+    const JSON: &str = indoc! {r#"
+        {
+            "default_system": "metric",
+            "quantity": [{
+                "quantity": "mass",
+                "best": ["g", "kg"],
+                "units": {
+                    "metric": [
+                        { "names": ["gram", "grams"], "symbols": ["g"], "ratio": 1 },
+                        { "names": ["kilogram", "kilograms"], "symbols": ["kg"], "ratio": 1000 }
+                    ]
+                }
+            }]
+        }
+    "#};
+
+    const YAML: &str = indoc! {r#"
+        # comment
+        default_system: metric
+        quantity:
+          - quantity: mass
+            best: [g, kg]
+            units:
+              metric:
+                - { names: [gram, grams], symbols: [g], ratio: 1 }
+                - { names: [kilogram, kilograms], symbols: [kg], ratio: 1000 }
+    "#};
+
+    fn converter(file: UnitsFile) -> Converter {
+        Converter::builder()
+            .with_units_file(file)
+            .unwrap()
+            .finish()
+            .unwrap()
+    }
+
+    #[test]
+    fn formats() {
+        let toml = converter(UnitsFile::from_toml(TOML).unwrap());
+        let json = converter(UnitsFile::from_json(JSON).unwrap());
+        let yaml = converter(UnitsFile::from_yaml(YAML).unwrap());
+        assert_eq!(toml.unit_count(), 2);
+        assert_eq!(toml, json);
+        assert_eq!(toml, yaml);
+    }
+
+    #[test]
+    fn auto_format() {
+        let toml = converter(UnitsFile::from_str_auto(TOML).unwrap());
+        assert_eq!(converter(UnitsFile::from_str_auto(JSON).unwrap()), toml);
+        assert_eq!(converter(UnitsFile::from_str_auto(YAML).unwrap()), toml);
+        assert!(matches!(
+            UnitsFile::from_str_auto("default_system: 3"),
+            Err(UnitsFileError::Yaml(_))
+        ));
+    }
+
+    #[test_case(Precedence::Before => ("gramme, gram, grams".to_string(), "gr, g".to_string(), 2.0, System::Imperial); "before")]
+    #[test_case(Precedence::After => ("gram, grams, gramme".to_string(), "g, gr".to_string(), 1.0, System::Metric); "after")]
+    #[test_case(Precedence::Override => ("gramme".to_string(), "gr, g".to_string(), 2.0, System::Imperial); "override")]
+    fn merge(precedence: Precedence) -> (String, String, f64, System) {
+        let other = UnitsFile::from_toml(indoc! {r#"
+            default_system = "imperial"
+
+            [[quantity]]
+            quantity = "mass"
+            [quantity.units]
+            metric = [
+                { names = ["gramme"], symbols = ["gr", "g"], ratio = 2 },
+                { names = ["pound"], symbols = ["lb"], ratio = 453.59 },
+            ]
+        "#})
+        .unwrap();
+        let merged = UnitsFile::from_toml(TOML).unwrap().merge(other, precedence);
+        let converter = converter(merged);
+        assert_eq!(converter.unit_count(), 3);
+        assert!(converter.find_unit("lb").is_some());
+        let gram = converter.find_unit("g").unwrap();
+        assert_eq!(converter.find_unit("gr").unwrap(), gram);
+        (
+            gram.names.join(", "),
+            gram.symbols.join(", "),
+            gram.ratio,
+            converter.default_system(),
+        )
+    }
+}
@@ -0,0 +1,305 @@
+//! Compare two versions of a recipe
+//!
+//! ```
+//! # use cooklang::diff::IngredientChange;
+//! let old = cooklang::parse("Mix @flour{200%g} and @water{100%ml}.").unwrap_output();
+//! let new = cooklang::parse("Mix @flour{250%g} and @water{100%ml}.").unwrap_output();
+//! let diff = old.diff(&new);
+//! assert_eq!(diff.ingredients.len(), 1);
+//! assert!(matches!(&diff.ingredients[0], IngredientChange::Changed { name, .. } if name == "flour"));
+//! assert!(diff.steps.is_empty());
+//! ```
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::{
+    model::{Content, Recipe},
+    quantity::{Quantity, QuantityValue},
+    Value,
+};
+
+/// Differences between two recipes
+///
+/// Created from [`Recipe::diff`]. Only the ingredients and steps are
+/// compared, not the metadata.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RecipeDiff<V: QuantityValue = Value> {
+    /// Ingredients removed or changed, in the order of the old recipe,
+    /// followed by the added ones, in the order of the new recipe
+    pub ingredients: Vec<IngredientChange<V>>,
+    /// Steps added, removed or with a different text, in order
+    pub steps: Vec<StepChange>,
+}
+
+impl<V: QuantityValue> RecipeDiff<V> {
+    /// Both recipes have the same ingredients and steps
+    pub fn is_empty(&self) -> bool {
+        self.ingredients.is_empty() && self.steps.is_empty()
+    }
+}
+
+/// A value before and after a change
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Change<T> {
+    pub before: T,
+    pub after: T,
+}
+
+impl<T: PartialEq> Change<T> {
+    fn new(before: T, after: T) -> Option<Self> {
+        (before != after).then_some(Self { before, after })
+    }
+}
+
+/// Change of an ingredient in a [`RecipeDiff`]
+///
+/// Indices are positions in [`Recipe::ingredients`]. References are compared
+/// like any other ingredient, so a change in the quantity of a reference is
+/// also reported.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum IngredientChange<V: QuantityValue = Value> {
+    /// Only in the new recipe
+    Added { name: String, index: usize },
+    /// Only in the old recipe
+    Removed { name: String, index: usize },
+    /// In both recipes but with a different quantity or note
+    Changed {
+        name: String,
+        index: Change<usize>,
+        /// The quantity, if changed
+        quantity: Option<Change<Option<Quantity<V>>>>,
+        /// The note, if changed
+        note: Option<Change<Option<String>>>,
+    },
+}
+
+/// Change of a step in a [`RecipeDiff`]
+///
+/// Steps are identified by their section index and step number. The text
+/// compared is the [plain text](Recipe::plain_text) of the step without
+/// quantities, so a quantity change is only reported in the ingredients.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum StepChange {
+    /// Only in the new recipe
+    Added {
+        section: usize,
+        number: u32,
+        text: String,
+    },
+    /// Only in the old recipe
+    Removed {
+        section: usize,
+        number: u32,
+        text: String,
+    },
+    /// In both recipes but with a different text
+    ///
+    /// The section and number are the ones in the new recipe.
+    Changed {
+        section: usize,
+        number: u32,
+        text: Change<String>,
+    },
+}
+
+impl<D: Clone, V: QuantityValue + PartialEq> Recipe<D, V> {
+    /// Compare this recipe with a newer version of it
+    ///
+    /// Ingredients are aligned by name, ignoring case. When a name appears
+    /// more than once, the occurrences are aligned by position, first with
+    /// first, second with second and so on. Steps are aligned by their
+    /// position in the recipe.
+    pub fn diff(&self, other: &Self) -> RecipeDiff<V> {
+        RecipeDiff {
+            ingredients: diff_ingredients(self, other),
+            steps: diff_steps(self, other),
+        }
+    }
+}
+
+fn diff_ingredients<D, V: QuantityValue + PartialEq>(
+    old: &Recipe<D, V>,
+    new: &Recipe<D, V>,
+) -> Vec<IngredientChange<V>> {
+    // lowercase name -> indices of the new ingredients not aligned yet
+    let mut pending: HashMap<String, Vec<usize>> = HashMap::new();
+    for (index, igr) in new.ingredients.iter().enumerate().rev() {
+        pending
+            .entry(igr.name.to_lowercase())
+            .or_default()
+            .push(index);
+    }
+
+    let mut changes = Vec::new();
+    let mut aligned = vec![false; new.ingredients.len()];
+    for (index, igr) in old.ingredients.iter().enumerate() {
+        let Some(new_index) = pending.get_mut(&igr.name.to_lowercase()).and_then(Vec::pop) else {
+            changes.push(IngredientChange::Removed {
+                name: igr.name.clone(),
+                index,
+            });
+            continue;
+        };
+        aligned[new_index] = true;
+        let new_igr = &new.ingredients[new_index];
+        let quantity = Change::new(igr.quantity.clone(), new_igr.quantity.clone());
+        let note = Change::new(igr.note.clone(), new_igr.note.clone());
+        // only moving the ingredient is not a change
+        if quantity.is_none() && note.is_none() {
+            continue;
+        }
+        changes.push(IngredientChange::Changed {
+            name: new_igr.name.clone(),
+            index: Change {
+                before: index,
+                after: new_index,
+            },
+            quantity,
+            note,
+        });
+    }
+
+    for (index, igr) in new.ingredients.iter().enumerate() {
+        if !aligned[index] {
+            changes.push(IngredientChange::Added {
+                name: igr.name.clone(),
+                index,
+            });
+        }
+    }
+    changes
+}
+
+fn diff_steps<D: Clone, V: QuantityValue>(
+    old: &Recipe<D, V>,
+    new: &Recipe<D, V>,
+) -> Vec<StepChange> {
+    let steps = |recipe: &Recipe<D, V>| {
+        recipe
+            .sections
+            .iter()
+            .enumerate()
+            .flat_map(|(section_index, section)| {
+                section.content.iter().filter_map(move |c| match c {
+                    Content::Step(step) => Some((section_index, step)),
+                    Content::Text(_) => None,
+                })
+            })
+            .map(|(section, step)| (section, step.number, recipe.step_text(step, false)))
+            .collect::<Vec<_>>()
+    };
+    let old_steps = steps(old);
+    let new_steps = steps(new);
+
+    let mut changes = Vec::new();
+    for i in 0..old_steps.len().max(new_steps.len()) {
+        match (old_steps.get(i).cloned(), new_steps.get(i).cloned()) {
+            (Some((_, _, before)), Some((section, number, after))) => {
+                if let Some(text) = Change::new(before, after) {
+                    changes.push(StepChange::Changed {
+                        section,
+                        number,
+                        text,
+                    });
+                }
+            }
+            (Some((section, number, text)), None) => changes.push(StepChange::Removed {
+                section,
+                number,
+                text,
+            }),
+            (None, Some((section, number, text))) => changes.push(StepChange::Added {
+                section,
+                number,
+                text,
+            }),
+            (None, None) => unreachable!(),
+        }
+    }
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CooklangParser, Extensions, ScalableRecipe};
+
+    fn parse(input: &str) -> ScalableRecipe {
+        CooklangParser::new(Extensions::all(), Default::default())
+            .parse(input)
+            .unwrap_output()
+    }
+
+    #[test]
+    fn changed_quantity() {
+        let old = parse("Mix @flour{200%g} with @salt.");
+        let new = parse("Mix @flour{250%g} with @salt.");
+        let diff = old.diff(&new);
+        assert!(diff.steps.is_empty());
+        assert_eq!(diff.ingredients.len(), 1);
+        let IngredientChange::Changed {
+            name,
+            index,
+            quantity,
+            note,
+        } = &diff.ingredients[0]
+        else {
+            panic!("not changed")
+        };
+        assert_eq!(name, "flour");
+        assert_eq!(index.before, 0);
+        assert_eq!(index.after, 0);
+        assert!(note.is_none());
+        let quantity = quantity.as_ref().unwrap();
+        assert_eq!(quantity.before.as_ref().unwrap().to_string(), "200 g");
+        assert_eq!(quantity.after.as_ref().unwrap().to_string(), "250 g");
+    }
+
+    #[test]
+    fn added_removed() {
+        let old = parse("Mix @flour{200%g} with @salt.\n\nBake.");
+        let new = parse("Mix @flour{200%g} with @sugar.");
+        let diff = old.diff(&new);
+        assert_eq!(
+            diff.ingredients,
+            [
+                IngredientChange::Removed {
+                    name: "salt".into(),
+                    index: 1
+                },
+                IngredientChange::Added {
+                    name: "sugar".into(),
+                    index: 1
+                }
+            ]
+        );
+        assert_eq!(
+            diff.steps,
+            [
+                StepChange::Changed {
+                    section: 0,
+                    number: 1,
+                    text: Change {
+                        before: "Mix flour with salt.".into(),
+                        after: "Mix flour with sugar.".into()
+                    }
+                },
+                StepChange::Removed {
+                    section: 0,
+                    number: 2,
+                    text: "Bake.".into()
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn same_recipe() {
+        let recipe = parse("Mix @flour{200%g} and @&flour{50%g}.");
+        assert!(recipe.diff(&recipe).is_empty());
+    }
+}
@@ -44,6 +44,8 @@ pub struct SourceDiag {
     ///
     /// It should be ordered from high to low importance.
     pub hints: Vec<CowStr>,
+    /// Stable identifier of the kind of problem, if any
+    pub code: Option<&'static str>,
 }
 
 impl std::fmt::Display for SourceDiag {
@@ -64,13 +66,17 @@ impl RichError for SourceDiag {
     fn severity(&self) -> Severity {
         self.severity
     }
+
+    fn code(&self) -> Option<&str> {
+        self.code
+    }
 }
 
 impl std::error::Error for SourceDiag {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         // idk why I can't .as_deref but I can do this
         match &self.source {
-            Some(err) => Some(err),
+            Some(err) => Some(err.as_ref()),
             None => None,
         }
     }
@@ -92,6 +98,7 @@ impl SourceDiag {
             hints: vec![],
             source: None,
             stage,
+            code: None,
         }
     }
 
@@ -104,6 +111,7 @@ impl SourceDiag {
             hints: vec![],
             source: None,
             stage,
+            code: None,
         }
     }
 
@@ -118,6 +126,7 @@ impl SourceDiag {
             source: None,
             labels: vec![],
             hints: vec![],
+            code: None,
         }
     }
 
@@ -152,6 +161,17 @@ impl SourceDiag {
         self.hints.push(hint.into());
         self
     }
+    /// Sets the diagnostic code
+    pub(crate) fn set_code(mut self, code: &'static str) -> Self {
+        self.code = Some(code);
+        self
+    }
+
+    /// Machine readable version of the diagnostic
+    pub fn to_diagnostic(&self) -> Diagnostic {
+        Diagnostic::new(self)
+    }
+
     /// Sets the error source
     ///
     /// This is where [`std::error::Error::source`] get's the information
@@ -165,7 +185,8 @@ impl SourceDiag {
 }
 
 /// Diagnostic severity
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Severity {
     /// Fatal error
     Error,
@@ -219,6 +240,14 @@ impl SourceReport {
         self.buf.retain(f)
     }
 
+    /// Turns every warning into an error
+    pub(crate) fn promote_warnings(&mut self) {
+        for diag in &mut self.buf {
+            diag.severity = Severity::Error;
+        }
+        self.severity = None;
+    }
+
     pub(crate) fn set_severity(&mut self, severity: Option<Severity>) {
         debug_assert!(
             severity.is_none()
@@ -295,6 +324,11 @@ impl SourceReport {
         self.buf.retain(SourceDiag::is_error)
     }
 
+    /// Get all the diagnostics in a machine readable format
+    pub fn diagnostics(&self) -> Vec<Diagnostic> {
+        self.iter().map(SourceDiag::to_diagnostic).collect()
+    }
+
     /// Consumes the report and returns [`Vec`] of [`SourceDiag`]
     pub fn into_vec(self) -> Vec<SourceDiag> {
         self.buf
@@ -360,6 +394,13 @@ impl<T> PassResult<T> {
         &self.report
     }
 
+    /// Get all the diagnostics in a machine readable format
+    ///
+    /// See [`SourceReport::diagnostics`].
+    pub fn diagnostics(&self) -> Vec<Diagnostic> {
+        self.report.diagnostics()
+    }
+
     /// Check if the result is valid.
     ///
     /// If the result is invalid, the output, if any, should be discarded or
@@ -430,6 +471,54 @@ impl<T> PassResult<T> {
     }
 }
 
+/// Serializable diagnostic, useful for editor integrations
+///
+/// Spans are byte offsets in the source code.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct Diagnostic {
+    /// If the diagnostic is an error or warning
+    pub severity: Severity,
+    /// Stable identifier of the kind of problem, if any
+    pub code: Option<String>,
+    /// Description of the problem
+    pub message: String,
+    /// Main location of the problem, if any
+    pub span: Option<Span>,
+    /// All the locations with their messages. The first one is [`Self::span`]
+    pub labels: Vec<DiagnosticLabel>,
+    /// Hints to solve the problem, ordered from high to low importance
+    pub help: Vec<String>,
+}
+
+/// A location in a [`Diagnostic`]
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct DiagnosticLabel {
+    pub span: Span,
+    pub message: Option<String>,
+}
+
+impl Diagnostic {
+    /// Create a diagnostic from any [`RichError`]
+    pub fn new(err: &(impl RichError + ?Sized)) -> Self {
+        let labels: Vec<_> = err
+            .labels()
+            .iter()
+            .map(|(span, message)| DiagnosticLabel {
+                span: *span,
+                message: message.as_ref().map(|m| m.to_string()),
+            })
+            .collect();
+        Self {
+            severity: err.severity(),
+            code: err.code().map(str::to_string),
+            message: err.to_string(),
+            span: labels.first().map(|l| l.span),
+            labels,
+            help: err.hints().iter().map(|h| h.to_string()).collect(),
+        }
+    }
+}
+
 /// Trait to enhace errors with rich metadata
 pub trait RichError: std::error::Error {
     fn labels(&self) -> Cow<[Label]> {
@@ -441,6 +530,9 @@ pub trait RichError: std::error::Error {
     fn severity(&self) -> Severity {
         Severity::Error
     }
+    fn code(&self) -> Option<&str> {
+        None
+    }
 }
 
 /// Writes a rich error report
@@ -1,6 +1,6 @@
 //! Generate ingredients lists from recipes
 
-use std::collections::BTreeMap;
+use std::{collections::BTreeMap, sync::Arc};
 
 use serde::Serialize;
 
@@ -127,6 +127,92 @@ impl ScaledRecipe {
         }
         list
     }
+
+    /// List of cookware items without repeated names, with the amounts of
+    /// all the items with the same name grouped.
+    ///
+    /// This is like [`ScaledRecipe::group_cookware`] but different
+    /// definitions with the same [`display_name`](Cookware::display_name) are
+    /// merged in the first one, and only items for which
+    /// [`should_be_listed`](crate::Modifiers::should_be_listed) is true are
+    /// included. Items without amounts are just listed once.
+    ///
+    /// Order is the recipe order.
+    ///
+    /// ```
+    /// # use cooklang::{CooklangParser, Extensions, Converter};
+    /// let parser = CooklangParser::new(Extensions::all(), Converter::bundled());
+    /// let recipe = parser.parse("#bowl{1} #pan #bowl{1} #pan #-towel")
+    ///                 .into_output()
+    ///                 .unwrap()
+    ///                 .default_scale();
+    /// let list = recipe.cookware_list();
+    ///
+    /// assert_eq!(list.len(), 2);
+    /// assert_eq!(list[0].cookware.name, "bowl");
+    /// assert_eq!(list[0].amount.to_string(), "2");
+    /// assert_eq!(list[1].cookware.name, "pan");
+    /// assert!(list[1].amount.is_empty());
+    /// ```
+    pub fn cookware_list(&self) -> Vec<GroupedCookware<'_>> {
+        let mut list: Vec<GroupedCookware> = Vec::new();
+        for entry in self.group_cookware() {
+            if !entry.cookware.modifiers().should_be_listed() {
+                continue;
+            }
+            let name = entry.cookware.display_name();
+            if let Some(existing) = list.iter_mut().find(|e| e.cookware.display_name() == name) {
+                existing.amount.merge(&entry.amount);
+            } else {
+                list.push(entry);
+            }
+        }
+        list
+    }
+}
+
+/// How ingredient names are matched in an [`IngredientList`]
+///
+/// Two names are the same ingredient if they normalize to the same string.
+/// The default is [`CaseInsensitive`]. Implement it to add stemming or
+/// synonyms. It's also implemented for functions.
+///
+/// ```
+/// # use cooklang::{CooklangParser, ingredient_list::IngredientList};
+/// let parser = CooklangParser::extended();
+/// let recipe = parser
+///     .parse("@tomato{1} @tomatoes{2}")
+///     .unwrap_output()
+///     .default_scale();
+///
+/// let mut list = IngredientList::new();
+/// list.set_normalizer(|name: &str| name.to_lowercase().trim_end_matches("es").to_string());
+/// list.add_recipe(&recipe, parser.converter());
+/// assert_eq!(list.iter().count(), 1);
+/// assert_eq!(list.get("tomatoes").unwrap().to_string(), "3");
+/// ```
+pub trait NameNormalizer: Send + Sync {
+    /// Normalized form of the name, used only to match names
+    fn normalize(&self, name: &str) -> String;
+}
+
+impl<F> NameNormalizer for F
+where
+    F: Fn(&str) -> String + Send + Sync,
+{
+    fn normalize(&self, name: &str) -> String {
+        self(name)
+    }
+}
+
+/// Matches names ignoring the case. See [`NameNormalizer`]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CaseInsensitive;
+
+impl NameNormalizer for CaseInsensitive {
+    fn normalize(&self, name: &str) -> String {
+        name.to_lowercase()
+    }
 }
 
 /// List of ingredients with quantities.
@@ -134,8 +220,43 @@ impl ScaledRecipe {
 /// This will only store the ingredient name and quantity. Sorted by name. This
 /// is used to combine multiple recipes into a single list. For ingredients of a
 /// single recipe, check [`ScaledRecipe::group_ingredients`].
-#[derive(Debug, Default)]
-pub struct IngredientList(BTreeMap<String, GroupedQuantity>);
+///
+/// Names are matched with a [`NameNormalizer`], by default ignoring the case,
+/// but the first name added is the one listed. So `Flour` and `flour` are
+/// merged and listed as the one that comes first.
+#[derive(Default)]
+pub struct IngredientList {
+    list: BTreeMap<String, GroupedQuantity>,
+    /// Normalized names of the ingredients in the list, name -> key in `list`
+    names: BTreeMap<String, String>,
+    /// Other names of the ingredients, as written, name -> key in `list`
+    aliases: BTreeMap<String, String>,
+    normalizer: Normalizer,
+    /// Quantity of each recipe added with a name, key in `list` -> quantities
+    contributions: BTreeMap<String, Vec<(String, GroupedQuantity)>>,
+    include_pantry: bool,
+}
+
+#[derive(Clone)]
+struct Normalizer(Arc<dyn NameNormalizer>);
+
+impl Default for Normalizer {
+    fn default() -> Self {
+        Self(Arc::new(CaseInsensitive))
+    }
+}
+
+impl std::fmt::Debug for IngredientList {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("IngredientList")
+            .field("list", &self.list)
+            .field("names", &self.names)
+            .field("aliases", &self.aliases)
+            .field("contributions", &self.contributions)
+            .field("include_pantry", &self.include_pantry)
+            .finish_non_exhaustive()
+    }
+}
 
 impl IngredientList {
     /// Empty list
@@ -156,13 +277,60 @@ impl IngredientList {
     /// for each one.
     ///
     /// Only ingredients for which [`should_be_listed`](crate::Modifiers::should_be_listed)
-    /// is true are added.
+    /// is true are added. [Pantry](crate::model::Ingredient::pantry)
+    /// ingredients are skipped unless [`IngredientList::set_include_pantry`]
+    /// is used.
     ///
     /// Scaling outcomes are ignored, but logged with [tracing] if they are an
     /// error.
     ///
     /// Ingredients are listed based on their [`display_name`](crate::model::Ingredient::display_name).
+    /// An ingredient with an [alias](crate::model::Ingredient::alias) is also
+    /// known by its name, so `@flour|all-purpose{}` and `@flour{}` are merged,
+    /// as are `@flour|all-purpose{}` and `@all-purpose{}`. The first name
+    /// added is the one listed.
     pub fn add_recipe(&mut self, recipe: &ScaledRecipe, converter: &Converter) {
+        self.add_recipe_impl(recipe, None, converter)
+    }
+
+    /// Like [`IngredientList::add_recipe`], but it also keeps how much of
+    /// each ingredient comes from this recipe
+    ///
+    /// The recipe id can be anything to identify the recipe, like its name.
+    /// See [`IngredientList::contributions`].
+    ///
+    /// ```
+    /// # use cooklang::{CooklangParser, ingredient_list::IngredientList};
+    /// let parser = CooklangParser::extended();
+    /// let bread = parser.parse("@flour{200%g}").unwrap_output().default_scale();
+    /// let cake = parser.parse("@flour{300%g}").unwrap_output().default_scale();
+    ///
+    /// let mut list = IngredientList::new();
+    /// list.add_named_recipe("Bread", &bread, parser.converter());
+    /// list.add_named_recipe("Cake", &cake, parser.converter());
+    ///
+    /// assert_eq!(list.get("flour").unwrap().to_string(), "500 g");
+    /// let contributions = list.contributions("flour");
+    /// assert_eq!(contributions[0].0, "Bread");
+    /// assert_eq!(contributions[0].1.to_string(), "200 g");
+    /// assert_eq!(contributions[1].0, "Cake");
+    /// assert_eq!(contributions[1].1.to_string(), "300 g");
+    /// ```
+    pub fn add_named_recipe(
+        &mut self,
+        recipe_id: impl Into<String>,
+        recipe: &ScaledRecipe,
+        converter: &Converter,
+    ) {
+        self.add_recipe_impl(recipe, Some(recipe_id.into()), converter)
+    }
+
+    fn add_recipe_impl(
+        &mut self,
+        recipe: &ScaledRecipe,
+        recipe_id: Option<String>,
+        converter: &Converter,
+    ) {
         for entry in recipe.group_ingredients(converter) {
             let GroupedIngredient {
                 ingredient,
@@ -171,7 +339,9 @@ impl IngredientList {
                 ..
             } = entry;
 
-            if !ingredient.modifiers().should_be_listed() {
+            if !ingredient.modifiers().should_be_listed()
+                || (ingredient.pantry && !self.include_pantry)
+            {
                 continue;
             }
 
@@ -179,10 +349,97 @@ impl IngredientList {
                 tracing::error!("Error scaling ingredient: {err}");
             }
 
-            self.add_ingredient(ingredient.display_name().into_owned(), &quantity, converter);
+            let name = self.key_for(ingredient);
+            if let Some(alias) = &ingredient.alias {
+                for other in [&ingredient.name, alias] {
+                    if *other != name {
+                        let normalized = self.normalizer.0.normalize(other);
+                        self.names.insert(normalized, name.clone());
+                        self.aliases.insert(other.clone(), name.clone());
+                    }
+                }
+            }
+            if let Some(recipe_id) = &recipe_id {
+                self.add_contribution(&name, recipe_id, &quantity, converter);
+            }
+            self.add_ingredient(name, &quantity, converter);
         }
     }
 
+    fn add_contribution(
+        &mut self,
+        name: &str,
+        recipe_id: &str,
+        quantity: &GroupedQuantity,
+        converter: &Converter,
+    ) {
+        let contributions = self.contributions.entry(name.to_string()).or_default();
+        match contributions.iter_mut().find(|(id, _)| id == recipe_id) {
+            Some((_, existing)) => existing.merge(quantity, converter),
+            None => contributions.push((recipe_id.to_string(), quantity.clone())),
+        }
+    }
+
+    /// Quantity of an ingredient coming from each recipe
+    ///
+    /// Only recipes added with [`IngredientList::add_named_recipe`] are
+    /// here, in the order they were added. The sum of all of them is in
+    /// [`IngredientList::get`], with the rest of recipes. Like there, the
+    /// ingredient can be found by any of its names.
+    pub fn contributions(&self, name: &str) -> &[(String, GroupedQuantity)] {
+        self.resolve(name)
+            .and_then(|key| self.contributions.get(key))
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// Name an ingredient is listed with, an existing one if it matches any
+    /// of the names of the ingredient
+    fn key_for(&self, ingredient: &Ingredient) -> String {
+        let display_name = ingredient.display_name();
+        let existing = [display_name.as_ref(), ingredient.name.as_str()]
+            .into_iter()
+            .find_map(|name| self.resolve(name))
+            .map(str::to_string);
+        existing.unwrap_or_else(|| display_name.into_owned())
+    }
+
+    /// Name an ingredient is listed with, if in the list
+    fn resolve(&self, name: &str) -> Option<&str> {
+        if let Some((key, _)) = self.list.get_key_value(name) {
+            Some(key)
+        } else {
+            let normalized = self.normalizer.0.normalize(name);
+            self.names.get(&normalized).map(String::as_str)
+        }
+    }
+
+    /// Use another [`NameNormalizer`] to match the names
+    ///
+    /// The names already in the list are normalized again, but their
+    /// ingredients are not merged if they now match. In that case, the name
+    /// added first is the one found.
+    pub fn set_normalizer(&mut self, normalizer: impl NameNormalizer + 'static) -> &mut Self {
+        self.normalizer = Normalizer(Arc::new(normalizer));
+        self.names.clear();
+        for key in self.list.keys() {
+            self.names
+                .entry(self.normalizer.0.normalize(key))
+                .or_insert_with(|| key.clone());
+        }
+        for (alias, key) in &self.aliases {
+            self.names
+                .entry(self.normalizer.0.normalize(alias))
+                .or_insert_with(|| key.clone());
+        }
+        self
+    }
+
+    /// Also add pantry ingredients in [`IngredientList::add_recipe`]
+    pub fn set_include_pantry(&mut self, include: bool) -> &mut Self {
+        self.include_pantry = include;
+        self
+    }
+
     /// Add an ingredient to the list.
     ///
     /// The quantity will be merged will the ingredients with the same name.
@@ -192,12 +449,27 @@ impl IngredientList {
         quantity: &GroupedQuantity,
         converter: &Converter,
     ) {
-        self.0.entry(name).or_default().merge(quantity, converter)
+        let name = self.resolve(&name).map(str::to_string).unwrap_or(name);
+        self.names
+            .entry(self.normalizer.0.normalize(&name))
+            .or_insert_with(|| name.clone());
+        self.list
+            .entry(name)
+            .or_default()
+            .merge(quantity, converter)
+    }
+
+    /// Get the quantity of an ingredient
+    ///
+    /// The ingredient can be found by the name it's listed with or by any
+    /// other name it has in the recipes, i.e., its name or alias.
+    pub fn get(&self, name: &str) -> Option<&GroupedQuantity> {
+        self.resolve(name).and_then(|key| self.list.get(key))
     }
 
     /// Cheks if the list is empty
     pub fn is_empty(&self) -> bool {
-        self.0.is_empty()
+        self.list.is_empty()
     }
 
     /// Split this list into different categories.
@@ -206,24 +478,41 @@ impl IngredientList {
     pub fn categorize(self, aisle: &AisleConf) -> CategorizedIngredientList {
         let aisle = aisle.reverse();
         let mut categorized = CategorizedIngredientList::default();
-        for (name, quantity) in self.0 {
-            if let Some(cat) = aisle.get(name.as_str()) {
+        let mut contributions = self.contributions;
+        categorized.other.normalizer = self.normalizer.clone();
+        for (name, quantity) in self.list {
+            let list = if let Some(cat) = aisle.get(name.as_str()) {
                 categorized
                     .categories
                     .entry(cat.to_string())
-                    .or_default()
-                    .0
-                    .insert(name, quantity);
+                    .or_insert_with(|| IngredientList {
+                        normalizer: self.normalizer.clone(),
+                        ..Default::default()
+                    })
             } else {
-                categorized.other.0.insert(name, quantity);
+                &mut categorized.other
+            };
+            for (normalized, key) in &self.names {
+                if *key == name {
+                    list.names.insert(normalized.clone(), key.clone());
+                }
+            }
+            for (alias, key) in &self.aliases {
+                if *key == name {
+                    list.aliases.insert(alias.clone(), key.clone());
+                }
+            }
+            if let Some(c) = contributions.remove(&name) {
+                list.contributions.insert(name.clone(), c);
             }
+            list.list.insert(name, quantity);
         }
         categorized
     }
 
     /// Iterate over all ingredients sorted by name
     pub fn iter(&self) -> impl Iterator<Item = (&String, &GroupedQuantity)> {
-        self.0.iter()
+        self.list.iter()
     }
 }
 
@@ -234,7 +523,7 @@ impl IntoIterator for IngredientList {
 
     /// Iterate over all ingrediends sorted by name
     fn into_iter(self) -> Self::IntoIter {
-        self.0.into_iter()
+        self.list.into_iter()
     }
 }
 
@@ -1,11 +1,17 @@
 use std::str::Chars;
 
+use super::TokenKind;
+
 /// Peekable iterator from a &str
 ///
 /// This was adapted from <https://github.com/rust-lang/rust/blob/2d429f3064cb67710fe64dee293329089871d92b/compiler/rustc_lexer/src/cursor.rs>
 pub struct Cursor<'a> {
     len_remaining: usize,
     chars: Chars<'a>,
+    /// Extra chars that are lexed as a single token of the given kind
+    pub(crate) markers: Vec<(char, TokenKind)>,
+    /// Line comment prefix that replaces `--`
+    pub(crate) comment: Option<String>,
     #[cfg(debug_assertions)]
     prev: char,
 }
@@ -17,11 +23,21 @@ impl<'a> Cursor<'a> {
         Self {
             len_remaining: input.len(),
             chars: input.chars(),
+            markers: Vec::new(),
+            comment: None,
             #[cfg(debug_assertions)]
             prev: EOF_CHAR,
         }
     }
 
+    /// Same as [`Self::new`] but `markers` chars are lexed as a single token
+    pub fn with_markers(input: &'a str, markers: Vec<(char, TokenKind)>) -> Self {
+        Self {
+            markers,
+            ..Self::new(input)
+        }
+    }
+
     /// Returns the last eaten symbol.
     pub(crate) fn prev(&self) -> char {
         #[cfg(debug_assertions)]
@@ -41,6 +57,11 @@ impl<'a> Cursor<'a> {
         self.chars.clone().next().unwrap_or(EOF_CHAR)
     }
 
+    /// Returns the input that has not been consumed.
+    pub(crate) fn as_str(&self) -> &'a str {
+        self.chars.as_str()
+    }
+
     /// Checks if there is more input to consume.
     pub(crate) fn is_eof(&self) -> bool {
         self.chars.as_str().is_empty()
@@ -1,22 +1,58 @@
+//! Tokens of a cooklang file
+//!
+//! This is the lowest level of the parser, mostly useful for tools that work
+//! with the text itself, like syntax highlighters. To understand a recipe use
+//! the [`parser`](crate::parser) or just [`parse`](crate::parse).
+//!
+//! ```
+//! # use cooklang::lexer::{tokenize, TokenKind};
+//! let input = "Add @salt{1%tsp}.";
+//! let tokens: Vec<_> = tokenize(input).collect();
+//! assert_eq!(tokens[2].kind, TokenKind::At);
+//! assert_eq!(&input[tokens[3].span.range()], "salt");
+//! ```
+//!
+//! The tokens don't depend on the [`Extensions`](crate::Extensions). They
+//! are the same for any input, the extensions only change how the parser
+//! reads them.
+//!
+//! ## Stability
+//! New [`TokenKind`]s may be added in minor versions, the enum is non
+//! exhaustive. How a given input is split into tokens may also change in
+//! minor versions if the syntax changes, but the tokens will always cover
+//! all the input without gaps, in order.
+
 mod cursor;
 
-pub use cursor::Cursor;
+pub(crate) use cursor::Cursor;
 
 use finl_unicode::categories::CharacterCategories;
 
+pub use crate::parser::token_stream::Token;
+
+/// Split the input into [`Token`]s
+///
+/// The spans of the tokens are byte offsets in the input. A UTF-8 BOM at the
+/// start is skipped.
+pub fn tokenize(input: &str) -> impl Iterator<Item = Token> + '_ {
+    crate::parser::token_stream::TokenStream::new(input)
+}
+
 #[derive(Debug)]
-pub struct Token {
+pub(crate) struct RawToken {
     pub kind: TokenKind,
     pub len: u32,
 }
 
-impl Token {
-    fn new(kind: TokenKind, len: u32) -> Token {
-        Token { kind, len }
+impl RawToken {
+    fn new(kind: TokenKind, len: u32) -> RawToken {
+        RawToken { kind, len }
     }
 }
 
+/// Kind of a [`Token`]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum TokenKind {
     /// ">>"
     MetadataStart,
@@ -87,7 +123,7 @@ fn is_whitespace(c: char) -> bool {
     c.is_separator_space() || c == '\t'
 }
 
-fn is_word_char(c: char) -> bool {
+pub(crate) fn is_word_char(c: char) -> bool {
     // this is critical code for performance, check lexer benchmark before and after chaging it
     match c {
         c if c.is_alphabetic() => true, // quick return true
@@ -100,12 +136,23 @@ fn is_word_char(c: char) -> bool {
 }
 
 impl Cursor<'_> {
-    pub fn advance_token(&mut self) -> Token {
+    pub fn advance_token(&mut self) -> RawToken {
         let current = match self.bump() {
             Some(c) => c,
-            None => return Token::new(TokenKind::Eof, 0),
+            None => return RawToken::new(TokenKind::Eof, 0),
         };
 
+        if let Some(kind) = self.marker(current) {
+            self.reset_pos_within_token();
+            return RawToken::new(kind, current.len_utf8() as u32);
+        }
+
+        if self.is_custom_comment(current) {
+            let token = RawToken::new(self.line_comment(), self.pos_within_token());
+            self.reset_pos_within_token();
+            return token;
+        }
+
         let token_kind = match current {
             '\\' => {
                 self.bump(); // any
@@ -121,7 +168,7 @@ impl Cursor<'_> {
                 }
             }
             '-' => match self.first() {
-                '-' => self.line_comment(),
+                '-' if self.comment.is_none() => self.line_comment(),
                 _ => TokenKind::Minus,
             },
             '[' if self.first() == '-' => self.block_comment(),
@@ -156,13 +203,21 @@ impl Cursor<'_> {
             // anything else, word
             _ => self.word(),
         };
-        let token = Token::new(token_kind, self.pos_within_token());
+        let token = RawToken::new(token_kind, self.pos_within_token());
         self.reset_pos_within_token();
         token
     }
 
+    fn is_custom_comment(&self, current: char) -> bool {
+        match &self.comment {
+            Some(prefix) => prefix
+                .strip_prefix(current)
+                .is_some_and(|rest| self.as_str().starts_with(rest)),
+            None => false,
+        }
+    }
+
     fn line_comment(&mut self) -> TokenKind {
-        debug_assert!(self.prev() == '-' && self.first() == '-');
         // this makes the next newline don't have the '\r' if on windows, but
         // I don't think that's a problem
         self.eat_while(|c| c != '\n');
@@ -186,10 +241,23 @@ impl Cursor<'_> {
 
     fn word(&mut self) -> TokenKind {
         debug_assert!(self.pos_within_token() > 0); // at least one char
-        self.eat_while(is_word_char);
+        if self.markers.is_empty() {
+            self.eat_while(is_word_char);
+        } else {
+            // markers can be word chars, so they have to break the word too
+            let markers = std::mem::take(&mut self.markers);
+            self.eat_while(|c| is_word_char(c) && !markers.iter().any(|(m, _)| *m == c));
+            self.markers = markers;
+        }
         TokenKind::Word
     }
 
+    fn marker(&self, c: char) -> Option<TokenKind> {
+        self.markers
+            .iter()
+            .find_map(|(m, kind)| (*m == c).then_some(*kind))
+    }
+
     fn whitespace(&mut self) -> TokenKind {
         debug_assert!(is_whitespace(self.prev()));
         self.eat_while(is_whitespace);
@@ -311,7 +379,7 @@ mod tests {
     use super::*;
     use TokenKind::*;
 
-    fn tokenize(input: &str) -> impl Iterator<Item = Token> + '_ {
+    fn tokenize(input: &str) -> impl Iterator<Item = RawToken> + '_ {
         let mut cursor = Cursor::new(input);
         std::iter::from_fn(move || {
             let token = cursor.advance_token();
@@ -330,6 +398,32 @@ mod tests {
         };
     }
 
+    #[test]
+    fn public_tokens() {
+        let input = "\u{FEFF}>> key: value\n@eggs{2} [- note -]";
+        let tokens: Vec<_> = super::tokenize(input).collect();
+        assert_eq!(tokens[0].kind, MetadataStart);
+        assert_eq!(tokens[0].span.start(), 3);
+        // no gaps after the BOM
+        for pair in tokens.windows(2) {
+            assert_eq!(pair[0].span.end(), pair[1].span.start());
+        }
+        assert_eq!(tokens.last().unwrap().span.end(), input.len());
+        let kinds: Vec<_> = tokens[7..].iter().map(|t| t.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                At,
+                Word,
+                OpenBrace,
+                Int,
+                CloseBrace,
+                Whitespace,
+                BlockComment
+            ]
+        );
+    }
+
     #[test]
     fn word() {
         t!("basic", vec![Word]);
@@ -419,6 +513,39 @@ mod tests {
         );
     }
 
+    #[test]
+    fn markers() {
+        let lex = |input| {
+            let mut cursor = Cursor::with_markers(input, vec![('$', Hash), (',', At)]);
+            std::iter::from_fn(move || Some(cursor.advance_token().kind))
+                .take_while(|k| *k != Eof)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(lex("$pan"), vec![Hash, Word]);
+        assert_eq!(lex("a$pan"), vec![Word, Hash, Word]);
+        assert_eq!(lex(",salt"), vec![At, Word]);
+        assert_eq!(lex("#pan @salt"), vec![Hash, Word, Whitespace, At, Word]);
+        assert_eq!(lex("\\$"), vec![Escaped]);
+    }
+
+    #[test]
+    fn custom_comment() {
+        let lex = |input| {
+            let mut cursor = Cursor::new(input);
+            cursor.comment = Some(";;".to_string());
+            std::iter::from_fn(move || Some(cursor.advance_token().kind))
+                .take_while(|k| *k != Eof)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(lex(";; comment"), vec![LineComment]);
+        assert_eq!(
+            lex("word ;; comment\nword"),
+            vec![Word, Whitespace, LineComment, Newline, Word]
+        );
+        assert_eq!(lex("a;b"), vec![Word, Punctuation, Word]);
+        assert_eq!(lex("--"), vec![Minus, Minus]);
+    }
+
     #[test]
     fn recipe() {
         const S: TokenKind = TokenKind::Whitespace;
@@ -24,7 +24,7 @@
 //! # use cooklang::{CooklangParser, Converter, Extensions};
 //! // Create a parser
 //! // (this is the default configuration)
-//! let parser = CooklangParser::new(Extensions::all(), Converter::default());
+//! let parser = CooklangParser::new(Extensions::default(), Converter::default());
 //! # assert_eq!(parser, CooklangParser::default());
 //! ```
 //!
@@ -56,7 +56,7 @@ pub mod _extensions {
 
 #[cfg(doc)]
 pub mod _features {
-    //! This lib has 2 features, both enabled by default:
+    //! This lib has 2 features enabled by default:
     //! - `bundled_units`. Includes a units file with the most common units for
     //!   recipes in English. These units are available to load when you want
     //!   without the need to read a file. The default
@@ -64,15 +64,30 @@ pub mod _features {
     //!   enabled. [This is the bundled file](https://github.com/cooklang/cooklang-rs/blob/main/units.toml)
     //!
     //! - `aisle`. Enables the [`aisle`](crate::aisle) module.
+    //!
+    //! And optional features:
+    //! - `parallel`. Parse multiple files concurrently with
+    //!   [`CooklangParser::parse_many`](crate::CooklangParser::parse_many)
+    //!   using [rayon](https://docs.rs/rayon).
+    //! - `units_toml`, `units_json` and `units_yaml`. Load a
+    //!   [`UnitsFile`](crate::convert::UnitsFile) from each format.
+    //!   `units_toml` is enabled by `bundled_units`.
+    //! - `binary`. Compact binary serialization of recipes with
+    //!   [`Recipe::to_bytes`](crate::model::Recipe::to_bytes) and
+    //!   [`Recipe::from_bytes`](crate::model::Recipe::from_bytes).
 }
 
 #[cfg(feature = "aisle")]
 pub mod aisle;
 pub mod analysis;
 pub mod ast;
+pub mod builder;
 pub mod convert;
+pub mod diff;
 pub mod error;
 pub mod ingredient_list;
+pub mod lexer;
+pub mod lint;
 pub mod located;
 pub mod metadata;
 pub mod model;
@@ -82,7 +97,7 @@ pub mod scale;
 pub mod span;
 pub mod text;
 
-mod lexer;
+use std::path::{Path, PathBuf};
 
 use bitflags::bitflags;
 use serde::{Deserialize, Serialize};
@@ -108,7 +123,8 @@ bitflags! {
     /// This allows to enable or disable the extensions. See [extensions](_extensions)
     /// for a detailed explanation of all of them.
     ///
-    /// [`Extensions::default`] enables all extensions.
+    /// [`Extensions::default`] enables all extensions except the ones that
+    /// change how already valid recipes are parsed, see its docs.
     #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
     pub struct Extensions: u32 {
         /// Steps separation is a blank line, not a line break. This may break
@@ -140,12 +156,47 @@ bitflags! {
         const INTERMEDIATE_PREPARATIONS = 1 << 11 | Self::COMPONENT_MODIFIERS.bits();
         /// Enables special metadata key parsing
         const SPECIAL_METADATA = 1 << 12;
+        /// Allows to write the unit right after the number without any
+        /// separator, like `@igr{200g}`
+        ///
+        /// This extensions also enables [`Self::ADVANCED_UNITS`].
+        const LENIENT_UNITS = 1 << 13 | Self::ADVANCED_UNITS.bits();
+        /// Metadata values continue in the following lines that start with
+        /// whitespace, joined with a space. Indented `>>` lines are new
+        /// entries.
+        const METADATA_CONTINUATION = 1 << 14;
+        /// A trailing `!` in a numeric value, like `@milk{0.5!%cup}`, keeps
+        /// it as a decimal, it's never shown as a fraction
+        const DECIMAL_MARKER = 1 << 15;
+        /// Quantities without a component with `@{2%cups}`
+        const INLINE_QUANTITIES = 1 << 16;
+        /// Named timers without duration, like `~rest{}`, take it from the
+        /// `rest time` metadata entry
+        const TIMER_METADATA = 1 << 17;
+        /// Emit [`Event::Comment`](crate::parser::Event::Comment) for line and
+        /// block comments in the [`PullParser`](crate::parser::PullParser)
+        const COMMENT_EVENTS = 1 << 18;
+        /// Quantity values with `*` and `+`, like `@water{2*250%ml}`, are
+        /// evaluated to a number
+        const ARITHMETIC_QUANTITIES = 1 << 19;
+        /// Unicode fraction characters in quantity values, like `@milk{½%cup}`
+        /// or `{1½}`, are numbers
+        const UNICODE_FRACTIONS = 1 << 20;
+        /// Attach images or videos to a step with `![path]`, available in
+        /// [`Step::media`](crate::model::Step::media)
+        const STEP_MEDIA = 1 << 21;
+        /// Cookware quantities can have a unit that describes the item, like
+        /// `#baking dish{9%inch}`. See [`CookwareQuantityKind`](crate::model::CookwareQuantityKind).
+        const COOKWARE_UNITS = 1 << 22;
 
         /// Enables a subset of extensions to maximize compatibility with other
         /// cooklang parsers.
         ///
-        /// Currently it enables all the extensions except
-        /// [`Self::MULTILINE_STEPS`] and [`Self::TIMER_REQUIRES_TIME`].
+        /// Currently it enables [`Self::COMPONENT_MODIFIERS`],
+        /// [`Self::COMPONENT_NOTE`], [`Self::COMPONENT_ALIAS`],
+        /// [`Self::SECTIONS`], [`Self::ADVANCED_UNITS`], [`Self::MODES`],
+        /// [`Self::TEMPERATURE`], [`Self::TEXT_STEPS`], [`Self::RANGE_VALUES`],
+        /// [`Self::INTERMEDIATE_PREPARATIONS`] and [`Self::SPECIAL_METADATA`].
         ///
         /// **ADDITIONS TO THE EXTENSIONS THIS ENABLES WILL NOT BE CONSIDERED A BREAKING CHANGE**
         const COMPAT = Self::COMPONENT_MODIFIERS.bits()
@@ -162,13 +213,81 @@ bitflags! {
     }
 }
 
+impl Extensions {
+    /// Extensions that give a new meaning to input that was already valid, not
+    /// enabled by [`Extensions::default`]
+    ///
+    /// Only the own bit of [`Self::LENIENT_UNITS`], so [`Self::ADVANCED_UNITS`]
+    /// is kept.
+    const OPT_IN: Self = Self::from_bits_retain(
+        (Self::LENIENT_UNITS.bits() & !Self::ADVANCED_UNITS.bits())
+            | Self::METADATA_CONTINUATION.bits()
+            | Self::DECIMAL_MARKER.bits()
+            | Self::INLINE_QUANTITIES.bits()
+            | Self::TIMER_METADATA.bits()
+            | Self::COMMENT_EVENTS.bits()
+            | Self::ARITHMETIC_QUANTITIES.bits()
+            | Self::UNICODE_FRACTIONS.bits()
+            | Self::STEP_MEDIA.bits()
+            | Self::COOKWARE_UNITS.bits(),
+    );
+}
+
 impl Default for Extensions {
-    /// Enables all extensions
+    /// Enables all extensions except the opt-in ones, which give a new meaning
+    /// to input that was already valid:
+    /// [`Self::LENIENT_UNITS`], [`Self::METADATA_CONTINUATION`],
+    /// [`Self::DECIMAL_MARKER`], [`Self::INLINE_QUANTITIES`],
+    /// [`Self::TIMER_METADATA`], [`Self::COMMENT_EVENTS`],
+    /// [`Self::ARITHMETIC_QUANTITIES`], [`Self::UNICODE_FRACTIONS`],
+    /// [`Self::STEP_MEDIA`] and [`Self::COOKWARE_UNITS`].
+    ///
+    /// [`Extensions::all`] enables them too.
     fn default() -> Self {
-        Self::all()
+        Self::all() - Self::OPT_IN
     }
 }
 
+impl std::fmt::Display for Extensions {
+    /// Writes the enabled extensions names separated by `|`
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, (name, _)) in self.iter_names().enumerate() {
+            if i > 0 {
+                f.write_str("|")?;
+            }
+            f.write_str(name)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::str::FromStr for Extensions {
+    type Err = ParseExtensionsError;
+
+    /// Parses extensions names separated by `|`, case insensitive
+    ///
+    /// An empty string is parsed as [`Extensions::empty`].
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut extensions = Self::empty();
+        for name in s.split('|').map(str::trim) {
+            if name.is_empty() {
+                continue;
+            }
+            let flag = <Self as bitflags::Flags>::FLAGS
+                .iter()
+                .find(|flag| flag.name().eq_ignore_ascii_case(name))
+                .ok_or_else(|| ParseExtensionsError(name.to_string()))?;
+            extensions |= *flag.value();
+        }
+        Ok(extensions)
+    }
+}
+
+/// Error parsing [`Extensions`] from a string
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+#[error("Unknown extension: '{0}'")]
+pub struct ParseExtensionsError(pub String);
+
 /// A cooklang parser
 ///
 /// Instantiating this takes time and the first parse may take longer. So
@@ -183,6 +302,7 @@ impl Default for Extensions {
 pub struct CooklangParser {
     extensions: Extensions,
     converter: Converter,
+    markers: parser::ComponentMarkers,
 }
 
 pub type RecipeResult = PassResult<ScalableRecipe>;
@@ -196,9 +316,16 @@ impl CooklangParser {
         Self {
             extensions,
             converter,
+            markers: Default::default(),
         }
     }
 
+    /// Use extra markers for components, see [`parser::ComponentMarkers`]
+    pub fn with_component_markers(mut self, markers: parser::ComponentMarkers) -> Self {
+        self.markers = markers;
+        self
+    }
+
     /// Creates a new extended parser
     ///
     /// This enables all extensions and uses the bunlded units.
@@ -226,6 +353,11 @@ impl CooklangParser {
         self.extensions
     }
 
+    /// Get the extra component markers
+    pub fn component_markers(&self) -> &parser::ComponentMarkers {
+        &self.markers
+    }
+
     /// Parse a recipe
     pub fn parse(&self, input: &str) -> RecipeResult {
         self.parse_with_options(input, ParseOptions::default())
@@ -234,7 +366,7 @@ impl CooklangParser {
     /// Same as [`Self::parse`] but with aditional options
     #[tracing::instrument(level = "debug", name = "parse", skip_all, fields(len = input.len()))]
     pub fn parse_with_options(&self, input: &str, options: ParseOptions) -> RecipeResult {
-        let mut parser = parser::PullParser::new(input, self.extensions);
+        let mut parser = parser::PullParser::with_markers(input, self.extensions, &self.markers);
         analysis::parse_events(
             &mut parser,
             input,
@@ -258,7 +390,7 @@ impl CooklangParser {
         input: &str,
         options: ParseOptions,
     ) -> MetadataResult {
-        let parser = parser::PullParser::new(input, self.extensions);
+        let parser = parser::PullParser::with_markers(input, self.extensions, &self.markers);
         let meta_events = parser.into_meta_iter();
         analysis::parse_events(
             meta_events,
@@ -269,11 +401,110 @@ impl CooklangParser {
         )
         .map(|c| c.metadata)
     }
+
+    /// Parse only the metadata at the top of a recipe from a reader
+    ///
+    /// This assumes the metadata is at the top of the recipe and stops
+    /// reading as soon as it ends, so for indexing many recipes most of each
+    /// file is never read. The reader is read line by line and it stops at
+    /// the first line that is not:
+    ///
+    /// - A metadata entry, a line starting with `>>`.
+    /// - Blank, only whitespace.
+    /// - A line comment, whitespace and then the
+    ///   [comment prefix](parser::ComponentMarkers::comment_prefix).
+    /// - With [`Extensions::METADATA_CONTINUATION`], a continuation of an
+    ///   entry, a line starting with whitespace right after an entry or
+    ///   another continuation line.
+    ///
+    /// That first line is consumed from the reader but not parsed. Metadata
+    /// entries after it are not read, so the result can differ from
+    /// [`Self::parse_metadata`] with the full input. Block comments are not
+    /// skipped, they end the metadata too.
+    ///
+    /// Only I/O errors, including invalid UTF-8, are returned as [`Err`].
+    ///
+    /// ```
+    /// # use cooklang::CooklangParser;
+    /// let input = ">> servings: 2\n>> tags: quick\n\nMix @flour{200%g}.\n>> late: entry\n";
+    /// let parser = CooklangParser::extended();
+    /// let meta = parser.parse_metadata_reader(input.as_bytes()).unwrap().unwrap_output();
+    /// assert_eq!(meta.servings(), Some([2].as_slice()));
+    /// assert!(meta.map.contains_key("tags"));
+    /// assert!(!meta.map.contains_key("late"));
+    /// ```
+    pub fn parse_metadata_reader<R: std::io::BufRead>(
+        &self,
+        mut reader: R,
+    ) -> std::io::Result<MetadataResult> {
+        let continuation = self.extensions.contains(Extensions::METADATA_CONTINUATION);
+        let comment = self.markers.comment_prefix();
+        let mut input = String::new();
+        let mut in_entry = false;
+        loop {
+            let start = input.len();
+            if reader.read_line(&mut input)? == 0 {
+                break;
+            }
+            let line = input[start..].trim_start_matches('\u{feff}');
+            let trimmed = line.trim_start();
+            if line.starts_with(">>") {
+                in_entry = true;
+            } else if trimmed.is_empty() || trimmed.starts_with(comment) {
+                in_entry = false;
+            } else if !(continuation && in_entry && trimmed.len() < line.len()) {
+                input.truncate(start);
+                break;
+            }
+        }
+        Ok(self.parse_metadata(&input))
+    }
+
+    /// Parse multiple recipe files
+    ///
+    /// Each file is read and parsed independently and the result is returned
+    /// along with its path, in the same order as the given paths. If a file
+    /// can't be read, its result has no output and an error in the report.
+    ///
+    /// With the `parallel` [feature](crate::_features) the files are parsed
+    /// concurrently. Otherwise they are parsed one after another.
+    pub fn parse_many(&self, paths: &[PathBuf]) -> Vec<(PathBuf, RecipeResult)> {
+        #[cfg(feature = "parallel")]
+        {
+            use rayon::prelude::*;
+            paths
+                .par_iter()
+                .map(|path| (path.clone(), self.parse_file(path)))
+                .collect()
+        }
+        #[cfg(not(feature = "parallel"))]
+        {
+            paths
+                .iter()
+                .map(|path| (path.clone(), self.parse_file(path)))
+                .collect()
+        }
+    }
+
+    fn parse_file(&self, path: &Path) -> RecipeResult {
+        match std::fs::read_to_string(path) {
+            Ok(input) => self.parse(&input),
+            Err(err) => {
+                let mut report = error::SourceReport::empty();
+                report.error(error::SourceDiag::unlabeled(
+                    format!("Error reading '{}': {err}", path.display()),
+                    error::Severity::Error,
+                    error::Stage::Parse,
+                ));
+                PassResult::new(None, report)
+            }
+        }
+    }
 }
 
 /// Parse a recipe with a default [`CooklangParser`]. Avoid calling this in a loop.
 ///
-/// The default parser enables all extensions.
+/// The default parser enables the [default extensions](Extensions::default).
 ///
 /// **IMPORTANT:** If you are going to parse more than one recipe you may want
 /// to only create one [`CooklangParser`] and reuse it. Every time this function
@@ -283,3 +514,52 @@ impl CooklangParser {
 pub fn parse(input: &str) -> RecipeResult {
     CooklangParser::default().parse(input)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test_case("" => Ok(Extensions::empty()); "empty")]
+    #[test_case("MULTILINE_STEPS" => Ok(Extensions::MULTILINE_STEPS); "single")]
+    #[test_case("multiline_steps" => Ok(Extensions::MULTILINE_STEPS); "case insensitive")]
+    #[test_case(
+        "MULTILINE_STEPS|COMPONENT_NOTE" => Ok(Extensions::MULTILINE_STEPS | Extensions::COMPONENT_NOTE);
+        "multiple"
+    )]
+    #[test_case(
+        " sections | Text_Steps " => Ok(Extensions::SECTIONS | Extensions::TEXT_STEPS);
+        "whitespace"
+    )]
+    #[test_case(
+        "SECTIONS|NOT_AN_EXTENSION" => Err(ParseExtensionsError("NOT_AN_EXTENSION".into()));
+        "invalid"
+    )]
+    fn extensions_from_str(s: &str) -> Result<Extensions, ParseExtensionsError> {
+        s.parse()
+    }
+
+    #[test_case(Extensions::empty() => ""; "empty")]
+    #[test_case(Extensions::SECTIONS => "SECTIONS"; "single")]
+    #[test_case(Extensions::MULTILINE_STEPS | Extensions::COMPONENT_NOTE => "MULTILINE_STEPS|COMPONENT_NOTE"; "multiple")]
+    fn extensions_display(e: Extensions) -> String {
+        e.to_string()
+    }
+
+    #[test_case(Extensions::empty(); "empty")]
+    #[test_case(Extensions::all(); "all")]
+    #[test_case(Extensions::COMPAT; "compat")]
+    #[test_case(Extensions::INTERMEDIATE_PREPARATIONS; "composed")]
+    fn extensions_roundtrip(e: Extensions) {
+        assert_eq!(e.to_string().parse::<Extensions>().unwrap(), e);
+    }
+
+    #[test]
+    fn default_extensions() {
+        let default = Extensions::default();
+        assert!(default.contains(Extensions::COMPAT | Extensions::MULTILINE_STEPS));
+        assert!(default.contains(Extensions::TIMER_REQUIRES_TIME));
+        assert!(!default.contains(Extensions::LENIENT_UNITS));
+        assert!(!default.intersects(Extensions::COMMENT_EVENTS | Extensions::COOKWARE_UNITS));
+    }
+}
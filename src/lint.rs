@@ -0,0 +1,360 @@
+//! Optional checks for common recipe authoring mistakes
+//!
+//! Lints are not errors, a recipe with lints is valid. They are checked on
+//! a parsed [`Recipe`] and use the locations of the step items, see
+//! [`Step::item_span`](crate::Step::item_span).
+//!
+//! ```
+//! # use cooklang::lint::{lint, Lints};
+//! let input = "Add @salt and a pinch more of @salt{1%pinch}.";
+//! let recipe = cooklang::parse(input).unwrap_output();
+//! let lints = lint(&recipe, Lints::all());
+//! assert_eq!(lints.len(), 1);
+//! assert_eq!(lints[0].kind(), Lints::DUPLICATE_DEFINITION);
+//! assert_eq!(lints[0].code(), "duplicate-definition");
+//! assert_eq!(&input[lints[0].span.range()], "@salt{1%pinch}");
+//! ```
+
+use std::{borrow::Cow, collections::HashMap};
+
+use bitflags::bitflags;
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::{
+    error::{CowStr, Label, RichError, Severity},
+    model::{Content, Item, Recipe},
+    parser::Modifiers,
+    quantity::QuantityValue,
+    span::Span,
+};
+
+bitflags! {
+    /// Lints checked by [`lint`]
+    ///
+    /// [`Lints::default`] enables all of them.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct Lints: u32 {
+        /// An ingredient never has a quantity in the recipe
+        const MISSING_QUANTITY        = 1 << 0;
+        /// An ingredient is defined more than once instead of referenced
+        /// with `@&name`
+        const DUPLICATE_DEFINITION    = 1 << 1;
+        /// A cookware amount that looks like a duration, like `#oven{10 min}`
+        const COOKWARE_DURATION       = 1 << 2;
+        /// A step without ingredients, cookware or timers
+        const STEP_WITHOUT_COMPONENTS = 1 << 3;
+    }
+}
+
+impl Default for Lints {
+    /// Enables all lints
+    fn default() -> Self {
+        Self::all()
+    }
+}
+
+/// A problem found by [`lint`]
+#[derive(Debug, Clone)]
+pub struct Lint {
+    kind: Lints,
+    /// Description of the problem
+    pub message: CowStr,
+    /// Location of the problem in the source
+    pub span: Span,
+    /// Other related location, like a previous definition
+    pub related: Option<(Span, CowStr)>,
+    /// How to fix it, if any
+    pub hint: Option<CowStr>,
+    /// Lints are warnings by default
+    pub severity: Severity,
+}
+
+impl Lint {
+    fn new(kind: Lints, message: impl Into<CowStr>, span: Span) -> Self {
+        Self {
+            kind,
+            message: message.into(),
+            span,
+            related: None,
+            hint: None,
+            severity: Severity::Warning,
+        }
+    }
+
+    /// Which lint found it, only one flag is set
+    pub fn kind(&self) -> Lints {
+        self.kind
+    }
+
+    /// Stable identifier of the lint
+    pub fn code(&self) -> &'static str {
+        match self.kind {
+            Lints::MISSING_QUANTITY => "missing-quantity",
+            Lints::DUPLICATE_DEFINITION => "duplicate-definition",
+            Lints::COOKWARE_DURATION => "cookware-duration",
+            Lints::STEP_WITHOUT_COMPONENTS => "step-without-components",
+            _ => unreachable!("lint with more than one kind"),
+        }
+    }
+}
+
+impl std::fmt::Display for Lint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for Lint {}
+
+impl RichError for Lint {
+    fn labels(&self) -> Cow<'_, [Label]> {
+        let mut labels = vec![(self.span, None)];
+        if let Some((span, message)) = &self.related {
+            labels.push((*span, Some(message.clone())));
+        }
+        labels.into()
+    }
+
+    fn hints(&self) -> Cow<'_, [CowStr]> {
+        self.hint.iter().cloned().collect::<Vec<_>>().into()
+    }
+
+    fn severity(&self) -> Severity {
+        self.severity
+    }
+
+    fn code(&self) -> Option<&str> {
+        Some(Lint::code(self))
+    }
+}
+
+/// Check the enabled `lints` in a recipe
+///
+/// The lints are returned in the order they appear in the source. It also
+/// works with the output of a recipe with errors, like `#oven{10 min}`
+/// without [`COOKWARE_UNITS`](crate::Extensions::COOKWARE_UNITS). A recipe
+/// that was not parsed has no locations, so all the spans are empty.
+pub fn lint<D, V: QuantityValue>(recipe: &Recipe<D, V>, lints: Lints) -> Vec<Lint> {
+    let mut found = Vec::new();
+    // lowercase name -> (first definition span, has any quantity)
+    let mut ingredients: HashMap<String, (Span, bool)> = HashMap::new();
+    let mut missing_order = Vec::new();
+
+    let steps = recipe
+        .sections
+        .iter()
+        .flat_map(|s| &s.content)
+        .filter_map(|c| match c {
+            Content::Step(step) => Some(step),
+            Content::Text(_) => None,
+        });
+    for step in steps {
+        let span_of = |index: usize| step.item_span(index).unwrap_or(Span::pos(0));
+
+        // a step with only media has no items
+        if lints.contains(Lints::STEP_WITHOUT_COMPONENTS)
+            && !step.items.is_empty()
+            && step.items.iter().all(|i| matches!(i, Item::Text { .. }))
+        {
+            found.push(Lint::new(
+                Lints::STEP_WITHOUT_COMPONENTS,
+                "Step without ingredients, cookware or timers",
+                Span::union(span_of(0), span_of(step.items.len() - 1)),
+            ));
+        }
+
+        for (item_index, item) in step.items.iter().enumerate() {
+            match item {
+                Item::Ingredient { index } => {
+                    let igr = &recipe.ingredients[*index];
+                    let name = igr.name.to_lowercase();
+                    let has_quantity = igr.quantity.is_some();
+                    if igr.modifiers().contains(Modifiers::REF) {
+                        if let Some(entry) = ingredients.get_mut(&name) {
+                            entry.1 |= has_quantity;
+                        }
+                        continue;
+                    }
+                    match ingredients.get_mut(&name) {
+                        Some((first, any_quantity)) => {
+                            *any_quantity |= has_quantity;
+                            if lints.contains(Lints::DUPLICATE_DEFINITION) {
+                                let mut l = Lint::new(
+                                    Lints::DUPLICATE_DEFINITION,
+                                    format!("Ingredient '{name}' is defined more than once"),
+                                    span_of(item_index),
+                                );
+                                l.related = Some((*first, "first defined here".into()));
+                                l.hint = Some(
+                                    format!("Reference the first definition with '@&{name}'")
+                                        .into(),
+                                );
+                                found.push(l);
+                            }
+                        }
+                        None => {
+                            ingredients.insert(name.clone(), (span_of(item_index), has_quantity));
+                            missing_order.push(name);
+                        }
+                    }
+                }
+                Item::Cookware { index } if lints.contains(Lints::COOKWARE_DURATION) => {
+                    let cw = &recipe.cookware[*index];
+                    let Some(quantity) = &cw.quantity else {
+                        continue;
+                    };
+                    if looks_like_duration(quantity, cw.unit.as_deref()) {
+                        let mut l = Lint::new(
+                            Lints::COOKWARE_DURATION,
+                            format!("The amount of cookware '{}' looks like a duration", cw.name),
+                            span_of(item_index),
+                        );
+                        l.hint = Some("Use a timer, like '~{10%minutes}'".into());
+                        found.push(l);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    if lints.contains(Lints::MISSING_QUANTITY) {
+        for name in missing_order {
+            let (span, has_quantity) = ingredients[&name];
+            if !has_quantity {
+                found.push(Lint::new(
+                    Lints::MISSING_QUANTITY,
+                    format!("Ingredient '{name}' has no quantity"),
+                    span,
+                ));
+            }
+        }
+    }
+
+    found.sort_by_key(|l| l.span.start());
+    found
+}
+
+const DURATION_UNITS: &str = "(s|secs?|seconds?|m|mins?|minutes?|h|hrs?|hours?|days?)";
+
+fn looks_like_duration(value: &impl QuantityValue, unit: Option<&str>) -> bool {
+    static RE: Lazy<Regex> =
+        Lazy::new(|| Regex::new(&format!(r"(?i)^\d+([.,]\d+)?\s*{DURATION_UNITS}$")).unwrap());
+    // with a unit, like `#oven{20%min}`
+    static UNIT_RE: Lazy<Regex> =
+        Lazy::new(|| Regex::new(&format!("(?i)^{DURATION_UNITS}$")).unwrap());
+    if let Some(unit) = unit {
+        return UNIT_RE.is_match(unit.trim());
+    }
+    // many values are joined with `|` and linear ones end with `*`
+    value.is_text()
+        && value
+            .to_string()
+            .split('|')
+            .any(|v| RE.is_match(v.trim().trim_end_matches('*')))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Converter, CooklangParser, Extensions};
+    use indoc::indoc;
+
+    fn codes_with(input: &str, extensions: Extensions, lints: Lints) -> Vec<&'static str> {
+        let parser = CooklangParser::new(extensions, Converter::empty());
+        let recipe = parser.parse(input).into_output().unwrap();
+        lint(&recipe, lints).iter().map(Lint::code).collect()
+    }
+
+    fn codes(input: &str, lints: Lints) -> Vec<&'static str> {
+        codes_with(input, Extensions::default(), lints)
+    }
+
+    #[test]
+    fn all_lints() {
+        let input = indoc! {r#"
+            Add @flour{200%g} and @salt.
+
+            Mix it.
+
+            Add @flour{50%g} and @&salt.
+
+            Bake in the #oven{20 min}.
+        "#};
+        assert_eq!(
+            codes(input, Lints::all()),
+            [
+                "missing-quantity",
+                "step-without-components",
+                "duplicate-definition",
+                "cookware-duration"
+            ]
+        );
+    }
+
+    #[test]
+    fn spans() {
+        let input = "Add @salt and @salt{1%tsp}.\n\nBake in the #oven{20 min}.";
+        let recipe = crate::parse(input).into_output().unwrap();
+        let lints = lint(&recipe, Lints::all());
+        let spans = lints
+            .iter()
+            .map(|l| &input[l.span.range()])
+            .collect::<Vec<_>>();
+        assert_eq!(spans, ["@salt{1%tsp}", "#oven{20 min}"]);
+        assert_eq!(
+            &input[lints[0].related.as_ref().unwrap().0.range()],
+            "@salt"
+        );
+    }
+
+    #[test]
+    fn cookware_duration() {
+        // without cookware units the amount is text, even with advanced units
+        let input = "Bake in the #oven{20 min}.";
+        assert!(Extensions::default().contains(Extensions::ADVANCED_UNITS));
+        assert_eq!(
+            codes(input, Lints::COOKWARE_DURATION),
+            ["cookware-duration"]
+        );
+        let extensions = Extensions::default() - Extensions::ADVANCED_UNITS;
+        assert_eq!(
+            codes_with(input, extensions, Lints::COOKWARE_DURATION),
+            ["cookware-duration"]
+        );
+
+        let extensions = Extensions::default() | Extensions::COOKWARE_UNITS;
+        let input = "Bake in the #oven{20%min}.";
+        assert_eq!(
+            codes_with(input, extensions, Lints::COOKWARE_DURATION),
+            ["cookware-duration"]
+        );
+        let input = "Use a #baking dish{9%inch}.";
+        assert!(codes_with(input, extensions, Lints::COOKWARE_DURATION).is_empty());
+    }
+
+    #[test]
+    fn toggle() {
+        let input = "Add @salt.\n\nMix it.";
+        assert_eq!(codes(input, Lints::empty()), Vec::<&str>::new());
+        assert_eq!(codes(input, Lints::MISSING_QUANTITY), ["missing-quantity"]);
+        assert_eq!(
+            codes(input, Lints::STEP_WITHOUT_COMPONENTS),
+            ["step-without-components"]
+        );
+    }
+
+    #[test]
+    fn media_only_step() {
+        let input = "Boil @water{1%l}.\n\n![photo.jpg]\n";
+        let extensions = Extensions::default() | Extensions::STEP_MEDIA;
+        assert!(codes_with(input, extensions, Lints::all()).is_empty());
+    }
+
+    #[test]
+    fn quantity_in_reference() {
+        let input = "Add @salt and then @&salt{1%tsp}.";
+        assert!(codes(input, Lints::all()).is_empty());
+    }
+}
@@ -22,7 +22,8 @@ pub(crate) use regex;
 
 use crate::{
     convert::{ConvertError, ConvertTo, ConvertUnit, ConvertValue, PhysicalQuantity, UnknownUnit},
-    Converter,
+    quantity::{Quantity, ScaledQuantity, Value},
+    Converter, Span,
 };
 
 /// Metadata of a recipe
@@ -34,13 +35,32 @@ use crate::{
 /// Also, most of these values will not have been parsed if the
 /// [`SPECIAL_METADATA`](crate::Extensions::SPECIAL_METADATA) extension is not
 /// enabled.
-#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Default)]
+///
+/// The location of the entries, [`Metadata::key_span`] and
+/// [`Metadata::value_span`], is not serialized and not compared.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct Metadata {
     special: HashMap<SpecialKey, SpecialValue>,
     /// All the raw key/value pairs from the recipe
     pub map: IndexMap<String, String>,
+    /// Location of the key and value of each entry in [`Self::map`]
+    #[serde(skip)]
+    pub(crate) spans: HashMap<String, (Span, Span)>,
+    /// Parsed values of the entries configured as lists
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    lists: HashMap<String, Vec<String>>,
+}
+
+impl PartialEq for Metadata {
+    fn eq(&self, other: &Self) -> bool {
+        self.special == other.special && self.map == other.map && self.lists == other.lists
+    }
 }
 
+/// Metadata keys with a special meaning
+///
+/// The [`Display`](std::fmt::Display) and [`FromStr`] implementations use the
+/// default english names.
 #[derive(
     Debug,
     Clone,
@@ -56,19 +76,152 @@ pub struct Metadata {
 )]
 #[serde(rename_all = "snake_case")]
 #[strum(serialize_all = "snake_case")]
-pub(crate) enum SpecialKey {
+pub enum SpecialKey {
+    /// See [`Metadata::description`]
     Description,
+    /// See [`Metadata::tags`]
     #[strum(serialize = "tag", to_string = "tags")]
     Tags,
+    /// See [`Metadata::emoji`]
     Emoji,
+    /// See [`Metadata::author`]
     Author,
+    /// See [`Metadata::source`]
     Source,
+    /// See [`Metadata::time`]
     Time,
+    /// Preparation part of [`Metadata::time`]
     #[strum(serialize = "prep_time", to_string = "prep time")]
     PrepTime,
+    /// Cooking part of [`Metadata::time`]
     #[strum(serialize = "cook_time", to_string = "cook time")]
     CookTime,
+    /// See [`Metadata::servings`]
     Servings,
+    /// See [`Metadata::scale`]
+    Scale,
+    /// See [`Metadata::fraction_denominator`]
+    #[strum(serialize = "fraction_denominator", to_string = "fraction denominator")]
+    FractionDenominator,
+    /// See [`Metadata::yield_`]
+    Yield,
+}
+
+/// Other names for the [`SpecialKey`]s
+///
+/// Recipes written in other languages can use their own keys and still get
+/// the typed values of [`Metadata`]. Keys without an alias keep working with
+/// the default english names. Set it in
+/// [`ParseOptions::special_keys`](crate::ParseOptions::special_keys).
+///
+/// [`Metadata::parse_special`] and [`Metadata::map_filtered`] only know the
+/// default names.
+///
+/// ```
+/// # use cooklang::{metadata::{SpecialKey, SpecialKeyMap}, CooklangParser, ParseOptions};
+/// let mut special_keys = SpecialKeyMap::new();
+/// special_keys
+///     .add("raciones", SpecialKey::Servings)
+///     .add("etiquetas", SpecialKey::Tags);
+///
+/// let options = ParseOptions {
+///     special_keys,
+///     ..Default::default()
+/// };
+/// let input = ">> raciones: 4\n>> etiquetas: postre, chocolate\n";
+/// let recipe = CooklangParser::default()
+///     .parse_with_options(input, options)
+///     .unwrap_output();
+/// assert_eq!(recipe.metadata.servings(), Some([4].as_slice()));
+/// assert_eq!(recipe.metadata.tags().unwrap(), ["postre", "chocolate"]);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SpecialKeyMap {
+    aliases: HashMap<String, SpecialKey>,
+}
+
+impl SpecialKeyMap {
+    /// Creates an empty map, only the default names are used
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add another name for a special key
+    pub fn add(&mut self, alias: impl Into<String>, key: SpecialKey) -> &mut Self {
+        self.aliases.insert(alias.into(), key);
+        self
+    }
+
+    /// Get the special key for a metadata key, if any
+    ///
+    /// The aliases are checked first, then the default names.
+    pub fn get(&self, key: &str) -> Option<SpecialKey> {
+        self.aliases
+            .get(key)
+            .copied()
+            .or_else(|| SpecialKey::from_str(key).ok())
+    }
+}
+
+/// Metadata keys whose values are lists
+///
+/// The values of these keys are split on `,` and every item is trimmed.
+/// Items can be quoted to include a comma, like `tags: a, b, "c, d"`. Empty
+/// items are skipped. Get the items with [`Metadata::list`]. Set it in
+/// [`ParseOptions::list_keys`](crate::ParseOptions::list_keys).
+///
+/// By default `tags`, `tag` and `category` are lists.
+///
+/// ```
+/// # use cooklang::{metadata::ListKeys, CooklangParser, ParseOptions};
+/// let mut list_keys = ListKeys::default();
+/// list_keys.add("diet");
+///
+/// let options = ParseOptions {
+///     list_keys,
+///     ..Default::default()
+/// };
+/// let input = ">> diet: vegan, gluten free\n";
+/// let recipe = CooklangParser::default()
+///     .parse_with_options(input, options)
+///     .unwrap_output();
+/// assert_eq!(
+///     recipe.metadata.list("diet").unwrap(),
+///     ["vegan", "gluten free"]
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct ListKeys {
+    keys: Vec<String>,
+}
+
+impl ListKeys {
+    /// Creates an empty set, no key is a list
+    pub fn empty() -> Self {
+        Self { keys: Vec::new() }
+    }
+
+    /// Add a key whose value is a list
+    pub fn add(&mut self, key: impl Into<String>) -> &mut Self {
+        let key = key.into();
+        if !self.contains(&key) {
+            self.keys.push(key);
+        }
+        self
+    }
+
+    /// Check if the values of a key are a list
+    pub fn contains(&self, key: &str) -> bool {
+        self.keys.iter().any(|k| k == key)
+    }
+}
+
+impl Default for ListKeys {
+    fn default() -> Self {
+        Self {
+            keys: vec!["tags".into(), "tag".into(), "category".into()],
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -79,6 +232,8 @@ enum SpecialValue {
     Time(RecipeTime),
     Servings(Vec<u32>),
     String(String),
+    Number(f64),
+    Quantity(ScaledQuantity),
 }
 
 macro_rules! unwrap_value {
@@ -96,6 +251,28 @@ macro_rules! unwrap_value {
 }
 
 impl Metadata {
+    /// Location of the key of an entry in the source
+    ///
+    /// The span doesn't include surrounding whitespace.
+    pub fn key_span(&self, key: &str) -> Option<Span> {
+        self.spans.get(key).map(|(k, _)| *k)
+    }
+
+    /// Location of the value of an entry in the source
+    ///
+    /// The span doesn't include surrounding whitespace.
+    pub fn value_span(&self, key: &str) -> Option<Span> {
+        self.spans.get(key).map(|(_, v)| *v)
+    }
+
+    /// Items of an entry configured as a list
+    ///
+    /// Returns [`None`] if the key is missing or is not configured as a list
+    /// in [`ListKeys`].
+    pub fn list(&self, key: &str) -> Option<Vec<String>> {
+        self.lists.get(key).cloned()
+    }
+
     /// Description of the recipe
     pub fn description(&self) -> Option<&str> {
         self.map
@@ -148,6 +325,40 @@ impl Metadata {
             .get(&SpecialKey::Servings)
             .map(|v| unwrap_value!(Servings, v).as_slice())
     }
+
+    /// Scaling factor already applied to the recipe quantities
+    ///
+    /// Only [linear](crate::quantity::ScalableValue::Linear) values are
+    /// affected by it.
+    pub fn scale(&self) -> Option<f64> {
+        self.special
+            .get(&SpecialKey::Scale)
+            .map(|v| *unwrap_value!(Number, v))
+    }
+
+    /// Denominator of the fractions in this recipe
+    ///
+    /// Useful when the recipe is written for a specific set of measuring
+    /// cups. When parsing, the values in units with fractions enabled are
+    /// rounded to the closest multiple of `1/denominator`, like `0.4 cup`
+    /// to `3/8 cup` with `8`. The same is done after scaling.
+    pub fn fraction_denominator(&self) -> Option<u8> {
+        self.special
+            .get(&SpecialKey::FractionDenominator)
+            .map(|v| *unwrap_value!(Number, v) as u8)
+    }
+
+    /// What the recipe makes, like `24 cookies` or `1 loaf`
+    ///
+    /// This is different from [`Self::servings`], a recipe that yields 24
+    /// cookies may serve 6. The value is a number followed by an optional
+    /// unit. A unit unknown to the converter, like `cookies`, is kept as
+    /// text. Free text values, like `a dozen`, are [`None`].
+    pub fn yield_(&self) -> Option<&ScaledQuantity> {
+        self.special
+            .get(&SpecialKey::Yield)
+            .map(|v| unwrap_value!(Quantity, v))
+    }
 }
 
 /// Combination of name and URL.
@@ -197,12 +408,7 @@ impl Metadata {
                 } else {
                     Vec::new()
                 };
-                for tag in value.split(',') {
-                    let tag = tag.trim().to_string();
-                    // no empty
-                    if tag.is_empty() {
-                        continue;
-                    }
+                for tag in parse_list(&value) {
                     // no duplicates
                     if tags.contains(&tag) {
                         continue;
@@ -277,22 +483,61 @@ impl Metadata {
                 self.special
                     .insert(SpecialKey::Servings, SpecialValue::Servings(servings));
             }
+            SpecialKey::Scale => {
+                let scale = value.parse::<f64>()?;
+                if !scale.is_finite() || scale <= 0.0 {
+                    return Err(MetadataError::InvalidScale { scale });
+                }
+                self.special.insert(key, SpecialValue::Number(scale));
+            }
+            SpecialKey::FractionDenominator => {
+                let den = value.parse::<u8>()?;
+                if !(1..=64).contains(&den) {
+                    return Err(MetadataError::InvalidFractionDenominator { den });
+                }
+                self.special.insert(key, SpecialValue::Number(den as f64));
+            }
+            SpecialKey::Yield => {
+                // free text is valid, it just has no typed value
+                match parse_yield(&value, converter) {
+                    Some(quantity) => self.special.insert(key, SpecialValue::Quantity(quantity)),
+                    None => self.special.remove(&key),
+                };
+            }
         }
         Ok(())
     }
 
+    pub(crate) fn insert_list(&mut self, key: &str, list_keys: &ListKeys) {
+        if !list_keys.contains(key) {
+            return;
+        }
+        if let Some(value) = self.map.get(key) {
+            self.lists.insert(key.to_string(), parse_list(value));
+        }
+    }
+
     /// Parse the inner [map](Self::map) updating the special keys
     ///
     /// This can be useful if you edit the inner values of the metadata map and
-    /// want the special keys to refresh.
+    /// want the special keys to refresh. The [lists](Self::list) are also
+    /// refreshed with `list_keys`, use the same ones given in
+    /// [`ParseOptions::list_keys`](crate::ParseOptions::list_keys) to keep
+    /// them.
     ///
     /// The error variant of the result contains the key value pairs that had
     /// an error parsing. Even if [`Err`] is returned, some values may have been
     /// updated.
-    pub fn parse_special(&mut self, converter: &Converter) -> Result<(), Vec<(String, String)>> {
+    pub fn parse_special(
+        &mut self,
+        converter: &Converter,
+        list_keys: &ListKeys,
+    ) -> Result<(), Vec<(String, String)>> {
         let mut new = Self::default();
         let mut errors = Vec::new();
         for (key, val) in &self.map {
+            new.map.insert(key.clone(), val.clone());
+            new.insert_list(key, list_keys);
             if let Ok(sp_key) = SpecialKey::from_str(key) {
                 if new.insert_special(sp_key, val.clone(), converter).is_err() {
                     errors.push((key.clone(), val.clone()));
@@ -300,6 +545,7 @@ impl Metadata {
             }
         }
         self.special = new.special;
+        self.lists = new.lists;
         if errors.is_empty() {
             Ok(())
         } else {
@@ -317,8 +563,44 @@ impl Metadata {
     }
 }
 
+/// Split a list value on `,` outside of quotes
+fn parse_list(s: &str) -> Vec<String> {
+    let mut items = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    for c in s.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            ',' if !quoted => items.push(std::mem::take(&mut current)),
+            _ => current.push(c),
+        }
+    }
+    items.push(current);
+    items
+        .into_iter()
+        .map(|item| item.trim().to_string())
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+fn parse_yield(s: &str, converter: &Converter) -> Option<ScaledQuantity> {
+    let re = regex!(r"^(\d+(?:\.\d+)?)(?:\s*/\s*(\d+))?\s*(.*)$");
+    let captures = re.captures(s.trim())?;
+    let mut value = captures[1].parse::<f64>().ok()?;
+    if let Some(den) = captures.get(2) {
+        let den = den.as_str().parse::<f64>().ok()?;
+        if den == 0.0 {
+            return None;
+        }
+        value /= den;
+    }
+    let unit = captures[3].trim();
+    let unit = (!unit.is_empty()).then(|| unit.to_string());
+    Some(Quantity::new_and_parse(Value::from(value), unit, converter))
+}
+
 /// Returns minutes
-fn parse_time(s: &str, converter: &Converter) -> Result<u32, ParseTimeError> {
+pub(crate) fn parse_time(s: &str, converter: &Converter) -> Result<u32, ParseTimeError> {
     if s.is_empty() {
         return Err(ParseTimeError::Empty);
     }
@@ -485,11 +767,18 @@ pub(crate) enum MetadataError {
     DuplicateServings { servings: Vec<u32> },
     #[error(transparent)]
     ParseTimeError(#[from] ParseTimeError),
+    #[error(transparent)]
+    ParseFloatError(#[from] ParseFloatError),
+    #[error("Scale has to be a positive number: {scale}")]
+    InvalidScale { scale: f64 },
+    #[error("Fraction denominator has to be between 1 and 64: {den}")]
+    InvalidFractionDenominator { den: u8 },
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::quantity::UnitInfo;
 
     #[test]
     fn test_parse_time_with_units() {
@@ -535,6 +824,10 @@ mod tests {
         t("cook time", SpecialKey::CookTime);
         t_alias("cook_time", SpecialKey::CookTime);
         t("servings", SpecialKey::Servings);
+        t("scale", SpecialKey::Scale);
+        t("fraction denominator", SpecialKey::FractionDenominator);
+        t_alias("fraction_denominator", SpecialKey::FractionDenominator);
+        t("yield", SpecialKey::Yield);
     }
 
     macro_rules! insert {
@@ -578,6 +871,101 @@ mod tests {
 
         let _ = insert!(m, converter, SpecialKey::Servings, "3|4");
         assert!(matches!(m.servings(), Some(_)));
+
+        let _ = insert!(m, converter, SpecialKey::Scale, "1.5");
+        assert!(m.scale().is_some());
+
+        let _ = insert!(m, converter, SpecialKey::FractionDenominator, "8");
+        assert!(m.fraction_denominator().is_some());
+
+        let _ = insert!(m, converter, SpecialKey::Yield, "2 loaves");
+        assert!(m.yield_().is_some());
+    }
+
+    #[test]
+    fn recipe_yield() {
+        let converter = Converter::bundled();
+        let mut m = Metadata::default();
+
+        assert!(insert!(m, converter, SpecialKey::Yield, "24 cookies").is_ok());
+        let q = m.yield_().unwrap();
+        assert_eq!(q.value, Value::from(24.0));
+        assert_eq!(q.unit_text(), Some("cookies"));
+        assert!(matches!(
+            q.unit().unwrap().unit_info(),
+            Some(UnitInfo::Unknown)
+        ));
+
+        assert!(insert!(m, converter, SpecialKey::Yield, "1 loaf").is_ok());
+        let q = m.yield_().unwrap();
+        assert_eq!(q.value, Value::from(1.0));
+        assert_eq!(q.unit_text(), Some("loaf"));
+
+        assert!(insert!(m, converter, SpecialKey::Yield, "1/2 l").is_ok());
+        let q = m.yield_().unwrap();
+        assert_eq!(q.value, Value::from(0.5));
+        let Some(UnitInfo::Known(unit)) = q.unit().unwrap().unit_info() else {
+            panic!("unit not known")
+        };
+        assert_eq!(unit.symbol(), "l");
+
+        assert!(insert!(m, converter, SpecialKey::Yield, "12").is_ok());
+        assert_eq!(m.yield_().unwrap().unit(), None);
+
+        // free text is not an error, but has no value
+        assert!(insert!(m, converter, SpecialKey::Yield, "a dozen").is_ok());
+        assert!(m.yield_().is_none());
+    }
+
+    #[test]
+    fn list_values() {
+        assert_eq!(parse_list(r#"a, b, "c, d""#), ["a", "b", "c, d"]);
+        assert_eq!(parse_list(" a ,, b,"), ["a", "b"]);
+        assert_eq!(parse_list(r#""unclosed, quote"#), ["unclosed, quote"]);
+        assert!(parse_list("").is_empty());
+
+        let mut m = Metadata::default();
+        m.map.insert("tags".into(), r#"a, b, "c, d""#.into());
+        m.map.insert("diet".into(), "vegan, raw".into());
+        let list_keys = ListKeys::default();
+        m.insert_list("tags", &list_keys);
+        m.insert_list("diet", &list_keys);
+        assert_eq!(m.list("tags").unwrap(), ["a", "b", "c, d"]);
+        assert_eq!(m.list("diet"), None);
+        assert_eq!(m.list("missing"), None);
+
+        let mut list_keys = ListKeys::empty();
+        list_keys.add("diet");
+        assert!(m.parse_special(&Converter::empty(), &list_keys).is_ok());
+        assert_eq!(m.list("diet").unwrap(), ["vegan", "raw"]);
+        assert_eq!(m.list("tags"), None);
+        assert_eq!(m.tags().unwrap(), ["a", "b", "c, d"]);
+    }
+
+    #[test]
+    fn invalid_scale() {
+        let converter = Converter::empty();
+        let mut m = Metadata::default();
+        assert!(insert!(m, converter, SpecialKey::Scale, "two").is_err());
+        assert!(insert!(m, converter, SpecialKey::Scale, "-1").is_err());
+        assert!(insert!(m, converter, SpecialKey::Scale, "0").is_err());
+        assert!(m.scale().is_none());
+        assert!(insert!(m, converter, SpecialKey::Scale, "2").is_ok());
+        assert_eq!(m.scale(), Some(2.0));
+    }
+
+    #[test]
+    fn invalid_fraction_denominator() {
+        let converter = Converter::empty();
+        let mut m = Metadata::default();
+        let den = SpecialKey::FractionDenominator;
+        assert!(m.insert_special(den, "eight".into(), &converter).is_err());
+        assert!(m.insert_special(den, "0".into(), &converter).is_err());
+        assert!(m.insert_special(den, "65".into(), &converter).is_err());
+        assert!(m.insert_special(den, "-8".into(), &converter).is_err());
+        assert!(m.fraction_denominator().is_none());
+        assert!(m.insert_special(den, "8".into(), &converter).is_ok());
+        assert_eq!(m.fraction_denominator(), Some(8));
     }
 
     #[test]
@@ -9,6 +9,7 @@ use crate::{
     metadata::Metadata,
     parser::Modifiers,
     quantity::{GroupedValue, Quantity, QuantityValue, ScalableValue, ScaledQuantity},
+    span::Span,
     GroupedQuantity, Value,
 };
 
@@ -39,7 +40,15 @@ pub struct Recipe<D, V: QuantityValue> {
     /// All the timers
     pub timers: Vec<Timer<V>>,
     /// All the inline quantities
-    pub inline_quantities: Vec<ScaledQuantity>,
+    ///
+    /// These are found in the text of the steps, like temperatures. Temperatures
+    /// are always [fixed](ScalableValue::Fixed), scaling them makes no sense.
+    /// The ones written with [`INLINE_QUANTITIES`](crate::Extensions::INLINE_QUANTITIES),
+    /// like `@{2%cups}`, are [linear](ScalableValue::Linear) unless they are
+    /// text or have many values.
+    pub inline_quantities: Vec<Quantity<V>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) unresolved: Vec<Unresolved>,
     pub(crate) data: D,
 }
 
@@ -55,6 +64,193 @@ pub type ScalableRecipe = Recipe<(), ScalableValue>;
 /// scaled once.
 pub type ScaledRecipe = Recipe<crate::scale::Scaled, Value>;
 
+impl<D, V: QuantityValue> Recipe<D, V> {
+    /// References whose target was not found while parsing
+    ///
+    /// These are `&` references to ingredients or cookware not defined
+    /// before and, if [`ParseOptions::recipe_ref_check`](crate::ParseOptions::recipe_ref_check)
+    /// is set, recipe references it did not accept. Each one is also reported
+    /// as a diagnostic. Empty for a self-contained recipe.
+    ///
+    /// ```
+    /// let recipe = cooklang::parse("Add @salt and @&pepper{}.").into_output().unwrap();
+    /// let unresolved = recipe.unresolved();
+    /// assert_eq!(unresolved.len(), 1);
+    /// assert_eq!(unresolved[0].name, "pepper");
+    /// ```
+    pub fn unresolved(&self) -> &[Unresolved] {
+        &self.unresolved
+    }
+}
+
+impl<D: Clone, V: QuantityValue> Recipe<D, V> {
+    /// Rename an ingredient, returning a new recipe and how many ingredients
+    /// were renamed
+    ///
+    /// The definitions and all their references share the name, so all of
+    /// them are renamed, even a reference written with another case, like
+    /// `@&Scallion`. Quantities, notes and modifiers are kept. Aliases are
+    /// not changed.
+    ///
+    /// With `ignore_case`, `from` is compared without case.
+    ///
+    /// ```
+    /// let recipe = cooklang::parse("Chop @scallion{2} and add @&Scallion{1}.").unwrap_output();
+    /// let (renamed, count) = recipe.rename_ingredient("scallion", "green onion", false);
+    /// assert_eq!(count, 2);
+    /// assert!(renamed.ingredients.iter().all(|i| i.name == "green onion"));
+    /// ```
+    pub fn rename_ingredient(&self, from: &str, to: &str, ignore_case: bool) -> (Self, usize) {
+        let from_ci = unicase::UniCase::new(from);
+        let matches = |name: &str| {
+            if ignore_case {
+                unicase::UniCase::new(name) == from_ci
+            } else {
+                name == from
+            }
+        };
+        // a regular reference is renamed with its definition
+        let renamed = self
+            .ingredients
+            .iter()
+            .map(|igr| match igr.relation.references_to() {
+                Some((index, IngredientReferenceTarget::Ingredient)) => {
+                    matches(&self.ingredients[index].name)
+                }
+                _ => matches(&igr.name),
+            })
+            .collect::<Vec<_>>();
+
+        let mut recipe = self.clone();
+        let mut count = 0;
+        for (igr, renamed) in recipe.ingredients.iter_mut().zip(renamed) {
+            if renamed {
+                igr.name = to.to_string();
+                count += 1;
+            }
+        }
+        (recipe, count)
+    }
+
+    /// Description of the recipe, from the `description` metadata entry
+    ///
+    /// With [`ParseOptions::leading_description`](crate::ParseOptions::leading_description)
+    /// it can also be the text before the first step.
+    pub fn description(&self) -> Option<&str> {
+        self.metadata.description()
+    }
+
+    /// Preparation notes of the ingredients, like `@onion(diced)`
+    ///
+    /// Returns the name and note of every ingredient with a note, in the
+    /// order they first appear. Useful for a mise en place summary.
+    pub fn preparations(&self) -> Vec<(&str, &str)> {
+        self.ingredients
+            .iter()
+            .filter_map(|igr| Some((igr.name.as_str(), igr.note.as_deref()?)))
+            .collect()
+    }
+
+    /// Text of the recipe without any markup
+    ///
+    /// Components are replaced with their names and, `with_quantities`, their
+    /// quantities before the name. Timers without name are always replaced
+    /// with their duration. Each section starts with its name, if any,
+    /// and each step or text paragraph is in its own line. Useful for full
+    /// text search, it's not meant to be parsed again.
+    ///
+    /// ```
+    /// let recipe = cooklang::parse("Mix @flour{200%g} and @water{}.").unwrap_output();
+    /// assert_eq!(recipe.plain_text(false), "Mix flour and water.");
+    /// assert_eq!(recipe.plain_text(true), "Mix 200 g flour and water.");
+    /// ```
+    pub fn plain_text(&self, with_quantities: bool) -> String {
+        let mut lines = Vec::new();
+        for section in &self.sections {
+            if let Some(name) = &section.name {
+                lines.push(name.clone());
+            }
+            for content in &section.content {
+                match content {
+                    Content::Step(step) => lines.push(self.step_text(step, with_quantities)),
+                    Content::Text(text) => lines.push(text.clone()),
+                }
+            }
+        }
+        lines.join("\n")
+    }
+
+    /// Text of a step without any markup, see [`Recipe::plain_text`]
+    pub(crate) fn step_text(&self, step: &Step, with_quantities: bool) -> String {
+        use std::fmt::Write;
+
+        let component = |line: &mut String, quantity: Option<&Quantity<V>>, name: Option<&str>| {
+            let quantity = quantity.filter(|_| with_quantities);
+            match (quantity, name) {
+                (Some(q), Some(name)) => write!(line, "{q} {name}"),
+                (Some(q), None) => write!(line, "{q}"),
+                (None, Some(name)) => write!(line, "{name}"),
+                (None, None) => Ok(()),
+            }
+            .unwrap()
+        };
+
+        let mut line = String::new();
+        for item in &step.items {
+            match *item {
+                Item::Text { ref value } => line += value,
+                Item::Ingredient { index } => {
+                    let igr = &self.ingredients[index];
+                    component(&mut line, igr.quantity.as_ref(), Some(&igr.display_name()))
+                }
+                Item::Cookware { index } => {
+                    let cw = &self.cookware[index];
+                    let quantity = cw
+                        .quantity
+                        .clone()
+                        .map(|v| Quantity::new(v, cw.unit.clone()));
+                    component(&mut line, quantity.as_ref(), Some(cw.display_name()))
+                }
+                Item::Timer { index } => {
+                    let timer = &self.timers[index];
+                    match (&timer.quantity, &timer.name) {
+                        // the duration is all there is of an anonymous timer
+                        (Some(q), None) => write!(line, "{q}").unwrap(),
+                        (q, name) => component(&mut line, q.as_ref(), name.as_deref()),
+                    }
+                }
+                Item::InlineQuantity { index } => {
+                    write!(line, "{}", self.inline_quantities[index]).unwrap()
+                }
+            }
+        }
+        line
+    }
+}
+
+/// A reference whose target was not found, see [`Recipe::unresolved`]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct Unresolved {
+    /// What was referenced
+    pub kind: UnresolvedKind,
+    /// Name that was looked up
+    pub name: String,
+    /// Location of the reference in the input
+    pub span: Span,
+}
+
+/// Kind of an [`Unresolved`] reference
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone, Copy, Hash)]
+#[serde(rename_all = "camelCase")]
+pub enum UnresolvedKind {
+    /// An ingredient reference, `@&name`
+    Ingredient,
+    /// A cookware reference, `#&name`
+    Cookware,
+    /// A recipe reference, `@@name`
+    Recipe,
+}
+
 /// A section holding steps
 #[derive(Debug, Default, Serialize, Deserialize, PartialEq, Clone)]
 pub struct Section {
@@ -64,6 +260,38 @@ pub struct Section {
     pub content: Vec<Content>,
 }
 
+/// Binary serialization
+///
+/// The format is [CBOR](https://cbor.io), which is compact and keeps every
+/// field of the model, so a round trip is lossless.
+///
+/// This is only available with the `binary` [feature](crate::_features).
+#[cfg(feature = "binary")]
+impl<D, V> Recipe<D, V>
+where
+    D: Serialize + serde::de::DeserializeOwned,
+    V: QuantityValue + Serialize + serde::de::DeserializeOwned,
+{
+    /// Serialize the recipe to bytes
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        // writing to a vec only fails if the model can't be serialized
+        ciborium::ser::into_writer(self, &mut bytes).expect("recipe serialization failed");
+        bytes
+    }
+
+    /// Deserialize a recipe from bytes created with [`Recipe::to_bytes`]
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, FromBytesError> {
+        ciborium::de::from_reader(bytes).map_err(FromBytesError)
+    }
+}
+
+/// Error from [`Recipe::from_bytes`]
+#[cfg(feature = "binary")]
+#[derive(Debug, thiserror::Error)]
+#[error("Invalid recipe bytes: {0}")]
+pub struct FromBytesError(ciborium::de::Error<std::io::Error>);
+
 impl Section {
     pub(crate) fn new(name: Option<String>) -> Section {
         Self {
@@ -78,6 +306,37 @@ impl Section {
     pub fn is_empty(&self) -> bool {
         self.name.is_none() && self.content.is_empty()
     }
+
+    /// Get the text introducing the section, if any
+    ///
+    /// This is the first content of the section when it's only text, a
+    /// [`Content::Text`] or a step without components. Note that a step like
+    /// `Let it rest.` is also only text, so it's up to you how to present it.
+    ///
+    /// The returned text is trimmed.
+    pub fn note(&self) -> Option<Cow<'_, str>> {
+        let note = match self.content.first()? {
+            Content::Text(text) => Cow::from(text.trim()),
+            Content::Step(step) => {
+                let mut note = Cow::Borrowed("");
+                for item in &step.items {
+                    let Item::Text { value } = item else {
+                        return None;
+                    };
+                    if note.is_empty() {
+                        note = Cow::from(value);
+                    } else {
+                        note.to_mut().push_str(value);
+                    }
+                }
+                match note {
+                    Cow::Borrowed(s) => Cow::Borrowed(s.trim()),
+                    Cow::Owned(s) => Cow::Owned(s.trim().to_string()),
+                }
+            }
+        };
+        (!note.is_empty()).then_some(note)
+    }
 }
 
 /// Each type of content inside a section
@@ -125,7 +384,7 @@ impl Content {
 }
 
 /// A step holding step [`Item`]s
-#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 #[non_exhaustive]
 pub struct Step {
     /// [`Item`]s inside
@@ -136,6 +395,69 @@ pub struct Step {
     /// The step numbers start at 1 in each section and increase with non
     /// text step.
     pub number: u32,
+
+    /// Number of [`Item::Ingredient`] in the step
+    #[serde(default)]
+    pub ingredient_count: usize,
+    /// Number of [`Item::Cookware`] in the step
+    #[serde(default)]
+    pub cookware_count: usize,
+    /// Number of [`Item::Timer`] in the step
+    #[serde(default)]
+    pub timer_count: usize,
+
+    /// Paths of the media attached to the step
+    ///
+    /// Only with [`Extensions::STEP_MEDIA`](crate::Extensions::STEP_MEDIA),
+    /// like `![plating.jpg]`.
+    #[serde(default)]
+    pub media: Vec<String>,
+
+    #[serde(skip)]
+    pub(crate) spans: Vec<Span>,
+}
+
+impl PartialEq for Step {
+    fn eq(&self, other: &Self) -> bool {
+        self.items == other.items
+            && self.number == other.number
+            && self.ingredient_count == other.ingredient_count
+            && self.cookware_count == other.cookware_count
+            && self.timer_count == other.timer_count
+            && self.media == other.media
+    }
+}
+
+impl Step {
+    pub(crate) fn new(items: Vec<Item>, number: u32) -> Self {
+        let count = |f: fn(&Item) -> bool| items.iter().filter(|i| f(i)).count();
+        Self {
+            ingredient_count: count(|i| matches!(i, Item::Ingredient { .. })),
+            cookware_count: count(|i| matches!(i, Item::Cookware { .. })),
+            timer_count: count(|i| matches!(i, Item::Timer { .. })),
+            media: Vec::new(),
+            spans: Vec::new(),
+            items,
+            number,
+        }
+    }
+
+    /// Location in the input of the item at `index` in [`Self::items`]
+    ///
+    /// Only for parsed recipes, [`None`] if the step was built with a
+    /// [`RecipeBuilder`](crate::builder::RecipeBuilder). The locations are not
+    /// serialized and not compared.
+    ///
+    /// ```
+    /// let input = "Add @salt.";
+    /// let recipe = cooklang::parse(input).unwrap_output();
+    /// let step = recipe.sections[0].content[0].unwrap_step();
+    /// let span = step.item_span(1).unwrap();
+    /// assert_eq!(&input[span.range()], "@salt");
+    /// ```
+    pub fn item_span(&self, index: usize) -> Option<Span> {
+        self.spans.get(index).copied()
+    }
 }
 
 /// A step item
@@ -178,6 +500,12 @@ pub struct Ingredient<V: QuantityValue = Value> {
     pub note: Option<String>,
     /// How the cookware is related to others
     pub relation: IngredientRelation,
+    /// Where it was written in the recipe
+    #[serde(default)]
+    pub origin: ComponentOrigin,
+    /// Is a pantry staple, see [`ParseOptions::pantry_check`](crate::ParseOptions::pantry_check)
+    #[serde(default)]
+    pub pantry: bool,
     pub(crate) modifiers: Modifiers,
 }
 
@@ -274,12 +602,26 @@ pub struct Cookware<V: QuantityValue = Value> {
     pub alias: Option<String>,
     /// Amount needed
     ///
-    /// Note that this is a value, not a quantity, so it doesn't have units.
+    /// Note that this is a value, not a quantity, the unit is in
+    /// [`Self::unit`].
+    ///
+    /// It does not scale unless it has the auto scale marker, like
+    /// `#loaf tin{1*}`, or a value for each serving size.
     pub quantity: Option<V>,
+    /// Unit of the quantity, like `inch` in `#baking dish{9%inch}`
+    ///
+    /// Only with [`Extensions::COOKWARE_UNITS`](crate::Extensions::COOKWARE_UNITS).
+    /// See [`Self::quantity_kind`]. It's converted with the quantity in
+    /// [`ScaledRecipe::to_system`](crate::ScaledRecipe::to_system).
+    #[serde(default)]
+    pub unit: Option<String>,
     /// Note
     pub note: Option<String>,
     /// How the cookware is related to others
     pub relation: ComponentRelation,
+    /// Where it was written in the recipe
+    #[serde(default)]
+    pub origin: ComponentOrigin,
     pub(crate) modifiers: Modifiers,
 }
 
@@ -293,13 +635,49 @@ impl<V: QuantityValue> Cookware<V> {
     pub fn modifiers(&self) -> Modifiers {
         self.modifiers
     }
+
+    /// What the quantity means, [`None`] if there is no quantity
+    ///
+    /// ```
+    /// # use cooklang::{CooklangParser, CookwareQuantityKind};
+    /// let parser = CooklangParser::extended();
+    /// let recipe = parser
+    ///     .parse("Thread it in #skewers{12} and put them in a #baking dish{9%inch}.")
+    ///     .unwrap_output();
+    /// let kinds = recipe.cookware.iter().map(|cw| cw.quantity_kind()).collect::<Vec<_>>();
+    /// assert_eq!(
+    ///     kinds,
+    ///     [
+    ///         Some(CookwareQuantityKind::Count),
+    ///         Some(CookwareQuantityKind::Descriptor)
+    ///     ]
+    /// );
+    /// ```
+    pub fn quantity_kind(&self) -> Option<CookwareQuantityKind> {
+        self.quantity.as_ref().map(|_| match self.unit {
+            Some(_) => CookwareQuantityKind::Descriptor,
+            None => CookwareQuantityKind::Count,
+        })
+    }
+}
+
+/// Meaning of a cookware quantity, see [`Cookware::quantity_kind`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CookwareQuantityKind {
+    /// How many items are needed, like `#skewers{12}`
+    Count,
+    /// A description of the item with a unit, like `#baking dish{9%inch}`
+    ///
+    /// It's not an amount, so it's not added in [`Cookware::group_amounts`].
+    Descriptor,
 }
 
 impl Cookware<Value> {
     /// Groups all the amounts of itself and it's references
     ///
     /// The first element is a grouped numeric value (if any), the rest are text
-    /// values.
+    /// values. Only [counts](CookwareQuantityKind::Count) are grouped.
     ///
     /// ```
     /// # use cooklang::{CooklangParser, Extensions, Converter, Value, Quantity};
@@ -325,8 +703,12 @@ impl Cookware<Value> {
     /// ```
     pub fn group_amounts(&self, all_cookware: &[Self]) -> GroupedValue {
         let mut g = GroupedValue::empty();
-        for a in self.all_amounts(all_cookware) {
-            g.add(a);
+        let references = self.relation.referenced_from().iter();
+        for cw in std::iter::once(self).chain(references.map(|&i| &all_cookware[i])) {
+            if let (Some(CookwareQuantityKind::Count), Some(a)) = (cw.quantity_kind(), &cw.quantity)
+            {
+                g.add(a);
+            }
         }
         g
     }
@@ -538,4 +920,22 @@ pub struct Timer<V: QuantityValue = Value> {
     /// - If the [`TIMER_REQUIRES_TIME`](crate::Extensions::TIMER_REQUIRES_TIME)
     /// extension is enabled, this is guaranteed to be [`Some`].
     pub quantity: Option<Quantity<V>>,
+    /// Where it was written in the recipe
+    #[serde(default)]
+    pub origin: ComponentOrigin,
+}
+
+/// Location of a component in the recipe content
+///
+/// `recipe.sections[origin.section].content[origin.step]` is the step where
+/// the component was written.
+///
+/// In components mode steps are not added to the sections,
+/// so the indices point to where the step would have been.
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq, Eq, Clone, Copy, Hash)]
+pub struct ComponentOrigin {
+    /// Index of the section in [`Recipe::sections`]
+    pub section: usize,
+    /// Index of the content in [`Section::content`]
+    pub step: usize,
 }
@@ -0,0 +1,195 @@
+//! Extra markers for components and comments
+
+use crate::lexer::{is_word_char, TokenKind, T};
+
+/// Kind of a component, see [`ComponentMarkers`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ComponentKind {
+    Ingredient,
+    Cookware,
+    Timer,
+}
+
+impl ComponentKind {
+    fn token(self) -> TokenKind {
+        match self {
+            ComponentKind::Ingredient => T![@],
+            ComponentKind::Cookware => T![#],
+            ComponentKind::Timer => T![~],
+        }
+    }
+}
+
+/// Characters that are already part of the cooklang syntax
+///
+/// `!` is the decimal marker and starts step media, and `,` separates list
+/// metadata values.
+const RESERVED: &str = r">:@#~?+-/*&|=%{}().\[]!,";
+
+/// Additional characters that start a component
+///
+/// Some dialects use other markers apart from `@`, `#` and `~`. Each marker
+/// is mapped to one of the existing [`ComponentKind`] and after that it
+/// behaves exactly the same as the default one. The default markers are
+/// always available.
+///
+/// A marker has to be a single ASCII punctuation or symbol character that is
+/// not already used by the syntax, extensions included. That leaves `"`, `$`,
+/// `'`, `;`, `<`, `^`, `_` and `` ` ``. Once registered, the char can't be used as a
+/// regular char in a step without escaping it with `\`.
+///
+/// ```
+/// # use cooklang::parser::{ComponentKind, ComponentMarkers, Event, PullParser};
+/// # use cooklang::Extensions;
+/// let mut markers = ComponentMarkers::new();
+/// markers.add('$', ComponentKind::Cookware).unwrap();
+///
+/// let events = PullParser::with_markers("Use a $pan.", Extensions::all(), &markers);
+/// let cookware = events
+///     .filter_map(|ev| match ev {
+///         Event::Cookware(c) => Some(c.name.text().into_owned()),
+///         _ => None,
+///     })
+///     .collect::<Vec<_>>();
+/// assert_eq!(cookware, ["pan"]);
+/// ```
+///
+/// The line comment prefix can also be changed from `--` with
+/// [`ComponentMarkers::set_comment_prefix`], and components can be disabled
+/// altogether with [`ComponentMarkers::set_components_disabled`].
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ComponentMarkers {
+    markers: Vec<(char, ComponentKind)>,
+    comment: Option<String>,
+    components_disabled: bool,
+}
+
+/// Error when registering a char that can't be a component marker
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+#[error("'{0}' can't be used as a component marker")]
+pub struct InvalidMarker(pub char);
+
+/// Error when setting a line comment prefix that collides with the syntax
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+#[error("'{0}' can't be used as a comment prefix")]
+pub struct InvalidCommentPrefix(pub String);
+
+impl ComponentMarkers {
+    /// Creates an empty set, only the default markers
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a new marker
+    ///
+    /// If the marker was already registered, its kind is replaced.
+    pub fn add(&mut self, marker: char, kind: ComponentKind) -> Result<&mut Self, InvalidMarker> {
+        if !marker.is_ascii_punctuation()
+            || RESERVED.contains(marker)
+            || self.comment.as_ref().is_some_and(|c| c.contains(marker))
+        {
+            return Err(InvalidMarker(marker));
+        }
+        match self.markers.iter_mut().find(|(c, _)| *c == marker) {
+            Some(entry) => entry.1 = kind,
+            None => self.markers.push((marker, kind)),
+        }
+        Ok(self)
+    }
+
+    /// Use another prefix for line comments instead of `--`
+    ///
+    /// Like `;;` for users coming from other formats. Once set, `--` is no
+    /// longer a comment. The prefix must start with an ASCII punctuation
+    /// character that is not already part of the syntax, so `//` is not
+    /// valid because it would cut URLs. It also can't have whitespace and
+    /// can't contain any component marker, default or registered.
+    ///
+    /// ```
+    /// # use cooklang::parser::ComponentMarkers;
+    /// let mut markers = ComponentMarkers::new();
+    /// markers.set_comment_prefix(";;").unwrap();
+    /// assert_eq!(markers.comment_prefix(), ";;");
+    /// assert!(markers.set_comment_prefix("#").is_err());
+    /// assert!(markers.set_comment_prefix("//").is_err());
+    /// ```
+    pub fn set_comment_prefix(&mut self, prefix: &str) -> Result<&mut Self, InvalidCommentPrefix> {
+        let valid_start = prefix == "--"
+            || prefix.chars().next().is_some_and(|c| {
+                c.is_ascii_punctuation() && !is_word_char(c) && c != '\\' && !RESERVED.contains(c)
+            });
+        let collides = prefix
+            .chars()
+            .any(|c| c.is_whitespace() || "@#~".contains(c) || self.get(c).is_some());
+        if !valid_start || collides {
+            return Err(InvalidCommentPrefix(prefix.to_string()));
+        }
+        self.comment = (prefix != "--").then(|| prefix.to_string());
+        Ok(self)
+    }
+
+    /// Get the line comment prefix
+    pub fn comment_prefix(&self) -> &str {
+        self.comment.as_deref().unwrap_or("--")
+    }
+
+    /// Treat all component markers as regular chars (plain-text mode)
+    ///
+    /// No ingredients, cookware or timers are parsed and steps are only text,
+    /// including the markers and the braces. Useful to show a recipe as prose
+    /// where the markup would be noise. Metadata, sections and comments are
+    /// parsed as usual.
+    ///
+    /// ```
+    /// # use cooklang::parser::{ComponentMarkers, Event, PullParser};
+    /// # use cooklang::Extensions;
+    /// let mut markers = ComponentMarkers::new();
+    /// markers.set_components_disabled(true);
+    ///
+    /// let text = PullParser::with_markers("Add @salt{1%tsp}.", Extensions::all(), &markers)
+    ///     .filter_map(|ev| match ev {
+    ///         Event::Text(t) => Some(t.text().into_owned()),
+    ///         Event::Ingredient(_) => panic!("no components"),
+    ///         _ => None,
+    ///     })
+    ///     .collect::<String>();
+    /// assert_eq!(text, "Add @salt{1%tsp}.");
+    /// ```
+    pub fn set_components_disabled(&mut self, disabled: bool) -> &mut Self {
+        self.components_disabled = disabled;
+        self
+    }
+
+    /// Check if components are disabled, see
+    /// [`ComponentMarkers::set_components_disabled`]
+    pub fn components_disabled(&self) -> bool {
+        self.components_disabled
+    }
+
+    /// Get the kind of a registered marker
+    pub fn get(&self, marker: char) -> Option<ComponentKind> {
+        self.markers
+            .iter()
+            .find(|(c, _)| *c == marker)
+            .map(|(_, k)| *k)
+    }
+
+    /// Iterate over the registered markers
+    pub fn iter(&self) -> impl Iterator<Item = (char, ComponentKind)> + '_ {
+        self.markers.iter().copied()
+    }
+
+    /// Check if there are no extra markers
+    pub fn is_empty(&self) -> bool {
+        self.markers.is_empty()
+    }
+
+    pub(crate) fn tokens(&self) -> Vec<(char, TokenKind)> {
+        self.iter().map(|(c, k)| (c, k.token())).collect()
+    }
+
+    /// The comment prefix if it's not the default
+    pub(crate) fn custom_comment(&self) -> Option<&str> {
+        self.comment.as_deref()
+    }
+}
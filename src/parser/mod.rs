@@ -14,10 +14,10 @@
 //! section    = Eq+ (section_name Eq*)
 //! sect_name  = (!Eq ANY)*
 //!
-//! step       = TextStep? (component | ANY)*
+//! step       = TextStep? (component | media | ANY)*
 //!
 //! component  = c_kind modifiers? c_body note?
-//! c_kind     = At | Hash | Tilde
+//! c_kind     = At | Hash | Tilde   (or a custom marker, see ComponentMarkers)
 //! c_body     = c_close | c_long | Word
 //! c_long     = c_l_name c_alias? c_close
 //! c_l_name   = (!(Newline | OpenBrace | Or) ANY)*
@@ -29,6 +29,8 @@
 //!
 //! note       = OpenParen (!CloseParen ANY)* CloseParen
 //!
+//! media      = "!" "[" (!("]" | Newline) ANY)* "]"
+//!
 //! quantity   = num_val Whitespace !(unit_sep | auto_scale | val_sep) unit
 //!            | val (val_sep val)* auto_scale? (unit_sep unit)?
 //!
@@ -53,17 +55,19 @@
 //! not cover but the pareser does.
 
 mod block_parser;
+mod markers;
 mod metadata;
 mod model;
 mod quantity;
 mod section;
 mod step;
 mod text_block;
-mod token_stream;
+pub(crate) mod token_stream;
 
+pub use markers::{ComponentKind, ComponentMarkers, InvalidCommentPrefix, InvalidMarker};
 pub use model::*;
 
-use std::collections::VecDeque;
+use std::{collections::VecDeque, ops::Range};
 
 use crate::{
     error::SourceDiag,
@@ -100,11 +104,40 @@ pub enum Event<'i> {
     /// Text item
     Text(Text<'i>),
     /// Ingredient item
+    ///
+    /// The location covers all the source of the component, from the `@`
+    /// to the closing brace or note, like `@olive oil{2%tbsp}(extra virgin)`.
     Ingredient(Located<Ingredient<'i>>),
     /// Cookware item
+    ///
+    /// The location covers all the source of the component, see
+    /// [`Event::Ingredient`].
     Cookware(Located<Cookware<'i>>),
     /// Timer item
+    ///
+    /// The location covers all the source of the component, see
+    /// [`Event::Ingredient`].
     Timer(Located<Timer<'i>>),
+    /// Quantity without a component, like `@{2%cups}`
+    ///
+    /// The location covers all the source, from the `@` to the closing
+    /// brace. Only with [`Extensions::INLINE_QUANTITIES`].
+    InlineQuantity(Located<Quantity<'i>>),
+    /// Media attached to a step, like `![plating.jpg]`
+    ///
+    /// The text is the path and the location covers all the source, from the
+    /// `!` to the closing bracket. Only with [`Extensions::STEP_MEDIA`].
+    Media(Located<Text<'i>>),
+
+    /// Line (`-- note`) or block (`[- note -]`) comment
+    ///
+    /// `text` is the content without the comment markers and `span` covers
+    /// the whole comment. Only with [`Extensions::COMMENT_EVENTS`].
+    ///
+    /// Comments are never emitted between [`Event::Start`] and [`Event::End`].
+    /// The ones before a block are emitted before it, and the ones inside or
+    /// after it, right after the block ends.
+    Comment { text: Text<'i>, span: Span },
 
     /// Parser error
     ///
@@ -144,6 +177,7 @@ where
     block: Vec<Token>,
     queue: VecDeque<Event<'i>>,
     extensions: Extensions,
+    comment_prefix_len: usize,
 }
 
 impl<'i> PullParser<'i, TokenStream<'i>> {
@@ -151,6 +185,25 @@ impl<'i> PullParser<'i, TokenStream<'i>> {
     pub fn new(input: &'i str, extensions: Extensions) -> Self {
         Self::new_from_token_iter(input, extensions, TokenStream::new(input))
     }
+
+    /// Creates a new parser with extra [`ComponentMarkers`]
+    pub fn with_markers(
+        input: &'i str,
+        extensions: Extensions,
+        markers: &ComponentMarkers,
+    ) -> Self {
+        let mut parser =
+            Self::new_from_token_iter(input, extensions, TokenStream::with_markers(input, markers));
+        parser.comment_prefix_len = markers.comment_prefix().len();
+        parser
+    }
+
+    /// Only parse a part of the input, the spans are still relative to the
+    /// whole input
+    pub(crate) fn with_range(input: &'i str, range: Range<usize>, extensions: Extensions) -> Self {
+        let tokens = TokenStream::with_range(input, range, &ComponentMarkers::default());
+        Self::new_from_token_iter(input, extensions, tokens)
+    }
 }
 
 impl<'i, T> PullParser<'i, T>
@@ -164,6 +217,7 @@ where
             block: Vec::new(),
             extensions,
             queue: VecDeque::new(),
+            comment_prefix_len: 2,
         }
     }
 
@@ -239,11 +293,20 @@ where
         // Eat empty lines
         while current_line.is_empty {
             start = self.block.len();
-            current_line = self.pull_line()?;
+            match self.pull_line() {
+                Some(line) => current_line = line,
+                None => {
+                    // the comments in the trailing empty lines
+                    self.comment_events(0..self.block.len());
+                    return (!self.queue.is_empty()).then_some(());
+                }
+            }
         }
 
         // Check if more lines have to be consumed
         let multiline = multiline_ext && !current_line.is_single_line;
+        let continuation = self.extensions.contains(Extensions::METADATA_CONTINUATION)
+            && self.block[start].kind == T![meta];
         end = self.block.len();
         if multiline {
             loop {
@@ -257,6 +320,8 @@ where
                 }
                 end = self.block.len();
             }
+        } else if continuation {
+            end = self.pull_continuation_lines();
         }
 
         // trim trailing newline
@@ -267,18 +332,70 @@ where
             end -= 1;
         }
         // trim empty lines
-        let trimmed_block = &self.block[start..end];
-        if trimmed_block.is_empty() {
+        if start == end {
             return None;
         }
 
+        self.comment_events(0..start);
+        let trimmed_block = &self.block[start..end];
         let mut bp = BlockParser::new(trimmed_block, self.input, &mut self.queue, self.extensions);
         parse_block(&mut bp);
         bp.finish();
+        self.comment_events(start..self.block.len());
 
         Some(())
     }
 
+    /// Queues [`Event::Comment`] for the comments in a range of the current
+    /// block tokens, if enabled
+    fn comment_events(&mut self, range: Range<usize>) {
+        if !self.extensions.contains(Extensions::COMMENT_EVENTS) {
+            return;
+        }
+        let input = self.input;
+        for tok in &self.block[range] {
+            let span = tok.span;
+            let source = &input[span.range()];
+            let (text, offset) = match tok.kind {
+                T![line comment] => {
+                    let prefix_len = self.comment_prefix_len.min(source.len());
+                    (&source[prefix_len..], span.start() + prefix_len)
+                }
+                T![block comment] => {
+                    let inner = &source[2..];
+                    let inner = inner.strip_suffix("-]").unwrap_or(inner);
+                    (inner, span.start() + 2)
+                }
+                _ => continue,
+            };
+            self.queue.push_back(Event::Comment {
+                text: Text::from_str(text, offset),
+                span,
+            });
+        }
+    }
+
+    /// Pulls the lines that continue a metadata value, the ones starting
+    /// with whitespace. Returns the end of the last non empty line.
+    ///
+    /// An indented `>>` line is not a continuation but a new entry. Its
+    /// indentation is dropped so the next block is that metadata line.
+    fn pull_continuation_lines(&mut self) -> usize {
+        let mut end = self.block.len();
+        while let Some(mt![ws]) = self.tokens.peek() {
+            let ws = self.tokens.next().unwrap();
+            if let Some(mt![meta]) = self.tokens.peek() {
+                break;
+            }
+            self.block.push(ws);
+            match self.pull_line() {
+                Some(line) if !line.is_empty => end = self.block.len(),
+                _ => break,
+            }
+        }
+        end
+    }
+
     fn next_metadata_block(&mut self) -> Option<()> {
         self.block.clear();
 
@@ -294,12 +411,16 @@ where
         }
 
         // eat until newline or end
-        for tok in self.tokens.by_ref() {
-            if tok.kind == T![newline] {
-                break;
-            }
-            self.block.push(tok);
+        self.pull_line();
+        let mut end = self.block.len();
+        if self.extensions.contains(Extensions::METADATA_CONTINUATION) {
+            end = self.pull_continuation_lines();
+        }
+        // trim trailing newline
+        while end > 0 && self.block[end - 1].kind == T![newline] {
+            end -= 1;
         }
+        self.block.truncate(end);
 
         let mut bp = BlockParser::new(&self.block, self.input, &mut self.queue, self.extensions);
         if let Some(ev) = metadata_entry(&mut bp) {
@@ -377,9 +498,7 @@ fn parse_multiline_block(bp: &mut BlockParser) {
 /// get the span for a slice of tokens. panics if the slice is empty
 pub(crate) fn tokens_span(tokens: &[Token]) -> Span {
     debug_assert!(!tokens.is_empty(), "tokens_span tokens empty");
-    let start = tokens.first().unwrap().span.start();
-    let end = tokens.last().unwrap().span.end();
-    Span::new(start, end)
+    Span::union(tokens.first().unwrap().span, tokens.last().unwrap().span)
 }
 
 // match token type
@@ -454,8 +573,19 @@ mod tests {
         // Only whitespace between line should be trimmed
         assert!(warn.is_empty());
         assert!(err.is_empty());
+        let blocks = ast.unwrap().blocks;
+        let Block::Step { items } = &blocks[0] else {
+            panic!("not a step")
+        };
+        let Item::Text(text) = &items[0] else {
+            panic!("not text")
+        };
+        assert_eq!(
+            text.rendered(),
+            "  This is a step and this line continues  "
+        );
         assert_eq!(
-            ast.unwrap().blocks,
+            blocks,
             vec![Block::Step {
                 items: vec![Item::Text({
                     let mut t = Text::empty(0);
@@ -467,4 +597,52 @@ mod tests {
             }]
         );
     }
+
+    #[test]
+    fn comment_events() {
+        let input = "-- note\nAdd @salt. [- inline -]\n\n-- end";
+        let events = PullParser::new(input, Extensions::COMMENT_EVENTS).collect::<Vec<_>>();
+        let comments = events
+            .iter()
+            .filter_map(|ev| match ev {
+                Event::Comment { text, span } => {
+                    Some((text.text().into_owned(), &input[span.range()]))
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            comments,
+            vec![
+                (" note".to_string(), "-- note"),
+                (" inline ".to_string(), "[- inline -]"),
+                (" end".to_string(), "-- end"),
+            ]
+        );
+        // before the step and after it ends
+        assert!(matches!(events[0], Event::Comment { .. }));
+        let end = events
+            .iter()
+            .position(|ev| matches!(ev, Event::End(_)))
+            .unwrap();
+        assert!(matches!(events[end + 1], Event::Comment { .. }));
+
+        let events = PullParser::new(input, Extensions::empty()).collect::<Vec<_>>();
+        assert!(!events.iter().any(|ev| matches!(ev, Event::Comment { .. })));
+    }
+
+    #[test]
+    fn custom_prefix_comment_event() {
+        let mut markers = ComponentMarkers::new();
+        markers.set_comment_prefix(";;").unwrap();
+        let input = "Add @salt. ;; to taste";
+        let comment = PullParser::with_markers(input, Extensions::COMMENT_EVENTS, &markers)
+            .find_map(|ev| match ev {
+                Event::Comment { text, .. } => Some(text),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(comment.text_trimmed(), "to taste");
+        assert_eq!(comment.span(), Span::new(13, 22));
+    }
 }
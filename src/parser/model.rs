@@ -36,6 +36,10 @@ pub enum Item<'a> {
     Ingredient(Box<Located<Ingredient<'a>>>),
     Cookware(Box<Located<Cookware<'a>>>),
     Timer(Box<Located<Timer<'a>>>),
+    /// Quantity without a component, like `@{2%cups}`
+    InlineQuantity(Box<Located<Quantity<'a>>>),
+    /// Media attached to the step, like `![plating.jpg]`
+    Media(Box<Located<Text<'a>>>),
 }
 
 impl Item<'_> {
@@ -46,6 +50,8 @@ impl Item<'_> {
             Item::Ingredient(c) => c.span(),
             Item::Cookware(c) => c.span(),
             Item::Timer(c) => c.span(),
+            Item::InlineQuantity(q) => q.span(),
+            Item::Media(m) => m.span(),
         }
     }
 }
@@ -78,20 +84,27 @@ pub struct Cookware<'a> {
     pub modifiers: Located<Modifiers>,
     pub name: Text<'a>,
     pub alias: Option<Text<'a>>,
-    /// This it's just a [`QuantityValue`], because cookware cannot not have
-    /// a unit.
+    /// This it's just a [`QuantityValue`], the unit is in [`Self::unit`].
     pub quantity: Option<Located<QuantityValue>>,
+    /// Unit of the quantity
+    ///
+    /// Only with [`COOKWARE_UNITS`](crate::Extensions::COOKWARE_UNITS),
+    /// without it a unit is an error.
+    pub unit: Option<Text<'a>>,
     pub note: Option<Text<'a>>,
 }
 
 /// Timer [`Item`]
 ///
-/// At least one of the fields is guaranteed to be [`Some`].
+/// At least one of the fields is [`Some`], unless it's an empty timer (`~{}`),
+/// which is a warning.
 #[derive(Debug, Clone, Serialize, PartialEq)]
 pub struct Timer<'a> {
     pub name: Option<Text<'a>>,
     /// If the [`TIMER_REQUIRES_TIME`](crate::Extensions::TIMER_REQUIRES_TIME)
-    /// extension is enabled, this is guaranteed to be [`Some`].
+    /// extension is enabled, this is guaranteed to be [`Some`], unless the
+    /// timer has a name and [`TIMER_METADATA`](crate::Extensions::TIMER_METADATA)
+    /// is also enabled.
     pub quantity: Option<Located<Quantity<'a>>>,
 }
 
@@ -114,11 +127,13 @@ pub enum QuantityValue {
         value: Located<Value>,
         /// [`Some`] if the auto scale marker (`*`) is present
         auto_scale: Option<Span>,
+        /// [`Some`] if the decimal marker (`!`) is present
+        decimal: Option<Span>,
     },
     /// Many values
     ///
     /// This is parsed from values separated by `|`. It is not compatible with
-    /// the auto scale marker (`*`).
+    /// the auto scale (`*`) and decimal (`!`) markers.
     Many(Vec<Located<Value>>),
 }
 
@@ -126,13 +141,16 @@ impl QuantityValue {
     /// Calculates the span of the value or values
     pub fn span(&self) -> Span {
         match self {
-            QuantityValue::Single { value, auto_scale } => {
+            QuantityValue::Single {
+                value,
+                auto_scale,
+                decimal,
+            } => {
+                // the markers go after the value, `!` before `*`
                 let s = value.span();
-                if let Some(marker) = auto_scale {
-                    assert_eq!(s.end(), marker.start());
-                    Span::new(s.start(), marker.end())
-                } else {
-                    s
+                match auto_scale.or(*decimal) {
+                    Some(marker) => Span::new(s.start(), marker.end()),
+                    None => s,
                 }
             }
             QuantityValue::Many(v) => {
@@ -165,6 +183,7 @@ impl Recover for QuantityValue {
         Self::Single {
             value: Recover::recover(),
             auto_scale: None,
+            decimal: None,
         }
     }
 }
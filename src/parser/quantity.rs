@@ -22,11 +22,30 @@ pub struct ParsedQuantity<'a> {
 pub(crate) fn parse_quantity<'i>(
     bp: &mut BlockParser<'_, 'i>,
     tokens: &[Token],
+) -> ParsedQuantity<'i> {
+    parse_quantity_with(bp, tokens, bp.extensions)
+}
+
+/// Same as [`parse_quantity`] for a quantity that only has a unit after `%`
+///
+/// With [`Extensions::ADVANCED_UNITS`], `10 min` is a value and a unit, here
+/// it's a text value.
+pub(crate) fn parse_unitless_quantity<'i>(
+    bp: &mut BlockParser<'_, 'i>,
+    tokens: &[Token],
+) -> ParsedQuantity<'i> {
+    parse_quantity_with(bp, tokens, bp.extensions - Extensions::ADVANCED_UNITS)
+}
+
+fn parse_quantity_with<'i>(
+    bp: &mut BlockParser<'_, 'i>,
+    tokens: &[Token],
+    extensions: Extensions,
 ) -> ParsedQuantity<'i> {
     assert!(!tokens.is_empty(), "empty quantity tokens. this is a bug.");
 
     // create an insolated sub-block for the quantity tokens
-    let mut bp2 = BlockParser::new(tokens, bp.input, bp.events, bp.extensions);
+    let mut bp2 = BlockParser::new(tokens, bp.input, bp.events, extensions);
 
     let advanced = bp2
         .extension(Extensions::ADVANCED_UNITS)
@@ -37,7 +56,11 @@ pub(crate) fn parse_quantity<'i>(
 }
 
 fn parse_regular_quantity<'i>(bp: &mut BlockParser<'_, 'i>) -> ParsedQuantity<'i> {
-    let mut value = many_values(bp);
+    let arithmetic = bp
+        .extension(Extensions::ARITHMETIC_QUANTITIES)
+        .then(|| bp.with_recover(arithmetic_value))
+        .flatten();
+    let mut value = arithmetic.unwrap_or_else(|| many_values(bp));
     let unit = match bp.peek() {
         // values parsed correctly and unit
         T![%] => {
@@ -55,6 +78,7 @@ fn parse_regular_quantity<'i>(bp: &mut BlockParser<'_, 'i>) -> ParsedQuantity<'i
             value = QuantityValue::Single {
                 value: Located::new(text_val, text.span()),
                 auto_scale: None,
+                decimal: None,
             };
 
             if let Some(sep) = bp.consume(T![%]) {
@@ -75,7 +99,8 @@ fn parse_regular_quantity<'i>(bp: &mut BlockParser<'_, 'i>) -> ParsedQuantity<'i
                     label!(unit_text.span(), "add unit here")
                 )
                 .label(label!(unit_separator.unwrap(), "or remove this"))
-                .hint("It will be as if the quantity has no unit"),
+                .hint("It will be as if the quantity has no unit")
+                .set_code("empty-quantity-unit"),
             );
             unit = None;
         }
@@ -99,7 +124,12 @@ fn parse_advanced_quantity<'i>(bp: &mut BlockParser<'_, 'i>) -> Option<ParsedQua
     bp.ws_comments();
     let value_tokens = bp.consume_while(|t| !matches!(t, T![word]));
 
-    if value_tokens.is_empty() || value_tokens.last().unwrap().kind != T![ws] {
+    if value_tokens.is_empty() {
+        return None;
+    }
+    // the unit has to be separated by a space unless lenient
+    let separated = value_tokens.last().unwrap().kind == T![ws];
+    if !separated && !bp.extension(Extensions::LENIENT_UNITS) {
         return None;
     }
     let value_tokens = {
@@ -110,6 +140,7 @@ fn parse_advanced_quantity<'i>(bp: &mut BlockParser<'_, 'i>) -> Option<ParsedQua
             .unwrap(); // ws_comments were already cosumed and then checked non empty
         &value_tokens[..=end_pos]
     };
+    let (value_tokens, decimal) = decimal_marker(value_tokens, bp);
 
     let unit_tokens = bp.consume_rest();
     if unit_tokens.is_empty() {
@@ -139,6 +170,7 @@ fn parse_advanced_quantity<'i>(bp: &mut BlockParser<'_, 'i>) -> Option<ParsedQua
                 value: QuantityValue::Single {
                     value,
                     auto_scale: None,
+                    decimal,
                 },
                 unit: Some(unit),
             },
@@ -151,9 +183,12 @@ fn parse_advanced_quantity<'i>(bp: &mut BlockParser<'_, 'i>) -> Option<ParsedQua
 fn many_values(bp: &mut BlockParser) -> QuantityValue {
     let mut values: Vec<Located<Value>> = vec![];
     let mut auto_scale = None;
+    let mut decimal = None;
 
     loop {
         let value_tokens = bp.consume_while(|t| !matches!(t, T![|] | T![*] | T![%]));
+        let (value_tokens, marker) = decimal_marker(value_tokens, bp);
+        decimal = decimal.or(marker);
         values.push(parse_value(value_tokens, bp));
 
         match bp.peek() {
@@ -163,6 +198,8 @@ fn many_values(bp: &mut BlockParser) -> QuantityValue {
             T![*] => {
                 let tok = bp.bump_any();
                 auto_scale = Some(tok.span);
+                // allow spaces before the unit separator
+                bp.ws_comments();
                 break;
             }
             _ => break,
@@ -173,8 +210,18 @@ fn many_values(bp: &mut BlockParser) -> QuantityValue {
         1 => QuantityValue::Single {
             value: values.pop().unwrap(),
             auto_scale,
+            decimal,
         },
         2.. => {
+            if let Some(span) = decimal {
+                bp.error(
+                    error!(
+                        "Invalid quantity value: decimal marker is not compatible with multiple values",
+                        label!(span, "remove this")
+                    )
+                    .hint("A quantity cannot have both the decimal marker (!) and many values"),
+                )
+            }
             if let Some(span) = auto_scale {
                 bp.error(
                     error!(
@@ -192,12 +239,84 @@ fn many_values(bp: &mut BlockParser) -> QuantityValue {
     }
 }
 
+/// Evaluates a value like `2*250` or `250+50`
+///
+/// `*` goes before `+`. Every operand has to be a number or a fraction, if not,
+/// `None` is returned and the value is parsed as usual.
+fn arithmetic_value(bp: &mut BlockParser) -> Option<QuantityValue> {
+    let tokens = trim_tokens(bp.consume_while(|t| t != T![%]));
+    // a trailing `*` is the auto scale marker
+    let (tokens, auto_scale) = match tokens {
+        [rest @ .., marker @ mt![*]] => (trim_tokens(rest), Some(marker.span)),
+        _ => (tokens, None),
+    };
+    if tokens.iter().any(|t| t.kind == T![|])
+        || !tokens.iter().any(|t| matches!(t.kind, T![*] | T![+]))
+    {
+        return None;
+    }
+
+    let mut total = 0.0;
+    for term in tokens.split(|t| t.kind == T![+]) {
+        let mut product = 1.0;
+        for factor in term.split(|t| t.kind == T![*]) {
+            match numeric_value(factor, bp)? {
+                Ok(Value::Number(n)) => product *= n.value(),
+                Ok(_) => unreachable!("numeric_value not number"),
+                Err(err) => {
+                    bp.error(err);
+                    let value = Located::new(Value::recover(), tokens_span(tokens));
+                    return Some(QuantityValue::Single {
+                        value,
+                        auto_scale,
+                        decimal: None,
+                    });
+                }
+            }
+        }
+        total += product;
+    }
+
+    let value = Located::new(Value::from(total), tokens_span(tokens));
+    Some(QuantityValue::Single {
+        value,
+        auto_scale,
+        decimal: None,
+    })
+}
+
+/// Splits the decimal marker (`!`) at the end of a numeric value
+fn decimal_marker<'t>(tokens: &'t [Token], bp: &BlockParser) -> (&'t [Token], Option<Span>) {
+    if !bp.extension(Extensions::DECIMAL_MARKER) {
+        return (tokens, None);
+    }
+    let Some(pos) = tokens.iter().rposition(not_ws_comment) else {
+        return (tokens, None);
+    };
+    let marker = tokens[pos];
+    if marker.kind != T![punctuation] || &bp.input[marker.span.range()] != "!" {
+        return (tokens, None);
+    }
+    let rest = &tokens[..pos];
+    // text values keep the `!`
+    if range_value(rest, bp)
+        .or_else(|| numeric_value(rest, bp))
+        .is_none()
+    {
+        return (tokens, None);
+    }
+    (rest, Some(marker.span))
+}
+
 fn parse_value(tokens: &[Token], bp: &mut BlockParser) -> Located<Value> {
     let start = tokens
         .first()
         .map(|t| t.span.start())
         .unwrap_or(bp.current_offset()); // if empty, use the current offset
-    let end = bp.current_offset();
+    let end = tokens
+        .last()
+        .map(|t| t.span.end())
+        .unwrap_or(bp.current_offset());
     let span = Span::new(start, end);
 
     let result = range_value(tokens, bp)
@@ -218,10 +337,13 @@ fn parse_value(tokens: &[Token], bp: &mut BlockParser) -> Located<Value> {
 fn text_value(tokens: &[Token], offset: usize, bp: &mut BlockParser) -> Value {
     let text = bp.text(offset, tokens);
     if text.is_text_empty() {
-        bp.error(error!(
-            "Empty quantity value",
-            label!(text.span(), "add value here"),
-        ));
+        bp.error(
+            error!(
+                "Empty quantity value",
+                label!(text.span(), "add value here"),
+            )
+            .set_code("empty-quantity-value"),
+        );
     }
     Value::Text(text.text_trimmed().into_owned())
 }
@@ -245,6 +367,25 @@ fn range_value(tokens: &[Token], bp: &BlockParser) -> Option<Result<Value, Sourc
         };
     }
 
+    let start_empty = trim_tokens(start).is_empty();
+    let end_empty = trim_tokens(end).is_empty();
+    if start_empty && end_empty {
+        return Some(Err(error!(
+            "Empty range value",
+            label!(tokens_span(tokens), "add the start or the end of the range"),
+        )
+        .hint("Use a value like '2-3', '2-' for \"at least 2\" or '-3' for \"up to 3\"")
+        .set_code("empty-range-value")));
+    }
+    if end_empty {
+        let start = unwrap_numeric!(numeric_value(start, bp)?);
+        return Some(Ok(Value::AtLeast(start)));
+    }
+    if start_empty {
+        let end = unwrap_numeric!(numeric_value(end, bp)?);
+        return Some(Ok(Value::AtMost(end)));
+    }
+
     let start = unwrap_numeric!(numeric_value(start, bp)?);
     let end = unwrap_numeric!(numeric_value(end, bp)?);
     Some(Ok(Value::Range { start, end }))
@@ -270,6 +411,18 @@ fn numeric_value(tokens: &[Token], bp: &BlockParser) -> Option<Result<Value, Sou
         return None;
     }
 
+    // negative numbers, only one sign allowed
+    if let [mt![-], rest @ ..] = trimmed_tokens {
+        if matches!(trim_tokens(rest), [mt![-], ..]) {
+            return None;
+        }
+        let r = numeric_value(rest, bp)?;
+        return Some(r.map(|v| match v {
+            Value::Number(n) => Value::Number(-n),
+            _ => unreachable!("numeric_value not number"),
+        }));
+    }
+
     // check simple numbers
 
     // int or float
@@ -300,12 +453,73 @@ fn numeric_value(tokens: &[Token], bp: &BlockParser) -> Option<Result<Value, Sou
         [i @ mt![int], a @ mt![int], mt![/], b @ mt![int]] => mixed_num(i, a, b, bp),
         // frac
         [a @ mt![int], mt![/], b @ mt![int]] => frac(a, b, bp),
+        // unicode fraction, alone or after an int
+        [g @ mt![word]] => {
+            let (num, den) = unicode_frac(g, bp)?;
+            Ok(Number::Fraction {
+                whole: 0,
+                num,
+                den,
+                err: 0.0,
+                negative: false,
+            })
+        }
+        [i @ mt![int], g @ mt![word]] => {
+            let (num, den) = unicode_frac(g, bp)?;
+            int(i, bp).map(|whole| Number::Fraction {
+                whole,
+                num,
+                den,
+                err: 0.0,
+                negative: false,
+            })
+        }
         // other => not numeric
         _ => return None,
     };
     Some(r.map(Value::Number))
 }
 
+/// Fraction from a unicode vulgar fraction character, like `½`
+///
+/// Returns `None` if the extension is disabled or the token is not a single
+/// known fraction character.
+fn unicode_frac(tok: Token, bp: &BlockParser) -> Option<(u32, u32)> {
+    if !bp.extension(Extensions::UNICODE_FRACTIONS) {
+        return None;
+    }
+    let mut chars = bp.token_str(tok).chars();
+    match (chars.next()?, chars.next()) {
+        (c, None) => unicode_frac_value(c),
+        _ => None,
+    }
+}
+
+fn unicode_frac_value(c: char) -> Option<(u32, u32)> {
+    let frac = match c {
+        '½' => (1, 2),
+        '⅓' => (1, 3),
+        '⅔' => (2, 3),
+        '¼' => (1, 4),
+        '¾' => (3, 4),
+        '⅕' => (1, 5),
+        '⅖' => (2, 5),
+        '⅗' => (3, 5),
+        '⅘' => (4, 5),
+        '⅙' => (1, 6),
+        '⅚' => (5, 6),
+        '⅐' => (1, 7),
+        '⅛' => (1, 8),
+        '⅜' => (3, 8),
+        '⅝' => (5, 8),
+        '⅞' => (7, 8),
+        '⅑' => (1, 9),
+        '⅒' => (1, 10),
+        _ => return None,
+    };
+    Some(frac)
+}
+
 fn mixed_num(i: Token, a: Token, b: Token, bp: &BlockParser) -> Result<Number, SourceDiag> {
     let i = int(i, bp)?;
     let Number::Fraction { num, den, .. } = frac(a, b, bp)? else {
@@ -316,6 +530,7 @@ fn mixed_num(i: Token, a: Token, b: Token, bp: &BlockParser) -> Result<Number, S
         num,
         den,
         err: 0.0,
+        negative: false,
     })
 }
 
@@ -326,13 +541,15 @@ fn frac(a: Token, b: Token, line: &BlockParser) -> Result<Number, SourceDiag> {
 
     if b == 0 {
         Err(error!("Division by zero", label!(span))
-            .hint("Change this please, we don't want an infinite amount of anything"))
+            .hint("Change this please, we don't want an infinite amount of anything")
+            .set_code("division-by-zero"))
     } else {
         Ok(Number::Fraction {
             whole: 0,
             num: a,
             den: b,
             err: 0.0,
+            negative: false,
         })
     }
 }
@@ -393,6 +610,22 @@ mod tests {
         };
     }
 
+    #[test_case("1%" => true; "empty")]
+    #[test_case("1% " => true; "blank")]
+    #[test_case("1%g" => false; "unit")]
+    #[test_case("1" => false; "no separator")]
+    fn empty_unit_warning(input: &str) -> bool {
+        let (q, s, ctx) = t!(input);
+        let warned = ctx
+            .warnings()
+            .any(|w| w.code == Some("empty-quantity-unit"));
+        if warned {
+            assert!(q.unit.is_none());
+            assert!(s.is_some());
+        }
+        warned
+    }
+
     #[test]
     fn basic_quantity() {
         let (q, s, _) = t!("100%ml");
@@ -401,12 +634,46 @@ mod tests {
             QuantityValue::Single {
                 value: Located::new(num!(100.0), 0..3),
                 auto_scale: None,
+                decimal: None,
             }
         );
         assert_eq!(s, Some(Span::new(3, 4)));
         assert_eq!(q.unit.unwrap().text(), "ml");
     }
 
+    #[test_case("1\t%\tg"; "unit separator")]
+    #[test_case("\t1 \t%g\t"; "outer")]
+    #[test_case("1\t*\t%\tg"; "auto scale")]
+    fn tabs_as_spaces(input: &str) {
+        let (q, s, ctx) = t!(input);
+        assert!(ctx.is_empty());
+        assert!(s.is_some());
+        let QuantityValue::Single { value, .. } = q.value else {
+            panic!("not single value")
+        };
+        assert_eq!(value.into_inner(), num!(1.0));
+        assert_eq!(q.unit.unwrap().text_trimmed(), "g");
+    }
+
+    #[test]
+    fn tabs_in_values() {
+        let (q, _, ctx) = t!("1\t|\t2\t%\tg");
+        assert!(ctx.is_empty());
+        let QuantityValue::Many(values) = q.value else {
+            panic!("not many values")
+        };
+        assert_eq!(values[0].value(), &num!(1.0));
+        assert_eq!(values[1].value(), &num!(2.0));
+
+        let (q, _, ctx) = t!("1\t-\t2\t1/2\tcups");
+        assert!(ctx.is_empty());
+        let QuantityValue::Single { value, .. } = q.value else {
+            panic!("not single value")
+        };
+        assert_eq!(value.into_inner(), range!(1.0, 2.5));
+        assert_eq!(q.unit.unwrap().text_trimmed(), "cups");
+    }
+
     #[test]
     fn no_separator_ext() {
         let (q, s, ctx) = t!("100 ml");
@@ -414,7 +681,8 @@ mod tests {
             q.value,
             QuantityValue::Single {
                 value: Located::new(num!(100.0), 0..3),
-                auto_scale: None
+                auto_scale: None,
+                decimal: None
             }
         );
         assert_eq!(s, None);
@@ -426,7 +694,8 @@ mod tests {
             q.value,
             QuantityValue::Single {
                 value: Located::new(Value::Text("100 ml".into()), 0..6),
-                auto_scale: None
+                auto_scale: None,
+                decimal: None
             }
         );
         assert_eq!(s, None);
@@ -434,6 +703,41 @@ mod tests {
         assert!(ctx.is_empty());
     }
 
+    #[test_case("200 g" => (200.0, "g".to_string()); "space")]
+    #[test_case("200g" => (200.0, "g".to_string()); "attached")]
+    #[test_case("1.5kg" => (1.5, "kg".to_string()); "attached decimal")]
+    #[test_case("1 1/2 cups" => (1.5, "cups".to_string()); "mixed number")]
+    fn lenient_units(s: &str) -> (f64, String) {
+        let (q, _, ctx) = t!(s);
+        assert!(ctx.is_empty());
+        let QuantityValue::Single { value, .. } = q.value else {
+            panic!("not single value")
+        };
+        let Value::Number(num) = value.into_inner() else {
+            panic!("not number")
+        };
+        (num.value(), q.unit.unwrap().text().into_owned())
+    }
+
+    #[test]
+    fn lenient_units_disabled() {
+        // keep ADVANCED_UNITS, which LENIENT_UNITS includes
+        let lenient = Extensions::from_bits_retain(
+            Extensions::LENIENT_UNITS.bits() & !Extensions::ADVANCED_UNITS.bits(),
+        );
+        let (q, s, _) = t!("200g", Extensions::all() - lenient);
+        assert_eq!(
+            q.value,
+            QuantityValue::Single {
+                value: Located::new(Value::Text("200g".into()), 0..4),
+                auto_scale: None,
+                decimal: None
+            }
+        );
+        assert_eq!(s, None);
+        assert_eq!(q.unit, None);
+    }
+
     #[test]
     fn no_separator_range() {
         let (q, s, ctx) = t!("100-200 ml");
@@ -441,7 +745,8 @@ mod tests {
             q.value,
             QuantityValue::Single {
                 value: Located::new(range!(100.0, 200.0), 0..7),
-                auto_scale: None
+                auto_scale: None,
+                decimal: None
             }
         );
         assert_eq!(s, None);
@@ -459,12 +764,14 @@ mod tests {
                             whole: 2,
                             num: 1,
                             den: 2,
-                            err: 0.0
+                            err: 0.0,
+                            negative: false,
                         }
                     },
                     0..11
                 ),
-                auto_scale: None
+                auto_scale: None,
+                decimal: None
             }
         );
         assert_eq!(s, None);
@@ -513,6 +820,115 @@ mod tests {
         assert_eq!(ctx.warnings().count(), 0);
     }
 
+    #[test_case("2*250%ml" => 500.0; "multiplication")]
+    #[test_case("250+50%ml" => 300.0; "addition")]
+    #[test_case("2 * 100 + 50%ml" => 250.0; "precedence")]
+    #[test_case("1/2*3%cups" => 1.5; "fraction")]
+    fn arithmetic_values(input: &str) -> f64 {
+        let (q, _, ctx) = t!(input);
+        assert!(ctx.is_empty());
+        assert_eq!(q.unit.unwrap().text(), input.split_once('%').unwrap().1);
+        let QuantityValue::Single {
+            value,
+            auto_scale: None,
+            ..
+        } = q.value
+        else {
+            panic!("not single value");
+        };
+        let Value::Number(n) = value.into_inner() else {
+            panic!("not a number");
+        };
+        n.value()
+    }
+
+    #[test]
+    fn decimal_marker_value() {
+        let (q, _, ctx) = t!("1.5!*%cup");
+        assert!(ctx.is_empty());
+        assert_eq!(
+            q.value,
+            QuantityValue::Single {
+                value: Located::new(num!(1.5), 0..3),
+                auto_scale: Some(Span::new(4, 5)),
+                decimal: Some(Span::new(3, 4)),
+            }
+        );
+        assert_eq!(q.value.span(), Span::new(0, 5));
+
+        let (q, _, ctx) = t!("1!|2%cup");
+        assert_eq!(ctx.errors().count(), 1);
+        assert!(matches!(q.value, QuantityValue::Many(_)));
+    }
+
+    #[test]
+    fn arithmetic_value_fallback() {
+        // division by zero
+        let (_, _, ctx) = t!("2*1/0%ml");
+        assert_eq!(ctx.errors().count(), 1);
+        assert_eq!(ctx.errors().next().unwrap().code, Some("division-by-zero"));
+
+        // non numeric operands are text
+        let (q, _, ctx) = t!("2*a few%ml");
+        assert!(ctx.is_empty());
+        assert!(matches!(
+            q.value,
+            QuantityValue::Single { value, .. } if *value == Value::Text("2*a few".into())
+        ));
+
+        // still the auto scale marker
+        let (q, _, _) = t!("2*250*%ml");
+        assert!(matches!(
+            q.value,
+            QuantityValue::Single { value, auto_scale: Some(_), .. } if *value == num!(500.0)
+        ));
+
+        // disabled
+        let (q, _, _) = t!(
+            "2*250%ml",
+            Extensions::all() ^ Extensions::ARITHMETIC_QUANTITIES
+        );
+        assert!(matches!(
+            q.value,
+            QuantityValue::Single { value, .. } if matches!(*value, Value::Text(_))
+        ));
+    }
+
+    #[test_case("½%cup" => 0.5; "glyph")]
+    #[test_case("1½%cup" => 1.5; "mixed")]
+    #[test_case("2 ¾%cup" => 2.75; "mixed with space")]
+    #[test_case("⅓%cup" => 1.0 / 3.0; "third")]
+    fn unicode_fractions(input: &str) -> f64 {
+        let (q, _, ctx) = t!(input);
+        assert!(ctx.is_empty());
+        assert_eq!(q.unit.unwrap().text(), "cup");
+        let QuantityValue::Single { value, .. } = q.value else {
+            panic!("not single value");
+        };
+        let Value::Number(n) = value.into_inner() else {
+            panic!("not a number");
+        };
+        n.value()
+    }
+
+    #[test]
+    fn unicode_fractions_fallback() {
+        // unknown fraction glyph
+        let (q, _, ctx) = t!("↉%cup");
+        assert!(ctx.is_empty());
+        assert!(matches!(
+            q.value,
+            QuantityValue::Single { value, .. } if *value == Value::Text("↉".into())
+        ));
+
+        // disabled
+        let (q, _, _) = t!("½%cup", Extensions::all() ^ Extensions::UNICODE_FRACTIONS);
+        assert!(matches!(
+            q.value,
+            QuantityValue::Single { value, .. } if *value == Value::Text("½".into())
+        ));
+    }
+
     #[test]
     fn range_value() {
         let (q, _, _) = t!("2-3");
@@ -520,7 +936,8 @@ mod tests {
             q.value,
             QuantityValue::Single {
                 value: Located::new(range!(2.0, 3.0), 0..3),
-                auto_scale: None
+                auto_scale: None,
+                decimal: None
             }
         );
         assert_eq!(q.unit, None);
@@ -533,12 +950,53 @@ mod tests {
             q.value,
             QuantityValue::Single {
                 value: Located::new(Value::Text("2-3".into()), 0..3),
-                auto_scale: None
+                auto_scale: None,
+                decimal: None
             }
         );
         assert_eq!(q.unit, None);
     }
 
+    #[test]
+    fn open_range_value() {
+        let (q, _, ctx) = t!("2-");
+        assert_eq!(
+            q.value,
+            QuantityValue::Single {
+                value: Located::new(Value::AtLeast(Number::Regular(2.0)), 0..2),
+                auto_scale: None,
+                decimal: None
+            }
+        );
+        assert!(ctx.is_empty());
+
+        let (q, _, ctx) = t!("-3");
+        assert_eq!(
+            q.value,
+            QuantityValue::Single {
+                value: Located::new(Value::AtMost(Number::Regular(3.0)), 0..2),
+                auto_scale: None,
+                decimal: None
+            }
+        );
+        assert!(ctx.is_empty());
+
+        // without ranges, no start is a negative number
+        let (q, _, _) = t!("-3", Extensions::all() - Extensions::RANGE_VALUES);
+        assert_eq!(
+            q.value,
+            QuantityValue::Single {
+                value: Located::new(num!(-3.0), 0..2),
+                auto_scale: None,
+                decimal: None
+            }
+        );
+
+        let (_, _, ctx) = t!("-");
+        assert_eq!(ctx.errors().count(), 1);
+        assert_eq!(ctx.errors().next().unwrap().code, Some("empty-range-value"));
+    }
+
     #[test]
     fn range_mixed_value() {
         let (q, _, _) = t!("2 1/2-3");
@@ -546,7 +1004,8 @@ mod tests {
             q.value,
             QuantityValue::Single {
                 value: Located::new(range!(2.5, 3.0), 0..7),
-                auto_scale: None
+                auto_scale: None,
+                decimal: None
             }
         );
         assert_eq!(q.unit, None);
@@ -556,7 +1015,8 @@ mod tests {
             q.value,
             QuantityValue::Single {
                 value: Located::new(range!(2.0, 3.5), 0..7),
-                auto_scale: None
+                auto_scale: None,
+                decimal: None
             }
         );
         assert_eq!(q.unit, None);
@@ -566,7 +1026,8 @@ mod tests {
             q.value,
             QuantityValue::Single {
                 value: Located::new(range!(2.5, 3.5), 0..11),
-                auto_scale: None
+                auto_scale: None,
+                decimal: None
             }
         );
         assert_eq!(q.unit, None);
@@ -576,8 +1037,11 @@ mod tests {
     #[test_case("0 1/2" => (0, 1, 2); "zero whole")]
     #[test_case("01/2" => panics "not number"; "bad fraction")]
     #[test_case("2 1/2" => (2, 1, 2); "mixed value")]
+    #[test_case("-1/2" => (0, 1, 2); "negative fraction")]
+    #[test_case("-2 1/2" => (2, 1, 2); "negative mixed value")]
     fn fractional_val(s: &str) -> (u32, u32, u32) {
-        let (q, _, _) = t!(s);
+        // without ranges, a leading `-` is a negative sign
+        let (q, _, _) = t!(s, Extensions::all() - Extensions::RANGE_VALUES);
         let QuantityValue::Single { value, .. } = q.value else {
             panic!("not single value")
         };
@@ -590,11 +1054,13 @@ mod tests {
             num,
             den,
             err,
+            negative,
         } = num
         else {
             panic!("not fraction")
         };
         assert_eq!(err, 0.0);
+        assert_eq!(negative, s.starts_with('-'));
         (whole, num, den)
     }
 
@@ -605,8 +1071,12 @@ mod tests {
     #[test_case("10.05" => 10.05)]
     #[test_case("01" => panics "not number")]
     #[test_case("01.0" => panics "not number")]
+    #[test_case("0" => 0.0; "zero")]
+    #[test_case("-1" => -1.0; "negative")]
+    #[test_case("-1.5" => -1.5; "negative decimal")]
+    #[test_case("- -1" => panics "not number"; "double negative")]
     fn simple_numbers(s: &str) -> f64 {
-        let (q, _, r) = t!(s);
+        let (q, _, r) = t!(s, Extensions::all() - Extensions::RANGE_VALUES);
         let QuantityValue::Single { value, .. } = q.value else {
             panic!("not single value")
         };
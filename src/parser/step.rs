@@ -6,8 +6,10 @@ use crate::{
 };
 
 use super::{
-    error, mt, quantity::parse_quantity, token_stream::Token, tokens_span, warning, BlockKind,
-    BlockParser, Event,
+    error, mt,
+    quantity::{parse_quantity, parse_unitless_quantity},
+    token_stream::Token,
+    tokens_span, warning, BlockKind, BlockParser, Event,
 };
 
 pub(crate) fn parse_step(bp: &mut BlockParser<'_, '_>) {
@@ -18,6 +20,7 @@ pub(crate) fn parse_step(bp: &mut BlockParser<'_, '_>) {
             T![@] => bp.with_recover(ingredient),
             T![#] => bp.with_recover(cookware),
             T![~] => bp.with_recover(timer),
+            T![punctuation] if at_media(bp) => bp.with_recover(media),
             _ => None,
         };
         if let Some(ev) = component {
@@ -26,7 +29,12 @@ pub(crate) fn parse_step(bp: &mut BlockParser<'_, '_>) {
             let start = bp.current_offset();
             let tokens = bp.capture_slice(|bp| {
                 bp.bump_any(); // consume the first token, this avoids entering an infinite loop
-                bp.consume_while(|t| !matches!(t, T![@] | T![#] | T![~]));
+                while !bp.rest().is_empty()
+                    && !matches!(bp.peek(), T![@] | T![#] | T![~])
+                    && !at_media(bp)
+                {
+                    bp.bump_any();
+                }
             });
             let text = bp.text(start, tokens);
             if !text.fragments().is_empty() {
@@ -38,6 +46,37 @@ pub(crate) fn parse_step(bp: &mut BlockParser<'_, '_>) {
     bp.event(Event::End(BlockKind::Step));
 }
 
+fn is_punct(bp: &BlockParser, token: Token, s: &str) -> bool {
+    token.kind == T![punctuation] && bp.token_str(token) == s
+}
+
+/// Checks if the next tokens are the start of a media item, `![`
+fn at_media(bp: &BlockParser) -> bool {
+    bp.extension(Extensions::STEP_MEDIA)
+        && matches!(bp.rest(), [bang, open, ..] if is_punct(bp, *bang, "!") && is_punct(bp, *open, "["))
+}
+
+fn media<'i>(bp: &mut BlockParser<'_, 'i>) -> Option<Event<'i>> {
+    let start = bp.current_offset();
+    bp.bump(T![punctuation]); // !
+    bp.bump(T![punctuation]); // [
+    let offset = bp.current_offset();
+    let path = bp.capture_slice(|bp| {
+        while !bp.rest().is_empty() && !bp.at(T![newline]) && !is_punct(bp, bp.rest()[0], "]") {
+            bp.bump_any();
+        }
+    });
+    // without the closing bracket in the same line it's just text
+    bp.next_token().filter(|t| is_punct(bp, *t, "]"))?;
+    let end = bp.current_offset();
+
+    let path = bp.text(offset, path);
+    if path.text_trimmed().is_empty() {
+        return None;
+    }
+    Some(Event::Media(Located::new(path, start..end)))
+}
+
 struct Body<'t> {
     name: &'t [Token],
     close: Option<Span>,
@@ -47,8 +86,15 @@ struct Body<'t> {
 fn comp_body<'t>(bp: &mut BlockParser<'t, '_>) -> Option<Body<'t>> {
     bp.with_recover(|line| {
         let name = line.until(|t| matches!(t, T!['{'] | T![@] | T![#] | T![~]))?;
-        let close_span_start = line.consume(T!['{'])?.span.start();
-        let quantity = line.until(|t| t == T!['}'])?;
+        let open = line.consume(T!['{'])?;
+        let close_span_start = open.span.start();
+        let Some(quantity) = until_closed(line, T!['{'], T!['}']) else {
+            // if the name is in another line the brace is not for this component
+            if !name.iter().any(|t| t.kind == T![newline]) {
+                unclosed(line, open, "}", "quantity");
+            }
+            return None;
+        };
         let close_span_end = line.bump(T!['}']).span.end();
         let close_span = Span::new(close_span_start, close_span_end);
         let quantity_not_empty = quantity
@@ -119,9 +165,12 @@ fn note<'i>(bp: &mut BlockParser<'_, 'i>) -> Option<Text<'i>> {
     bp.extension(Extensions::COMPONENT_NOTE)
         .then(|| {
             bp.with_recover(|line| {
-                line.consume(T!['('])?;
+                let open = line.consume(T!['('])?;
                 let offset = line.current_offset();
-                let note = line.until(|t| t == T![')'])?;
+                let Some(note) = until_closed(line, T!['('], T![')']) else {
+                    unclosed(line, open, ")", "note");
+                    return None;
+                };
                 line.bump(T![')']);
                 Some(line.text(offset, note))
             })
@@ -129,6 +178,50 @@ fn note<'i>(bp: &mut BlockParser<'_, 'i>) -> Option<Text<'i>> {
         .flatten()
 }
 
+/// Like [`BlockParser::until`] the `close` token
+///
+/// Without [`MULTILINE_STEPS`](Extensions::MULTILINE_STEPS) the `close` has to
+/// be in the same line. With it, it can be in a later line of the step, but
+/// not after another `open`, so an unclosed part doesn't use the `close` of
+/// the next component.
+fn until_closed<'t>(
+    bp: &mut BlockParser<'t, '_>,
+    open: crate::lexer::TokenKind,
+    close: crate::lexer::TokenKind,
+) -> Option<&'t [Token]> {
+    let multiline = bp.extension(Extensions::MULTILINE_STEPS);
+    let tokens = bp.until(|t| t == close || t == open || (!multiline && t == T![newline]))?;
+    if bp.at(close) {
+        Some(tokens)
+    } else {
+        None
+    }
+}
+
+/// Warns about an `open` token without its `close`
+fn unclosed(bp: &mut BlockParser, open: Token, close: &str, part: &str) {
+    // the end of the line where it was opened
+    let expected = match bp
+        .tokens()
+        .iter()
+        .find(|t| t.kind == T![newline] && t.span.start() > open.span.start())
+    {
+        Some(newline) => newline.span.start(),
+        None => bp.tokens().last().map_or(open.span.end(), |t| t.span.end()),
+    };
+    bp.warn(
+        warning!(
+            format!("Invalid component {part}: missing '{close}'"),
+            label!(Span::pos(expected), "expected '{}' here", close),
+        )
+        .label(label!(open.span, "opened here"))
+        .hint(format!(
+            "The component {part} has to be closed before the next one or the end of the step. It will be ignored"
+        ))
+        .set_code("component-part-missing"),
+    );
+}
+
 struct ParsedModifiers {
     flags: Located<Modifiers>,
     intermediate_data: Option<Located<IntermediateData>>,
@@ -336,6 +429,12 @@ fn ingredient<'i>(bp: &mut BlockParser<'_, 'i>) -> Option<Event<'i>> {
     let modifiers_tokens = modifiers(bp);
     let name_offset = bp.current_offset();
     let body = comp_body(bp)?;
+    if body.name.is_empty()
+        && modifiers_tokens.is_empty()
+        && bp.extension(Extensions::INLINE_QUANTITIES)
+    {
+        return Some(inline_quantity(bp, start, body.quantity));
+    }
     let note = note(bp);
     let end = bp.current_offset();
 
@@ -365,6 +464,29 @@ fn ingredient<'i>(bp: &mut BlockParser<'_, 'i>) -> Option<Event<'i>> {
     )))
 }
 
+fn inline_quantity<'i>(
+    bp: &mut BlockParser<'_, 'i>,
+    start: usize,
+    tokens: Option<&[Token]>,
+) -> Event<'i> {
+    let end = bp.current_offset();
+    let quantity = match tokens {
+        Some(tokens) => parse_quantity(bp, tokens).quantity.into_inner(),
+        None => {
+            bp.error(
+                error!(
+                    "Invalid inline quantity: is empty",
+                    label!(Span::new(start, end), "add a quantity inside the braces"),
+                )
+                .hint("Remove it or use `@name{}` for an ingredient")
+                .set_code("empty-inline-quantity"),
+            );
+            Quantity::recover()
+        }
+    };
+    Event::InlineQuantity(Located::new(quantity, start..end))
+}
+
 fn cookware<'i>(bp: &mut BlockParser<'_, 'i>) -> Option<Event<'i>> {
     // Parse
     let start = bp.current_offset();
@@ -380,9 +502,15 @@ fn cookware<'i>(bp: &mut BlockParser<'_, 'i>) -> Option<Event<'i>> {
     let (name, alias) = parse_alias(COOKWARE, bp, body.name, name_offset);
     check_empty_name(COOKWARE, bp, &name);
 
+    let allow_unit = bp.extension(Extensions::COOKWARE_UNITS);
+    let mut unit = None;
     let quantity = body.quantity.map(|tokens| {
-        let q = parse_quantity(bp, tokens);
-        if let Some(unit) = &q.quantity.unit {
+        let q = if allow_unit {
+            parse_quantity(bp, tokens)
+        } else {
+            parse_unitless_quantity(bp, tokens)
+        };
+        if let (Some(unit), false) = (&q.quantity.unit, allow_unit) {
             let span = if let Some(sep) = q.unit_separator {
                 Span::new(sep.start(), unit.span().end())
             } else {
@@ -396,20 +524,12 @@ fn cookware<'i>(bp: &mut BlockParser<'_, 'i>) -> Option<Event<'i>> {
                 .hint("Cookware items can't have units"),
             );
         }
-        if let QuantityValue::Single {
-            auto_scale: Some(auto_scale),
-            ..
-        } = &q.quantity.value
-        {
-            bp.error(
-                error!(
-                    "Invalid cookware quantity: auto scale marker",
-                    label!(auto_scale, "remove this"),
-                )
-                .hint("Cookware items amount can't be auto scaled"),
-            );
-        }
-        q.quantity.map(|q| q.value)
+        q.quantity.map(|q| {
+            if allow_unit {
+                unit = q.unit;
+            }
+            q.value
+        })
     });
     let modifiers = parse_modifiers(bp, modifiers_tokens, modifiers_pos);
     let modifiers = check_intermediate_data(bp, modifiers, COOKWARE);
@@ -434,6 +554,7 @@ fn cookware<'i>(bp: &mut BlockParser<'_, 'i>) -> Option<Event<'i>> {
             name,
             alias,
             quantity,
+            unit,
             modifiers,
             note,
         },
@@ -481,18 +602,24 @@ fn timer<'i>(bp: &mut BlockParser<'_, 'i>) -> Option<Event<'i>> {
                         "expected unit here"
                     ),
                 )
-                .hint("A timer needs a unit to know the duration"),
+                .hint("A timer needs a unit to know the duration")
+                .set_code("timer-missing-unit"),
             )
         }
         q.quantity
     });
 
-    if quantity.is_none() && bp.extension(Extensions::TIMER_REQUIRES_TIME) {
+    // with metadata durations, it's checked in the analysis
+    let from_metadata = bp.extension(Extensions::TIMER_METADATA) && !name.is_text_empty();
+    if quantity.is_none() && bp.extension(Extensions::TIMER_REQUIRES_TIME) && !from_metadata {
         let span = body.close.unwrap_or_else(|| Span::pos(name.span().end()));
-        bp.error(error!(
-            "Invalid timer: missing quantity",
-            label!(span, "expected timer duration here"),
-        ));
+        bp.error(
+            error!(
+                "Invalid timer: missing quantity",
+                label!(span, "expected timer duration here"),
+            )
+            .set_code("timer-missing-quantity"),
+        );
         quantity = Some(Recover::recover());
     }
 
@@ -508,11 +635,14 @@ fn timer<'i>(bp: &mut BlockParser<'_, 'i>) -> Option<Event<'i>> {
         } else {
             Span::pos(name_offset)
         };
-        bp.error(error!(
-            "Invalid timer: neither quantity nor name",
-            label!(span, "expected duration or name"),
-        ));
-        quantity = Some(Recover::recover()); // could be also name, but whatever
+        bp.warn(
+            warning!(
+                "Empty timer: neither quantity nor name",
+                label!(span, "expected duration or name"),
+            )
+            .hint("The timer has no effect, add a duration or remove it")
+            .set_code("empty-timer"),
+        );
     }
 
     Some(Event::Timer(Located::new(
@@ -617,6 +747,10 @@ mod tests {
     use test_case::test_case;
 
     fn t(input: &str) -> (Vec<Event>, SourceReport) {
+        t_with(input, Extensions::all())
+    }
+
+    fn t_with(input: &str, extensions: Extensions) -> (Vec<Event<'_>>, SourceReport) {
         let mut tokens = TokenStream::new(input).collect::<Vec<_>>();
         // trim trailing newlines, block splitting should make sure this never
         // reaches the step function
@@ -624,7 +758,7 @@ mod tests {
             tokens.pop();
         }
         let mut events = VecDeque::new();
-        let mut bp = BlockParser::new(&tokens, input, &mut events, Extensions::all());
+        let mut bp = BlockParser::new(&tokens, input, &mut events, extensions);
         parse_step(&mut bp);
         bp.finish();
         let mut other = Vec::new();
@@ -702,6 +836,87 @@ mod tests {
         assert_eq!(ctx.errors().count(), 1);
     }
 
+    #[test_case("Add @olive oil{2%tbsp} now." => "@olive oil{2%tbsp}"; "ingredient")]
+    #[test_case("Add @salt now." => "@salt"; "single word")]
+    #[test_case("Add @&(1)dough{}(rested) now." => "@&(1)dough{}(rested)"; "modifiers and note")]
+    #[test_case("Use a #big pot{1}(lid) now." => "#big pot{1}(lid)"; "cookware")]
+    #[test_case("Wait ~rest{5%min} now." => "~rest{5%min}"; "timer")]
+    fn component_raw_span(input: &str) -> &str {
+        let (s, ctx) = t(input);
+        assert!(ctx.is_empty());
+        let span = match &s[1] {
+            Event::Ingredient(c) => c.span(),
+            Event::Cookware(c) => c.span(),
+            Event::Timer(c) => c.span(),
+            _ => panic!(),
+        };
+        &input[span.range()]
+    }
+
+    #[test_case("Add @flour{200%g and mix." => ("}", 25); "unclosed quantity")]
+    #[test_case("Add @flour{200%g\nand mix." => ("}", 16); "unclosed quantity newline")]
+    #[test_case("Add @flour{200%g}(sifted and mix." => (")", 33); "unclosed note")]
+    #[test_case("Add @flour{200%g}(sifted\nand @salt(fine)." => (")", 24); "unclosed note newline")]
+    #[test_case("Add @flour{200%g and\n@salt{1}." => ("}", 20); "next quantity")]
+    fn unclosed_component_part(input: &str) -> (&'static str, usize) {
+        unclosed_component_part_with(input, Extensions::all())
+    }
+
+    #[test_case("Add @flour{200%g\nand mix}." => ("}", 16); "quantity")]
+    #[test_case("Add @flour{200%g}(sifted\nand mix)." => (")", 24); "note")]
+    fn unclosed_component_part_single_line(input: &str) -> (&'static str, usize) {
+        unclosed_component_part_with(input, Extensions::all() - Extensions::MULTILINE_STEPS)
+    }
+
+    fn unclosed_component_part_with(input: &str, extensions: Extensions) -> (&'static str, usize) {
+        let (s, ctx) = t_with(input, extensions);
+        assert_eq!(ctx.errors().count(), 0);
+        let warnings = ctx.warnings().collect::<Vec<_>>();
+        assert_eq!(warnings.len(), 1);
+        let w = warnings[0];
+        assert_eq!(w.code, Some("component-part-missing"));
+        // the component is still there and the rest is text
+        assert_eq!(igr!(&s[1]).name.text_trimmed(), "flour");
+        assert!(matches!(s.last().unwrap(), Event::Text(_)));
+        let close = if w.message.contains("'}'") { "}" } else { ")" };
+        (close, w.labels[0].0.start())
+    }
+
+    #[test_case("Add @flour{200\n%g} and mix." => ("200\n%g", None); "quantity")]
+    #[test_case("Add @flour(sifted\nfine) and mix." => ("", Some("sifted\nfine")); "note")]
+    fn multiline_component_part(input: &str) -> (&str, Option<&str>) {
+        let (s, ctx) = t(input);
+        assert!(ctx.is_empty());
+        let igr = igr!(&s[1]);
+        assert_eq!(igr.name.text_trimmed(), "flour");
+        let quantity = igr
+            .quantity
+            .as_ref()
+            .map_or("", |q| &input[q.span().range()]);
+        let quantity = quantity.trim_start_matches('{').trim_end_matches('}');
+        (
+            quantity,
+            igr.note.as_ref().map(|n| &input[n.span().range()]),
+        )
+    }
+
+    #[test_case("Add @salt{1%g}, @x{1|} and stir." => 1; "empty value")]
+    #[test_case("Add @salt{1%g}, @x{1/0} and stir." => 1; "division by zero")]
+    #[test_case("Add @salt{1%g}, @&(awebo)x{} and stir." => 1; "invalid intermediate")]
+    #[test_case("Add @salt{1%g}, @x{!!} and stir." => 0; "text value")]
+    fn malformed_component_keeps_step(input: &str) -> usize {
+        let (s, ctx) = t(input);
+        // the error is only for the bad component, the rest of the step is
+        // still there
+        assert_eq!(s.len(), 5);
+        assert_eq!(igr!(&s[1]).name.text_trimmed(), "salt");
+        assert!(igr!(&s[1]).quantity.is_some());
+        assert_eq!(igr!(&s[3]).name.text_trimmed(), "x");
+        let Event::Text(text) = &s[4] else { panic!() };
+        assert_eq!(text.text(), " and stir.");
+        ctx.errors().count()
+    }
+
     #[test_case("bread" => "bread")]
     #[test_case("bread1" => "bread1")]
     #[test_case("bread01" => "bread01")]
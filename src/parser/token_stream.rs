@@ -2,18 +2,46 @@
 //! `Parser(super::parser::Parser`).
 
 pub use crate::lexer::TokenKind;
-use crate::{lexer::Cursor, span::Span};
+use std::ops::Range;
+
+use crate::{
+    lexer::{Cursor, T},
+    parser::ComponentMarkers,
+    span::Span,
+};
+
+/// UTF-8 byte order mark, some editors add it at the start of the file
+const BOM: char = '\u{FEFF}';
 
 pub struct TokenStream<'i> {
     cursor: Cursor<'i>,
     consumed: usize,
+    components_disabled: bool,
 }
 
 impl<'i> TokenStream<'i> {
     pub fn new(input: &'i str) -> Self {
+        Self::with_markers(input, &ComponentMarkers::default())
+    }
+
+    pub fn with_markers(input: &'i str, markers: &ComponentMarkers) -> Self {
+        Self::with_range(input, 0..input.len(), markers)
+    }
+
+    /// Only tokenize a part of the input, the offsets are still relative to
+    /// the whole input
+    pub fn with_range(input: &'i str, range: Range<usize>, markers: &ComponentMarkers) -> Self {
+        let mut content = &input[range.clone()];
+        // Skip the BOM but keep the offsets relative to the whole input
+        if range.start == 0 {
+            content = content.strip_prefix(BOM).unwrap_or(content);
+        }
+        let mut cursor = Cursor::with_markers(content, markers.tokens());
+        cursor.comment = markers.custom_comment().map(str::to_string);
         Self {
-            cursor: Cursor::new(input),
-            consumed: 0,
+            cursor,
+            consumed: range.end - content.len(),
+            components_disabled: markers.components_disabled(),
         }
     }
 }
@@ -28,24 +56,37 @@ impl<'i> Iterator for TokenStream<'i> {
         if t.kind == TokenKind::Eof && self.cursor.is_eof() {
             None
         } else {
+            let kind = match t.kind {
+                T![@] | T![#] | T![~] if self.components_disabled => T![punctuation],
+                kind => kind,
+            };
             Some(Token {
-                kind: t.kind,
+                kind,
                 span: Span::new(start, self.consumed),
             })
         }
     }
 }
 
+/// A token of the input. See [`lexer`](crate::lexer)
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Token {
+    /// What the token is
     pub kind: TokenKind,
+    /// Location in the input
     pub span: Span,
 }
 
 impl Token {
+    /// Length of the token in bytes
     pub fn len(&self) -> usize {
         self.span.len()
     }
+
+    /// Checks if the token has no length
+    pub fn is_empty(&self) -> bool {
+        self.span.is_empty()
+    }
 }
 
 #[cfg(test)]
@@ -69,6 +110,17 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn skip_bom() {
+        let input = "\u{FEFF}>> a: b\r\n";
+        let tokens = TokenStream::new(input).collect::<Vec<_>>();
+        assert_eq!(tokens[0].kind, T![meta]);
+        assert_eq!(tokens[0].span, Span::new(3, 5));
+        let last = tokens.last().unwrap();
+        assert_eq!(last.kind, T![newline]);
+        assert_eq!(&input[last.span.range()], "\r\n");
+    }
+
     #[test]
     fn tokens_macro() {
         let t = tokens![word.3, ws.1];
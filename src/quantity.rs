@@ -1,6 +1,6 @@
 //! Quantity model
 
-use std::{collections::HashMap, fmt::Display, sync::Arc};
+use std::{cmp::Ordering, collections::HashMap, fmt::Display, ops::RangeInclusive, sync::Arc};
 
 use enum_map::EnumMap;
 use once_cell::sync::{Lazy, OnceCell};
@@ -13,11 +13,24 @@ use crate::{
 };
 
 /// A quantity used in components
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+///
+/// The value as written, [`Quantity::source_value`], is not serialized and
+/// not compared, so `200` and `200.0` are equal.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Quantity<V: QuantityValue = Value> {
     /// Value
     pub value: V,
     pub(crate) unit: Option<QuantityUnit>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub(crate) decimal: bool,
+    #[serde(skip)]
+    pub(crate) source: Option<Box<str>>,
+}
+
+impl<V: QuantityValue + PartialEq> PartialEq for Quantity<V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value && self.unit == other.unit && self.decimal == other.decimal
+    }
 }
 
 pub type ScalableQuantity = Quantity<ScalableValue>;
@@ -43,6 +56,10 @@ pub enum Value {
     Number(Number),
     /// Range
     Range { start: Number, end: Number },
+    /// Range without an upper bound, like `2-` for "at least 2"
+    AtLeast(Number),
+    /// Range without a lower bound, like `-3` for "up to 3"
+    AtMost(Number),
     /// Text
     ///
     /// It is not possible to operate with this variant.
@@ -64,12 +81,15 @@ pub enum Value {
 /// assert_eq!(num.to_string(), "14");
 /// let num = Number::Regular(14.57893);
 /// assert_eq!(num.to_string(), "14.579");
-/// let num = Number::Fraction { whole: 0, num: 1, den: 2, err: 0.0 };
+/// let num = Number::Fraction { whole: 0, num: 1, den: 2, err: 0.0, negative: false };
 /// assert_eq!(num.to_string(), "1/2");
 /// assert_eq!(num.value(), 0.5);
-/// let num = Number::Fraction { whole: 2, num: 1, den: 2, err: 0.001 };
+/// let num = Number::Fraction { whole: 2, num: 1, den: 2, err: 0.001, negative: false };
 /// assert_eq!(num.to_string(), "2 1/2");
 /// assert_eq!(num.value(), 2.501);
+/// let num = Number::Fraction { whole: 0, num: 1, den: 2, err: 0.0, negative: true };
+/// assert_eq!(num.to_string(), "-1/2");
+/// assert_eq!(num.value(), -0.5);
 /// ```
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 #[serde(tag = "type", content = "value", rename_all = "camelCase")]
@@ -78,8 +98,8 @@ pub enum Number {
     Regular(f64),
     /// A fractional number
     ///
-    /// This is in the form of `[<whole>] <num>/<den>` and the total value is
-    /// `whole + err + num / den`.
+    /// This is in the form of `[-][<whole>] <num>/<den>` and the total value is
+    /// `whole + err + num / den`, negated if `negative`.
     ///
     /// `err` exists to allow lossy conversions between a regular number and a
     /// fraction. Use the alternate (`#`) for the [`Display`] impl to include
@@ -89,6 +109,8 @@ pub enum Number {
         num: u32,
         den: u32,
         err: f64,
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        negative: bool,
     },
 }
 
@@ -104,6 +126,30 @@ impl From<f64> for Number {
     }
 }
 
+impl std::ops::Neg for Number {
+    type Output = Self;
+
+    /// Changes the sign, a fraction is kept as a fraction
+    fn neg(self) -> Self {
+        match self {
+            Self::Regular(v) => Self::Regular(-v),
+            Self::Fraction {
+                whole,
+                num,
+                den,
+                err,
+                negative,
+            } => Self::Fraction {
+                whole,
+                num,
+                den,
+                err,
+                negative: !negative,
+            },
+        }
+    }
+}
+
 impl Number {
     /// Get's the true inner value
     ///
@@ -116,7 +162,15 @@ impl Number {
                 num,
                 den,
                 err,
-            } => whole as f64 + err + num as f64 / den as f64,
+                negative,
+            } => {
+                let value = whole as f64 + err + num as f64 / den as f64;
+                if negative {
+                    -value
+                } else {
+                    value
+                }
+            }
         }
     }
 }
@@ -219,6 +273,8 @@ impl<V: QuantityValue> Quantity<V> {
                 text,
                 info: OnceCell::new(),
             }),
+            decimal: false,
+            source: None,
         }
     }
 
@@ -230,6 +286,8 @@ impl<V: QuantityValue> Quantity<V> {
                 info: OnceCell::from(UnitInfo::new(&text, converter)),
                 text,
             }),
+            decimal: false,
+            source: None,
         }
     }
 
@@ -241,6 +299,8 @@ impl<V: QuantityValue> Quantity<V> {
                 text: unit.to_string(),
                 info: OnceCell::from(UnitInfo::Known(unit)),
             }),
+            decimal: false,
+            source: None,
         }
     }
 
@@ -263,6 +323,41 @@ impl<V: QuantityValue> Quantity<V> {
     pub fn unit_text(&self) -> Option<&str> {
         self.unit.as_ref().map(|u| u.text.as_ref())
     }
+
+    /// Checks if the value is kept as a decimal
+    ///
+    /// A decimal quantity is never approximated to a fraction when fitting,
+    /// converting or formatting it. See [`Extensions::DECIMAL_MARKER`](crate::Extensions::DECIMAL_MARKER).
+    pub fn is_decimal(&self) -> bool {
+        self.decimal
+    }
+
+    /// Set if the value is kept as a decimal, see [`Self::is_decimal`]
+    pub fn set_decimal(&mut self, decimal: bool) {
+        self.decimal = decimal;
+    }
+
+    /// Original text of the value, exactly as written in the recipe
+    ///
+    /// For a number this is the literal, like `1 1/2` or `1.5`, so it can be
+    /// displayed the way the author wrote it. For [`Value::Text`] it's the
+    /// text.
+    ///
+    /// This is [`None`] when the quantity was not parsed from a recipe or when
+    /// the value no longer matches the text, after scaling it, converting
+    /// it, adding it to other quantity or if it has many values (`2|3`). The
+    /// unit is not included, see [`Self::unit_text`].
+    ///
+    /// ```
+    /// # use cooklang::CooklangParser;
+    /// let parser = CooklangParser::extended();
+    /// let recipe = parser.parse("@flour{1 1/2%cups}").unwrap_output();
+    /// let q = recipe.ingredients[0].quantity.as_ref().unwrap();
+    /// assert_eq!(q.source_value(), Some("1 1/2"));
+    /// ```
+    pub fn source_value(&self) -> Option<&str> {
+        self.source.as_deref()
+    }
 }
 
 impl ScalableValue {
@@ -318,12 +413,14 @@ impl Display for Value {
         match self {
             Value::Number(n) => n.fmt(f),
             Value::Range { start, end } => write!(f, "{start}-{end}"),
+            Value::AtLeast(start) => write!(f, "at least {start}"),
+            Value::AtMost(end) => write!(f, "up to {end}"),
             Value::Text(t) => t.fmt(f),
         }
     }
 }
 
-fn round_float(n: f64) -> f64 {
+pub(crate) fn round_float(n: f64) -> f64 {
     (n * 1000.0).round() / 1000.0
 }
 
@@ -336,10 +433,14 @@ impl Display for Number {
                 num,
                 den,
                 err,
+                negative,
             } => {
                 if self.value() == 0.0 {
                     return write!(f, "{}", 0.0);
                 }
+                if negative {
+                    f.write_str("-")?;
+                }
 
                 match (whole, num, den) {
                     (0, 0, _) => write!(f, "{}", 0.0),
@@ -363,6 +464,241 @@ impl Display for QuantityUnit {
     }
 }
 
+/// Options to format a quantity with [`Quantity::display_with`]
+///
+/// The default matches the [`Display`] implementation: `.` as decimal
+/// separator and no thousands grouping.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FormatOptions {
+    /// Decimal separator
+    pub decimal_sep: char,
+    /// Thousands separator, if any
+    pub thousands_sep: Option<char>,
+    /// How to display fractions
+    pub fraction_style: FractionStyle,
+    /// How to display known units
+    pub unit_style: UnitStyle,
+    /// Denominator for [`FractionStyle::Auto`], the values are rounded to
+    /// the closest multiple of `1/denominator` in the units with fractions
+    ///
+    /// Recipes with [`Metadata::fraction_denominator`] already have their
+    /// values rounded, so this is only needed to override it.
+    ///
+    /// [`Metadata::fraction_denominator`]: crate::metadata::Metadata::fraction_denominator
+    pub fraction_denominator: Option<u8>,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        Self {
+            decimal_sep: '.',
+            thousands_sep: None,
+            fraction_style: FractionStyle::default(),
+            unit_style: UnitStyle::default(),
+            fraction_denominator: None,
+        }
+    }
+}
+
+/// How to display fractions. See [`FormatOptions`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FractionStyle {
+    /// Fractions are displayed as fractions and decimals as decimals
+    #[default]
+    Keep,
+    /// Always display decimals
+    Decimal,
+    /// Like [`Self::Keep`], but decimals are displayed as fractions if the
+    /// converter fractions configuration for the unit allows it
+    Auto,
+}
+
+/// How to display units. See [`FormatOptions`]
+///
+/// Unknown units are always displayed as written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UnitStyle {
+    /// The unit as written in the recipe
+    #[default]
+    AsWritten,
+    /// The first symbol of the unit, like `g`
+    Symbol,
+    /// The name of the unit, like `grams`
+    ///
+    /// The first name is used for a value of exactly 1 and the second one, if
+    /// any, for other values. Units files list the singular and then the
+    /// plural.
+    Name,
+}
+
+impl ScaledQuantity {
+    /// Get a [`Display`] implementation with custom formatting options
+    ///
+    /// ```
+    /// # use cooklang::{Converter, Quantity, Value};
+    /// # use cooklang::quantity::FormatOptions;
+    /// let converter = Converter::bundled();
+    /// let q = Quantity::new(Value::from(1250.5), Some("g".into()));
+    /// let options = FormatOptions {
+    ///     decimal_sep: ',',
+    ///     thousands_sep: Some('.'),
+    ///     ..Default::default()
+    /// };
+    /// assert_eq!(q.display_with(&converter, options).to_string(), "1.250,5 g");
+    /// ```
+    pub fn display_with<'a>(
+        &'a self,
+        converter: &'a Converter,
+        options: FormatOptions,
+    ) -> QuantityDisplay<'a> {
+        QuantityDisplay {
+            quantity: self,
+            converter,
+            options,
+        }
+    }
+}
+
+/// See [`Quantity::display_with`]
+pub struct QuantityDisplay<'a> {
+    quantity: &'a ScaledQuantity,
+    converter: &'a Converter,
+    options: FormatOptions,
+}
+
+impl Display for QuantityDisplay<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let fitted;
+        let mut quantity = self.quantity;
+        // values that already are fractions are kept, unless a denominator is
+        // given, so the ones of a recipe with a fraction denominator stay
+        let auto = match self.options.fraction_denominator {
+            Some(_) => true,
+            None => !self.quantity.value.has_fraction(),
+        };
+        if self.options.fraction_style == FractionStyle::Auto && auto {
+            let mut q = self.quantity.clone();
+            if q.try_fraction_impl(self.converter, self.options.fraction_denominator) {
+                fitted = q;
+                quantity = &fitted;
+            }
+        }
+
+        match &quantity.value {
+            Value::Number(n) => fmt_number(*n, &self.options, f)?,
+            Value::Range { start, end } => {
+                fmt_number(*start, &self.options, f)?;
+                f.write_str("-")?;
+                fmt_number(*end, &self.options, f)?;
+            }
+            Value::AtLeast(start) => {
+                f.write_str("at least ")?;
+                fmt_number(*start, &self.options, f)?;
+            }
+            Value::AtMost(end) => {
+                f.write_str("up to ")?;
+                fmt_number(*end, &self.options, f)?;
+            }
+            Value::Text(t) => f.write_str(t)?,
+        }
+        if let Some(unit) = &quantity.unit {
+            f.write_str(" ")?;
+            let known = match self.options.unit_style {
+                UnitStyle::AsWritten => None,
+                _ => match unit.unit_info_or_parse(self.converter) {
+                    UnitInfo::Known(u) => Some(u),
+                    UnitInfo::Unknown => None,
+                },
+            };
+            let styled = known.and_then(|u| match self.options.unit_style {
+                UnitStyle::AsWritten => None,
+                UnitStyle::Symbol => u.symbols.first().cloned(),
+                UnitStyle::Name => {
+                    let singular = matches!(quantity.value, Value::Number(n) if n.value() == 1.0);
+                    let name = if singular { None } else { u.names.get(1) };
+                    name.or(u.names.first()).cloned()
+                }
+            });
+            match styled {
+                Some(text) => f.write_str(&text)?,
+                None => unit.fmt(f)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+fn fmt_number(
+    n: Number,
+    options: &FormatOptions,
+    f: &mut std::fmt::Formatter<'_>,
+) -> std::fmt::Result {
+    match n {
+        Number::Fraction {
+            whole,
+            num,
+            den,
+            negative,
+            ..
+        } if options.fraction_style != FractionStyle::Decimal && n.value() != 0.0 => {
+            if negative {
+                f.write_str("-")?;
+            }
+            match (whole, num) {
+                (0, 0) => f.write_str("0"),
+                (0, num) => write!(f, "{num}/{den}"),
+                (whole, 0) => fmt_grouped(&whole.to_string(), options.thousands_sep, f),
+                (whole, num) => {
+                    fmt_grouped(&whole.to_string(), options.thousands_sep, f)?;
+                    write!(f, " {num}/{den}")
+                }
+            }
+        }
+        _ => {
+            let s = round_float(n.value()).to_string();
+            let (int, dec) = match s.split_once('.') {
+                Some((int, dec)) => (int, Some(dec)),
+                None => (s.as_str(), None),
+            };
+            let int = match int.strip_prefix('-') {
+                Some(int) => {
+                    f.write_str("-")?;
+                    int
+                }
+                None => int,
+            };
+            fmt_grouped(int, options.thousands_sep, f)?;
+            if let Some(dec) = dec {
+                write!(f, "{}{dec}", options.decimal_sep)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Writes the integer digits with a separator every 3 of them
+fn fmt_grouped(
+    digits: &str,
+    sep: Option<char>,
+    f: &mut std::fmt::Formatter<'_>,
+) -> std::fmt::Result {
+    let Some(sep) = sep else {
+        return f.write_str(digits);
+    };
+    let first = match digits.len() % 3 {
+        0 => 3,
+        r => r,
+    };
+    let (head, mut rest) = digits.split_at(first.min(digits.len()));
+    f.write_str(head)?;
+    while !rest.is_empty() {
+        let (group, tail) = rest.split_at(3);
+        write!(f, "{sep}{group}")?;
+        rest = tail;
+    }
+    Ok(())
+}
+
 impl From<f64> for Value {
     fn from(value: f64) -> Self {
         Self::Number(Number::Regular(value))
@@ -375,6 +711,95 @@ impl From<String> for Value {
     }
 }
 
+impl From<RangeInclusive<f64>> for Value {
+    fn from(value: RangeInclusive<f64>) -> Self {
+        let (start, end) = value.into_inner();
+        Self::Range {
+            start: start.into(),
+            end: end.into(),
+        }
+    }
+}
+
+/// Error when a [`Value`] is not a [`Value::Number`]
+#[derive(Debug, Error, Clone)]
+#[error("The value is not a number")]
+pub struct NotANumberError(pub Value);
+
+impl TryFrom<&Value> for f64 {
+    type Error = NotANumberError;
+
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        value
+            .as_number()
+            .ok_or_else(|| NotANumberError(value.clone()))
+    }
+}
+
+impl Value {
+    /// Get the number if the value is [`Value::Number`]
+    pub fn as_number(&self) -> Option<f64> {
+        match self {
+            Value::Number(n) => Some(n.value()),
+            _ => None,
+        }
+    }
+
+    /// Get the range if the value is [`Value::Range`]
+    pub fn as_range(&self) -> Option<RangeInclusive<f64>> {
+        match self {
+            Value::Range { start, end } => Some(start.value()..=end.value()),
+            _ => None,
+        }
+    }
+
+    /// Get the lower bound if the value is [`Value::AtLeast`]
+    pub fn as_at_least(&self) -> Option<f64> {
+        match self {
+            Value::AtLeast(start) => Some(start.value()),
+            _ => None,
+        }
+    }
+
+    /// Get the upper bound if the value is [`Value::AtMost`]
+    pub fn as_at_most(&self) -> Option<f64> {
+        match self {
+            Value::AtMost(end) => Some(end.value()),
+            _ => None,
+        }
+    }
+
+    /// Checks if any number of the value is a [`Number::Fraction`]
+    pub(crate) fn has_fraction(&self) -> bool {
+        let is_fraction = |n: &Number| matches!(n, Number::Fraction { .. });
+        match self {
+            Value::Number(n) | Value::AtLeast(n) | Value::AtMost(n) => is_fraction(n),
+            Value::Range { start, end } => is_fraction(start) || is_fraction(end),
+            Value::Text(_) => false,
+        }
+    }
+
+    /// Rounds every number of the value, see [`Number::new_snapped`]
+    pub(crate) fn try_snap(&mut self, den: u8, max_whole: u32) -> bool {
+        match self {
+            Value::Number(n) | Value::AtLeast(n) | Value::AtMost(n) => n.try_snap(den, max_whole),
+            Value::Range { start, end } => {
+                // both, no short circuit
+                start.try_snap(den, max_whole) | end.try_snap(den, max_whole)
+            }
+            Value::Text(_) => false,
+        }
+    }
+
+    /// Get the text if the value is [`Value::Text`]
+    pub fn as_text(&self) -> Option<&str> {
+        match self {
+            Value::Text(t) => Some(t),
+            _ => None,
+        }
+    }
+}
+
 /// Error during adding of quantities
 #[derive(Debug, Error)]
 pub enum QuantityAddError {
@@ -402,6 +827,8 @@ pub enum IncompatibleUnits {
     },
     #[error("Unknown units differ: '{a}' '{b}'")]
     UnknownDifferentUnits { a: String, b: String },
+    #[error("Different count units: '{a}' '{b}'")]
+    DifferentCountUnits { a: String, b: String },
 }
 
 impl<V: QuantityValue> Quantity<V> {
@@ -439,6 +866,12 @@ impl<V: QuantityValue> Quantity<V> {
                                 b: b_unit.physical_quantity,
                             });
                         }
+                        if a_unit.physical_quantity == PhysicalQuantity::Count && a_unit != b_unit {
+                            return Err(IncompatibleUnits::DifferentCountUnits {
+                                a: a.text.clone(),
+                                b: b.text.clone(),
+                            });
+                        }
                         // common unit is first one
                         Some(a_unit)
                     }
@@ -457,6 +890,67 @@ impl<V: QuantityValue> Quantity<V> {
         };
         Ok(base)
     }
+
+    /// Get the [`PhysicalQuantity`] of the quantity
+    ///
+    /// A numeric quantity without unit is a [`PhysicalQuantity::Count`], like
+    /// `@eggs{2}`. [`None`] if the value is text or the unit is unknown.
+    pub fn physical_quantity(&self, converter: &Converter) -> Option<PhysicalQuantity> {
+        if self.value.is_text() {
+            return None;
+        }
+        match self.unit.as_ref().map(|u| u.unit_info_or_parse(converter)) {
+            None => Some(PhysicalQuantity::Count),
+            Some(UnitInfo::Known(unit)) => Some(unit.physical_quantity),
+            Some(UnitInfo::Unknown) => None,
+        }
+    }
+
+    /// Replaces the unit text with the symbol of the unit
+    ///
+    /// Different names for the same unit, like `grams` and `g`, end up with
+    /// the same text. Unknown units are left unchanged.
+    ///
+    /// ```
+    /// # use cooklang::{Converter, Quantity, Value};
+    /// let converter = Converter::bundled();
+    /// let mut q = Quantity::new(Value::from(200.0), Some("grams".into()));
+    /// q.canonicalize_unit(&converter);
+    /// assert_eq!(q.unit_text(), Some("g"));
+    /// ```
+    pub fn canonicalize_unit(&mut self, converter: &Converter) {
+        if let Some(unit) = &mut self.unit {
+            if let UnitInfo::Known(info) = unit.unit_info_or_parse(converter) {
+                unit.text = info.symbol().to_string();
+            }
+        }
+    }
+
+    /// Checks if two quantities can be added or converted to each other
+    ///
+    /// This is `false` if any value is text or any unit is unknown. The units
+    /// have to be of the same [`PhysicalQuantity`] and, for counts, the same
+    /// unit. Two quantities without units are compatible. No conversion is
+    /// performed.
+    pub fn is_compatible_with(&self, other: &Self, converter: &Converter) -> bool {
+        if self.value.is_text() || other.value.is_text() {
+            return false;
+        }
+        match (&self.unit, &other.unit) {
+            (None, None) => true,
+            (Some(a), Some(b)) => match (
+                a.unit_info_or_parse(converter),
+                b.unit_info_or_parse(converter),
+            ) {
+                (UnitInfo::Known(a), UnitInfo::Known(b)) => {
+                    a.physical_quantity == b.physical_quantity
+                        && (a.physical_quantity != PhysicalQuantity::Count || a == b)
+                }
+                _ => false,
+            },
+            _ => false,
+        }
+    }
 }
 
 impl ScaledQuantity {
@@ -478,10 +972,53 @@ impl ScaledQuantity {
         let qty = Quantity {
             value,
             unit: self.unit.clone(), // unit is mantained
+            decimal: self.decimal || rhs.decimal,
+            source: None,
         };
 
         Ok(qty)
     }
+
+    /// Compare two quantities after converting them to a common unit
+    ///
+    /// Returns [`None`] if the units are not compatible or any value is text.
+    /// A range is only less or greater than other value if they don't
+    /// overlap.
+    ///
+    /// ```
+    /// # use cooklang::{Converter, quantity::{Quantity, Value}};
+    /// # use std::cmp::Ordering;
+    /// let converter = Converter::bundled();
+    /// let kg = Quantity::new(Value::from(1.0), Some("kg".to_string()));
+    /// let g = Quantity::new(Value::from(500.0), Some("g".to_string()));
+    /// assert_eq!(kg.partial_cmp_in(&g, &converter), Some(Ordering::Greater));
+    /// ```
+    pub fn partial_cmp_in(&self, other: &Self, converter: &Converter) -> Option<Ordering> {
+        let convert_to = self.compatible_unit(other, converter).ok()?;
+        let mut other = other.clone();
+        if let Some(to) = convert_to {
+            other.convert(&to, converter).ok()?;
+        }
+        let bounds = |v: &Value| match v {
+            Value::Number(n) => Some((n.value(), n.value())),
+            Value::Range { start, end } => Some((start.value(), end.value())),
+            Value::AtLeast(start) => Some((start.value(), f64::INFINITY)),
+            // amounts are not negative
+            Value::AtMost(end) => Some((0.0, end.value())),
+            Value::Text(_) => None,
+        };
+        let (a_start, a_end) = bounds(&self.value)?;
+        let (b_start, b_end) = bounds(&other.value)?;
+        if a_start == b_start && a_end == b_end {
+            Some(Ordering::Equal)
+        } else if a_end < b_start {
+            Some(Ordering::Less)
+        } else if a_start > b_end {
+            Some(Ordering::Greater)
+        } else {
+            None
+        }
+    }
 }
 
 pub trait TryAdd: Sized {
@@ -512,6 +1049,31 @@ impl TryAdd for Value {
                     end: (e1.value() + e2.value()).into(),
                 }
             }
+            // adding anything to an open range keeps it open
+            (Value::AtLeast(a), Value::Number(b) | Value::AtLeast(b))
+            | (Value::Number(b), Value::AtLeast(a)) => {
+                Value::AtLeast((a.value() + b.value()).into())
+            }
+            (Value::AtLeast(a), Value::Range { start, .. })
+            | (Value::Range { start, .. }, Value::AtLeast(a)) => {
+                Value::AtLeast((a.value() + start.value()).into())
+            }
+            // the missing lower bound is 0, amounts are not negative
+            (Value::AtLeast(a), Value::AtMost(_)) | (Value::AtMost(_), Value::AtLeast(a)) => {
+                Value::AtLeast(*a)
+            }
+            (Value::AtMost(a), Value::AtMost(b)) => Value::AtMost((a.value() + b.value()).into()),
+            (Value::AtMost(a), Value::Number(n)) | (Value::Number(n), Value::AtMost(a)) => {
+                Value::Range {
+                    start: *n,
+                    end: (n.value() + a.value()).into(),
+                }
+            }
+            (Value::AtMost(a), Value::Range { start, end })
+            | (Value::Range { start, end }, Value::AtMost(a)) => Value::Range {
+                start: *start,
+                end: (end.value() + a.value()).into(),
+            },
             (t @ Value::Text(_), _) | (_, t @ Value::Text(_)) => {
                 return Err(TextValueError(t.to_owned()));
             }
@@ -537,6 +1099,8 @@ impl TryAdd for Value {
 pub struct GroupedQuantity {
     /// known units
     known: EnumMap<PhysicalQuantity, Option<ScaledQuantity>>,
+    /// known count units, one per unit as they can't be converted
+    counts: Vec<ScaledQuantity>,
     /// unknown units
     unknown: HashMap<String, ScaledQuantity>,
     /// no units
@@ -581,6 +1145,17 @@ impl GroupedQuantity {
         let unit = q.unit.as_ref().unwrap();
         let info = unit.unit_info_or_parse(converter);
         match info {
+            UnitInfo::Known(unit) if unit.physical_quantity == PhysicalQuantity::Count => {
+                if let Some(stored) = self
+                    .counts
+                    .iter_mut()
+                    .find(|s| s.is_compatible_with(q, converter))
+                {
+                    add!(stored, q, converter, self.other);
+                } else {
+                    self.counts.push(q.clone());
+                }
+            }
             UnitInfo::Known(unit) => {
                 if let Some(stored) = &mut self.known[unit.physical_quantity] {
                     add!(stored, q, converter, self.other);
@@ -628,6 +1203,7 @@ impl GroupedQuantity {
         self.known
             .values()
             .filter_map(|q| q.as_ref())
+            .chain(self.counts.iter())
             .chain(self.unknown.values())
             .chain(self.other.iter())
             .chain(self.no_unit.iter())
@@ -635,6 +1211,7 @@ impl GroupedQuantity {
 
     pub fn len(&self) -> usize {
         self.known.values().filter(|q| q.is_some()).count()
+            + self.counts.len()
             + self.unknown.len()
             + self.other.len()
             + (self.no_unit.is_some() as usize)
@@ -648,6 +1225,7 @@ impl GroupedQuantity {
             .known
             .into_values()
             .flatten()
+            .chain(self.counts)
             .chain(self.unknown.into_values())
             .chain(self.other.into_iter())
             .chain(self.no_unit.into_iter())
@@ -823,7 +1401,9 @@ impl Number {
     /// It returns none if:
     /// - The value is an integer
     /// - It can't be represented with the given restrictions as a fraction.
-    /// - The number is not positive.
+    /// - The number is zero.
+    ///
+    /// Negative numbers are approximated like their absolute value.
     ///
     /// It will return `Number::Regular` when the number is an integer with less
     /// than a 1e-10 margin of error.
@@ -831,7 +1411,11 @@ impl Number {
     /// Otherwise it will return a `Number::Fraction`. `num` can be 0 if the
     /// value is rounded to an integer.
     ///
-    /// `accuracy` is a value between 0 and 1 representing the error percent.
+    /// `accuracy` is a value between 0 and 1 representing the max error
+    /// allowed relative to `value`. The value is only approximated if the
+    /// difference with the fraction is within `accuracy * value`. For example,
+    /// with `0.05` and `max_den` 3, `0.33` is `1/3` but `0.3` is not
+    /// approximated because `1/3` is more than a 5% off.
     ///
     /// `max_den` is the maximum denominator. The denominator is one a list of
     /// "common" fractions: 2, 3, 4, 5, 8, 10, 16, 32, 64. 64 is the max.
@@ -846,7 +1430,10 @@ impl Number {
     pub fn new_approx(value: f64, accuracy: f32, max_den: u8, max_whole: u32) -> Option<Self> {
         assert!((0.0..=1.0).contains(&accuracy));
         assert!(max_den <= 64);
-        if value <= 0.0 || !value.is_finite() {
+        if value < 0.0 {
+            return Self::new_approx(-value, accuracy, max_den, max_whole).map(|n| -n);
+        }
+        if value == 0.0 || !value.is_finite() {
             return None;
         }
 
@@ -871,6 +1458,7 @@ impl Number {
                 num: 0,
                 den: 1,
                 err: round_err,
+                negative: false,
             });
         }
 
@@ -885,6 +1473,7 @@ impl Number {
             num: num as u32,
             den: den as u32,
             err,
+            negative: false,
         })
     }
 
@@ -899,6 +1488,58 @@ impl Number {
             None => false,
         }
     }
+
+    /// Rounds the number to the closest multiple of `1/den`, without an
+    /// accuracy limit
+    ///
+    /// Returns [`None`] if it would round to 0 or the whole part is bigger
+    /// than `max_whole`.
+    pub(crate) fn new_snapped(value: f64, den: u8, max_whole: u32) -> Option<Self> {
+        if value < 0.0 {
+            return Self::new_snapped(-value, den, max_whole).map(|n| -n);
+        }
+        if value == 0.0 || !value.is_finite() || den == 0 {
+            return None;
+        }
+        if value.fract() < 1e-10 {
+            return Some(Self::Regular(value));
+        }
+
+        let den = den as u32;
+        let steps = (value * den as f64).round() as u32;
+        let whole = steps / den;
+        if steps == 0 || whole > max_whole {
+            return None;
+        }
+        let num = steps % den;
+        let gcd = {
+            let (mut a, mut b) = (num, den);
+            while b != 0 {
+                (a, b) = (b, a % b);
+            }
+            a
+        };
+        let (num, den) = (num / gcd, den / gcd);
+        let err = value - (whole as f64 + num as f64 / den as f64);
+        Some(Self::Fraction {
+            whole,
+            num,
+            den,
+            err,
+            negative: false,
+        })
+    }
+
+    /// Same as [`Self::try_approx`] but with [`Self::new_snapped`]
+    pub(crate) fn try_snap(&mut self, den: u8, max_whole: u32) -> bool {
+        match Self::new_snapped(self.value(), den, max_whole) {
+            Some(f) => {
+                *self = f;
+                true
+            }
+            None => false,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -906,6 +1547,61 @@ mod tests {
     use super::*;
     use test_case::test_case;
 
+    #[test]
+    fn value_conversions() {
+        let num = Value::from(2.5);
+        assert_eq!(num.as_number(), Some(2.5));
+        assert_eq!(f64::try_from(&num).unwrap(), 2.5);
+        assert_eq!(num.as_range(), None);
+
+        let range = Value::from(1.0..=3.0);
+        assert_eq!(range.as_range(), Some(1.0..=3.0));
+        assert_eq!(range.as_number(), None);
+        assert!(f64::try_from(&range).is_err());
+
+        let text = Value::from("a pinch".to_string());
+        assert_eq!(text.as_text(), Some("a pinch"));
+        assert_eq!(text.as_number(), None);
+        let err = f64::try_from(&text).unwrap_err();
+        assert_eq!(err.0, text);
+    }
+
+    #[test]
+    fn at_least_value() {
+        let open = Value::AtLeast(2.0.into());
+        assert_eq!(open.to_string(), "at least 2");
+        assert_eq!(open.as_at_least(), Some(2.0));
+        assert_eq!(open.as_range(), None);
+        assert_eq!(
+            open.try_add(&Value::from(1.0)).unwrap(),
+            Value::AtLeast(3.0.into())
+        );
+        assert_eq!(
+            Value::from(1.0..=2.0).try_add(&open).unwrap(),
+            Value::AtLeast(3.0.into())
+        );
+    }
+
+    #[test]
+    fn at_most_value() {
+        let open = Value::AtMost(3.0.into());
+        assert_eq!(open.to_string(), "up to 3");
+        assert_eq!(open.as_at_most(), Some(3.0));
+        assert_eq!(open.as_range(), None);
+        assert_eq!(
+            open.try_add(&Value::from(1.0)).unwrap(),
+            Value::from(1.0..=4.0)
+        );
+        assert_eq!(
+            open.try_add(&Value::AtMost(2.0.into())).unwrap(),
+            Value::AtMost(5.0.into())
+        );
+        assert_eq!(
+            open.try_add(&Value::AtLeast(2.0.into())).unwrap(),
+            Value::AtLeast(2.0.into())
+        );
+    }
+
     macro_rules! frac {
         ($whole:expr) => {
             frac!($whole, 0, 1)
@@ -939,4 +1635,151 @@ mod tests {
         }
         num
     }
+
+    #[test_case(0.3 => matches frac!(1, 4) ; "closest eighth")]
+    #[test_case(0.4 => matches frac!(3, 8) ; "eighth")]
+    #[test_case(1.3 => matches frac!(1, 1, 4) ; "with whole")]
+    #[test_case(2.0 => matches Some(Number::Regular(v)) if v == 2.0 ; "integer")]
+    #[test_case(0.05 => None ; "no snap to 0")]
+    #[test_case(-0.4 => matches Some(Number::Fraction { num: 3, den: 8, negative: true, .. }) ; "negative")]
+    fn snapped_fractions(value: f64) -> Option<Number> {
+        let num = Number::new_snapped(value, 8, u32::MAX);
+        if let Some(num) = num {
+            assert!((num.value() - value).abs() < 10e-9);
+        }
+        num
+    }
+
+    #[test_case(0.33, 0.05, 3 => matches frac!(1, 3) ; "within accuracy")]
+    #[test_case(0.30, 0.05, 3 => None ; "outside accuracy")]
+    #[test_case(0.30, 0.15, 3 => matches frac!(1, 3) ; "wider accuracy")]
+    #[test_case(0.26, 0.05, 3 => None ; "denominator too big")]
+    #[test_case(0.26, 0.05, 4 => matches frac!(1, 4) ; "denominator allowed")]
+    #[test_case(0.5, 0.0, 2 => matches frac!(1, 2) ; "exact with zero accuracy")]
+    fn fraction_accuracy(value: f64, accuracy: f32, max_den: u8) -> Option<Number> {
+        Number::new_approx(value, accuracy, max_den, u32::MAX)
+    }
+
+    #[test_case(Value::from(1.5), '.', None => "1.5 g"; "default")]
+    #[test_case(Value::from(1250.5), ',', Some('.') => "1.250,5 g"; "european")]
+    #[test_case(Value::from(1234567.0), '.', Some(',') => "1,234,567 g"; "many groups")]
+    #[test_case(Value::from(123.0), '.', Some(',') => "123 g"; "no group")]
+    #[test_case(Value::from(-1500.25), ',', Some(' ') => "-1 500,25 g"; "negative")]
+    #[test_case(Value::Range { start: 1000.5.into(), end: 2000.5.into() }, ',', Some('.') => "1.000,5-2.000,5 g"; "range")]
+    #[test_case(Value::Number(Number::Fraction { whole: 1000, num: 1, den: 2, err: 0.0, negative: false }), ',', Some('.') => "1.000 1/2 g"; "fraction")]
+    fn display_with_separators(
+        value: Value,
+        decimal_sep: char,
+        thousands_sep: Option<char>,
+    ) -> String {
+        let converter = Converter::empty();
+        let q = Quantity::new(value, Some("g".into()));
+        let options = FormatOptions {
+            decimal_sep,
+            thousands_sep,
+            ..Default::default()
+        };
+        q.display_with(&converter, options).to_string()
+    }
+
+    #[test_case("grams", UnitStyle::AsWritten => "200 grams"; "as written")]
+    #[test_case("grams", UnitStyle::Symbol => "200 g"; "symbol")]
+    #[test_case("g", UnitStyle::Name => "200 grams"; "name")]
+    #[test_case("handful", UnitStyle::Symbol => "200 handful"; "unknown")]
+    fn display_with_unit_style(unit: &str, unit_style: UnitStyle) -> String {
+        let converter = Converter::bundled();
+        let q = Quantity::new(Value::from(200.0), Some(unit.into()));
+        let options = FormatOptions {
+            unit_style,
+            ..Default::default()
+        };
+        q.display_with(&converter, options).to_string()
+    }
+
+    #[test]
+    fn display_with_singular_name() {
+        let converter = Converter::bundled();
+        let q = Quantity::new(Value::from(1.0), Some("kg".into()));
+        let options = FormatOptions {
+            unit_style: UnitStyle::Name,
+            ..Default::default()
+        };
+        assert_eq!(
+            q.display_with(&converter, options).to_string(),
+            "1 kilogram"
+        );
+    }
+
+    #[test]
+    fn display_with_fraction_style() {
+        let converter = Converter::bundled();
+        let frac = Quantity::new(
+            Value::Number(Number::Fraction {
+                whole: 1,
+                num: 1,
+                den: 2,
+                err: 0.0,
+                negative: false,
+            }),
+            Some("cup".into()),
+        );
+        let decimal = Quantity::new(Value::from(1.5), Some("cup".into()));
+        let t = |q: &ScaledQuantity, fraction_style| {
+            let options = FormatOptions {
+                fraction_style,
+                ..Default::default()
+            };
+            q.display_with(&converter, options).to_string()
+        };
+        assert_eq!(t(&frac, FractionStyle::Keep), "1 1/2 cup");
+        assert_eq!(t(&frac, FractionStyle::Decimal), "1.5 cup");
+        assert_eq!(t(&decimal, FractionStyle::Keep), "1.5 cup");
+        assert_eq!(t(&decimal, FractionStyle::Auto), "1 1/2 cup");
+        assert_eq!(
+            decimal
+                .display_with(&converter, FormatOptions::default())
+                .to_string(),
+            decimal.to_string()
+        );
+    }
+
+    #[test_case(Value::from(1.0), Some("g"), Value::from(2.0), Some("kg") => true ; "same physical quantity")]
+    #[test_case(Value::from(1.0), Some("g"), Value::from(2.0), Some("ml") => false ; "different physical quantity")]
+    #[test_case(Value::from(1.0), None, Value::from(2.0), None => true ; "no units")]
+    #[test_case(Value::from(1.0), Some("g"), Value::from(2.0), None => false ; "missing unit")]
+    #[test_case(Value::from(1.0), Some("pinch"), Value::from(2.0), Some("pinch") => false ; "unknown unit")]
+    #[test_case(Value::from("some".to_string()), Some("g"), Value::from(2.0), Some("g") => false ; "text")]
+    fn is_compatible_with(a: Value, a_unit: Option<&str>, b: Value, b_unit: Option<&str>) -> bool {
+        let converter = Converter::bundled();
+        let a = Quantity::new(a, a_unit.map(String::from));
+        let b = Quantity::new(b, b_unit.map(String::from));
+        a.is_compatible_with(&b, &converter)
+    }
+
+    #[test_case("grams" => "g" ; "name")]
+    #[test_case("kilograms" => "kg" ; "prefixed name")]
+    #[test_case("oz." => "oz" ; "other symbol")]
+    #[test_case("ml" => "ml" ; "symbol")]
+    #[test_case("pinch" => "pinch" ; "unknown")]
+    fn canonicalize_unit(unit: &str) -> String {
+        let converter = Converter::bundled();
+        let mut q = Quantity::new(Value::from(200.0), Some(unit.to_string()));
+        q.canonicalize_unit(&converter);
+        q.unit_text().unwrap().to_string()
+    }
+
+    #[test_case(Value::from(1.0), "kg", Value::from(500.0), "g" => Some(Ordering::Greater) ; "kg g")]
+    #[test_case(Value::from(1.0), "cup", Value::from(2.0), "tbsp" => Some(Ordering::Greater) ; "cup tbsp")]
+    #[test_case(Value::from(2.0), "tbsp", Value::from(1.0), "cup" => Some(Ordering::Less) ; "tbsp cup")]
+    #[test_case(Value::from(1.0), "kg", Value::from(1000.0), "g" => Some(Ordering::Equal) ; "equal")]
+    #[test_case(Value::from(1.0), "kg", Value::from(1.0), "l" => None ; "incompatible")]
+    #[test_case(Value::from("some".to_string()), "g", Value::from(2.0), "g" => None ; "text")]
+    #[test_case(Value::from(1.0..=3.0), "g", Value::from(2.0), "g" => None ; "overlapping range")]
+    #[test_case(Value::from(1.0..=3.0), "g", Value::from(5.0), "g" => Some(Ordering::Less) ; "range")]
+    fn partial_cmp_in(a: Value, a_unit: &str, b: Value, b_unit: &str) -> Option<Ordering> {
+        let converter = Converter::bundled();
+        let a = Quantity::new(a, Some(a_unit.to_string()));
+        let b = Quantity::new(b, Some(b_unit.to_string()));
+        a.partial_cmp_in(&b, &converter)
+    }
 }
@@ -4,8 +4,11 @@ use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 use crate::{
-    convert::Converter,
-    quantity::{ScalableQuantity, ScalableValue, ScaledQuantity, TextValueError, Value},
+    convert::{Converter, PhysicalQuantity},
+    quantity::{
+        Number, QuantityValue, ScalableQuantity, ScalableValue, ScaledQuantity, TextValueError,
+        Value,
+    },
     Cookware, Ingredient, Quantity, ScalableRecipe, ScaledRecipe, Timer,
 };
 
@@ -15,6 +18,12 @@ pub struct ScaleTarget {
     base: u32,
     target: u32,
     index: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    factor: Option<f64>,
+    #[serde(default)]
+    rounding: RoundingPolicy,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    count_rounding: Option<RoundingPolicy>,
 }
 
 impl ScaleTarget {
@@ -33,12 +42,84 @@ impl ScaleTarget {
             base,
             target,
             index: declared_servings.iter().position(|&s| s == target),
+            factor: None,
+            rounding: RoundingPolicy::default(),
+            count_rounding: None,
+        }
+    }
+
+    /// Creates a new [`ScaleTarget`] with a custom factor, like `1.5`
+    ///
+    /// `base` is the number of servings the recipe was initially written
+    /// for. Values defined by servings can't be scaled with a factor.
+    pub fn from_factor(base: u32, factor: f64) -> Self {
+        ScaleTarget {
+            base,
+            target: (base as f64 * factor).round() as u32,
+            index: None,
+            factor: Some(factor),
+            rounding: RoundingPolicy::default(),
+            count_rounding: None,
+        }
+    }
+
+    /// Same as [`Self::new`] but the target may be fractional, like `2.5`
+    ///
+    /// If the target is whole, this is the same as [`Self::new`].
+    pub fn new_fractional(base: u32, target: f64, declared_servings: &[u32]) -> Self {
+        if target.fract() == 0.0 && (0.0..=u32::MAX as f64).contains(&target) {
+            Self::new(base, target as u32, declared_servings)
+        } else {
+            Self::from_factor(base, target / base as f64)
+        }
+    }
+
+    /// Set how the scaled values are rounded
+    pub fn with_rounding(mut self, rounding: RoundingPolicy) -> Self {
+        self.rounding = rounding;
+        self
+    }
+
+    /// Set how the scaled counts are rounded
+    ///
+    /// Counts are the quantities that are a [`PhysicalQuantity::Count`], like
+    /// `@eggs{2}` or `@garlic{2%cloves}`, and cookware amounts. For them,
+    /// this overrides [`Self::with_rounding`], so eggs can be whole while
+    /// `@flour{150%g}` keeps the decimals.
+    ///
+    /// ```
+    /// # use cooklang::{scale::{RoundingPolicy, ScaleTarget}, Converter, Value};
+    /// let recipe = cooklang::parse("Mix @eggs{2*} and @flour{150*%g}.").unwrap_output();
+    /// let target = ScaleTarget::from_factor(4, 1.25).with_count_rounding(RoundingPolicy::Up);
+    /// let recipe = recipe.scale_to_target(target, &Converter::bundled());
+    /// assert_eq!(recipe.ingredients[0].quantity.as_ref().unwrap().value, Value::from(3.0));
+    /// assert_eq!(recipe.ingredients[1].quantity.as_ref().unwrap().value, Value::from(187.5));
+    /// ```
+    pub fn with_count_rounding(mut self, rounding: RoundingPolicy) -> Self {
+        self.count_rounding = Some(rounding);
+        self
+    }
+
+    /// Target used to scale a quantity, with the count rounding if needed
+    fn for_quantity<V: QuantityValue>(
+        self,
+        quantity: Option<&Quantity<V>>,
+        converter: &Converter,
+    ) -> Self {
+        match self.count_rounding {
+            Some(rounding)
+                if quantity.and_then(|q| q.physical_quantity(converter))
+                    == Some(PhysicalQuantity::Count) =>
+            {
+                self.with_rounding(rounding)
+            }
+            _ => self,
         }
     }
 
     /// Get the calculated scaling factor
     pub fn factor(&self) -> f64 {
-        self.target as f64 / self.base as f64
+        self.factor.unwrap_or(self.target as f64 / self.base as f64)
     }
 
     /// Get the index into a [`ScalableValue::ByServings`]
@@ -47,13 +128,72 @@ impl ScaleTarget {
     }
 
     /// Get the target servings
+    ///
+    /// If the target was created with a factor or it's fractional, this is
+    /// rounded to the nearest integer.
     pub fn target_servings(&self) -> u32 {
         self.target
     }
+
+    /// Get how the scaled values are rounded
+    pub fn rounding(&self) -> RoundingPolicy {
+        self.rounding
+    }
+
+    /// Get how the scaled counts are rounded, see [`Self::with_count_rounding`]
+    pub fn count_rounding(&self) -> RoundingPolicy {
+        self.count_rounding.unwrap_or(self.rounding)
+    }
+}
+
+/// How to round scaled values that were integers in the recipe
+///
+/// If the scaled value is whole, it's always kept exact. Other numbers, like
+/// `1.5`, are not rounded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RoundingPolicy {
+    /// Keep the decimals, `2` scaled by `1.3` is `2.6`
+    #[default]
+    Decimal,
+    /// Round to the nearest integer, `2` scaled by `1.3` is `3`
+    Nearest,
+    /// Round up, `2` scaled by `1.1` is `3`
+    Up,
+    /// Round down, `2` scaled by `1.3` is `2`
+    ///
+    /// A positive value is never rounded to `0`, it will be `1`.
+    Down,
+}
+
+impl RoundingPolicy {
+    fn scale(self, n: Number, factor: f64) -> Number {
+        let scaled = n.value() * factor;
+        let is_integer = matches!(n, Number::Regular(v) if v.fract() == 0.0);
+        if !is_integer {
+            return scaled.into();
+        }
+        let whole = scaled.round();
+        // avoid floating point errors, like 3 * (1/3)
+        if (scaled - whole).abs() < 1e-9 {
+            return whole.into();
+        }
+        let rounded = match self {
+            RoundingPolicy::Decimal => return scaled.into(),
+            RoundingPolicy::Nearest => whole,
+            RoundingPolicy::Up => scaled.ceil(),
+            RoundingPolicy::Down => scaled.floor(),
+        };
+        if rounded == 0.0 && scaled > 0.0 {
+            1.0.into()
+        } else {
+            rounded.into()
+        }
+    }
 }
 
 /// Possible scaled states of a recipe
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type")]
 pub enum Scaled {
     /// The recipe was scaled to its based servings
@@ -66,7 +206,7 @@ pub enum Scaled {
 }
 
 /// Data from scaling a recipe
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ScaledData {
     /// What the target was
     pub target: ScaleTarget,
@@ -76,6 +216,10 @@ pub struct ScaledData {
     pub cookware: Vec<ScaleOutcome>,
     /// Outcome of scaling the timers. Use the same index as in the recipe.
     pub timers: Vec<ScaleOutcome>,
+    /// Outcome of scaling the inline quantities. Use the same index as in the
+    /// recipe.
+    #[serde(default)]
+    pub inline_quantities: Vec<ScaleOutcome>,
 }
 
 /// Possible outcomes from scaling a component
@@ -110,6 +254,15 @@ pub enum ScaleError {
         value: ScalableValue,
     },
 
+    #[error("No value for servings index {index}, there are only {len}")]
+    ServingsOutOfRange { index: usize, len: usize },
+
+    #[error("Ingredient not found: {name}")]
+    IngredientNotFound { name: String },
+
+    #[error("Cannot scale to the ingredient quantity: {reason}")]
+    InvalidIngredientTarget { reason: String },
+
     /// There has been an error but it can't be determined
     ///
     /// This is used when deserializing, because serializing the [`ScaleOutcome`]
@@ -120,6 +273,13 @@ pub enum ScaleError {
 }
 
 impl ScalableRecipe {
+    fn base_servings(&self) -> u32 {
+        self.metadata
+            .servings()
+            .and_then(|s| s.first().copied())
+            .unwrap_or(1)
+    }
+
     /// Scale a recipe
     ///
     /// Note that this returns a [`ScaledRecipe`] wich doesn't implement this
@@ -131,25 +291,174 @@ impl ScalableRecipe {
         } else {
             ScaleTarget::new(1, target, &[])
         };
+        self.scale_to_target(target, converter)
+    }
+
+    /// Scale a recipe by a factor, like `1.5`
+    ///
+    /// Values defined by servings can't be scaled by a factor. To scale to
+    /// fractional servings or to change the [`RoundingPolicy`] use
+    /// [`Self::scale_to_target`].
+    pub fn scale_by(self, factor: f64, converter: &Converter) -> ScaledRecipe {
+        let base = self.base_servings();
+        self.scale_to_target(ScaleTarget::from_factor(base, factor), converter)
+    }
+
+    /// Scale the quantity of a single ingredient by a factor
+    ///
+    /// The recipe is not modified. The result is the same as the ingredient
+    /// quantity after [`Self::scale_by`], without scaling everything else.
+    /// Fixed values are not changed and values that fail to scale are
+    /// kept as written. Returns [`None`] if the index is out of bounds or the
+    /// ingredient has no quantity.
+    ///
+    /// ```
+    /// # use cooklang::{Converter, Value};
+    /// let recipe = cooklang::parse("Add @flour{200*%g} and @salt{1%tsp}.").unwrap_output();
+    /// let converter = Converter::bundled();
+    /// let flour = recipe.scaled_quantity(0, 2.0, &converter).unwrap();
+    /// assert_eq!(flour.to_string(), "400 g");
+    /// let salt = recipe.scaled_quantity(1, 2.0, &converter).unwrap();
+    /// assert_eq!(salt.value, Value::from(1.0));
+    /// ```
+    pub fn scaled_quantity(
+        &self,
+        ingredient_index: usize,
+        factor: f64,
+        converter: &Converter,
+    ) -> Option<ScaledQuantity> {
+        let quantity = self.ingredients.get(ingredient_index)?.quantity.as_ref()?;
+        let target = ScaleTarget::from_factor(self.base_servings(), factor)
+            .for_quantity(Some(quantity), converter);
+        let (mut scaled, _) = quantity.clone().scale(target);
+        let _ = scaled.fit(converter);
+        if let Some(den) = self.metadata.fraction_denominator() {
+            scaled.try_fraction_impl(converter, Some(den));
+        }
+        Some(scaled)
+    }
+
+    /// Scale a recipe so an ingredient has the target quantity
+    ///
+    /// The factor is the target divided by the current quantity of the first
+    /// ingredient definition with the name, after converting it to the target
+    /// unit. Then it's the same as [`Self::scale_by`], so only linear values
+    /// change, but the factor is computed even if the ingredient itself is
+    /// fixed. Useful to scale a dough to a flour weight.
+    ///
+    /// It's an error if the ingredient is missing, has no quantity, the
+    /// quantity or the target are not numbers or the units can't be
+    /// converted.
+    ///
+    /// ```
+    /// # use cooklang::{Converter, Quantity, Value};
+    /// let recipe = cooklang::parse("Mix @flour{500*%g} and @water{325*%ml}.").unwrap_output();
+    /// let target = Quantity::new(Value::from(1.0), Some("kg".into()));
+    /// let scaled = recipe
+    ///     .scale_to_ingredient("flour", target, &Converter::bundled())
+    ///     .unwrap();
+    /// let water = scaled.ingredients[1].quantity.as_ref().unwrap();
+    /// assert_eq!(water.to_string(), "650 ml");
+    /// ```
+    pub fn scale_to_ingredient(
+        self,
+        name: &str,
+        target: ScaledQuantity,
+        converter: &Converter,
+    ) -> Result<ScaledRecipe, ScaleError> {
+        let invalid = |reason: &str| ScaleError::InvalidIngredientTarget {
+            reason: reason.to_string(),
+        };
+        let number = |value: &Value| match value {
+            Value::Number(n) => Ok(n.value()),
+            Value::Text(_) => Err(ScaleError::from(TextValueError(value.clone()))),
+            Value::Range { .. } | Value::AtLeast(_) | Value::AtMost(_) => {
+                Err(invalid("the value is a range"))
+            }
+        };
 
+        let ingredient = self
+            .ingredients
+            .iter()
+            .find(|i| i.name == name && i.relation.is_definition())
+            .ok_or_else(|| ScaleError::IngredientNotFound {
+                name: name.to_string(),
+            })?;
+        let quantity = ingredient
+            .quantity
+            .as_ref()
+            .ok_or_else(|| invalid("the ingredient has no quantity"))?;
+        let value = match &quantity.value {
+            ScalableValue::Fixed(v) | ScalableValue::Linear(v) => v.clone(),
+            ScalableValue::ByServings(values) => values[0].clone(),
+        };
+        number(&value)?;
+        let target_value = number(&target.value)?;
+
+        let mut current = Quantity::new(value, quantity.unit_text().map(str::to_string));
+        match (target.unit_text(), current.unit_text()) {
+            (Some(unit), Some(_)) => current
+                .convert(unit, converter)
+                .map_err(|e| invalid(&e.to_string()))?,
+            (None, None) => {}
+            (Some(_), None) | (None, Some(_)) => {
+                return Err(invalid("only one of the quantities has a unit"))
+            }
+        }
+        let current_value = number(&current.value)?;
+        if current_value == 0.0 {
+            return Err(invalid("the ingredient quantity is zero"));
+        }
+
+        Ok(self.scale_by(target_value / current_value, converter))
+    }
+
+    /// Scale a recipe to a custom [`ScaleTarget`]
+    ///
+    /// ```
+    /// # use cooklang::{scale::{RoundingPolicy, ScaleTarget}, Converter, Value};
+    /// let recipe = cooklang::parse(">> servings: 2\nAdd @eggs{2*}.").unwrap_output();
+    /// let target = ScaleTarget::new_fractional(2, 2.5, &[2]).with_rounding(RoundingPolicy::Up);
+    /// let scaled = recipe.scale_to_target(target, &Converter::empty());
+    /// let eggs = scaled.ingredients[0].quantity.as_ref().unwrap();
+    /// assert_eq!(eggs.value, Value::from(3.0));
+    /// ```
+    pub fn scale_to_target(self, target: ScaleTarget, converter: &Converter) -> ScaledRecipe {
         if target.index() == Some(0) {
             return self.default_scale();
         }
 
+        // the values were rounded when parsing, round them again
+        let denominator = self.metadata.fraction_denominator();
+        let snap = |q: &mut ScaledQuantity| {
+            if denominator.is_some() {
+                q.try_fraction_impl(converter, denominator);
+            }
+        };
+
         let (ingredients, ingredient_outcomes): (Vec<_>, Vec<_>) = self
             .ingredients
             .into_iter()
-            .map(|i| i.scale(target))
+            .map(|i| {
+                let target = target.for_quantity(i.quantity.as_ref(), converter);
+                i.scale(target)
+            })
             .map(|(mut i, o)| {
                 if let Some(q) = &mut i.quantity {
                     let _ = q.fit(converter);
+                    snap(q);
                 }
                 (i, o)
             })
             .unzip();
 
-        let (cookware, cookware_outcomes): (Vec<_>, Vec<_>) =
-            self.cookware.into_iter().map(|c| c.scale(target)).unzip();
+        // cookware amounts are always counts
+        let cookware_target = target.with_rounding(target.count_rounding());
+        let (cookware, cookware_outcomes): (Vec<_>, Vec<_>) = self
+            .cookware
+            .into_iter()
+            .map(|c| c.scale(cookware_target))
+            .unzip();
 
         let (timers, timer_outcomes): (Vec<_>, Vec<_>) = self
             .timers
@@ -158,16 +467,28 @@ impl ScalableRecipe {
             .map(|(mut t, o)| {
                 if let Some(q) = &mut t.quantity {
                     let _ = q.fit(converter);
+                    snap(q);
                 }
                 (t, o)
             })
             .unzip();
 
+        let (inline_quantities, inline_quantity_outcomes): (Vec<_>, Vec<_>) = self
+            .inline_quantities
+            .into_iter()
+            .map(|q| q.scale(target))
+            .map(|(mut q, o)| {
+                snap(&mut q);
+                (q, o)
+            })
+            .unzip();
+
         let data = ScaledData {
             target,
             ingredients: ingredient_outcomes,
             cookware: cookware_outcomes,
             timers: timer_outcomes,
+            inline_quantities: inline_quantity_outcomes,
         };
 
         ScaledRecipe {
@@ -176,11 +497,86 @@ impl ScalableRecipe {
             ingredients,
             cookware,
             timers,
-            inline_quantities: self.inline_quantities,
+            inline_quantities,
+            unresolved: self.unresolved,
             data: Scaled::Scaled(data),
         }
     }
 
+    /// Same as [`Self::scale_to_target`] but also returns a [`ScaleReport`]
+    ///
+    /// The report has the quantities of every ingredient before and after
+    /// scaling and how they changed. Useful to confirm the changes to the
+    /// user.
+    ///
+    /// ```
+    /// # use cooklang::{scale::{ScaleChange, ScaleTarget}, Converter};
+    /// let recipe = cooklang::parse("Mix @flour{200*%g} and @salt{1%tsp}.").unwrap_output();
+    /// let target = ScaleTarget::new(1, 2, &[]);
+    /// let (_, report) = recipe.scale_with_report(target, &Converter::bundled());
+    /// let flour = &report.ingredients[0];
+    /// assert_eq!(flour.change, ScaleChange::Linear);
+    /// assert_eq!(flour.scaled.as_ref().unwrap().to_string(), "400 g");
+    /// assert_eq!(report.ingredients[1].change, ScaleChange::Fixed);
+    /// ```
+    pub fn scale_with_report(
+        self,
+        target: ScaleTarget,
+        converter: &Converter,
+    ) -> (ScaledRecipe, ScaleReport) {
+        let originals = self
+            .ingredients
+            .iter()
+            .map(|i| {
+                let rounded = i.quantity.as_ref().is_some_and(|q| {
+                    let target = target.for_quantity(Some(q), converter);
+                    matches!(&q.value, ScalableValue::Linear(v) if is_rounded(v, target))
+                });
+                (i.name.clone(), i.quantity.clone(), rounded)
+            })
+            .collect::<Vec<_>>();
+
+        let scaled = self.scale_to_target(target, converter);
+        let outcomes = scaled.scaled_data().map(|d| d.ingredients.as_slice());
+
+        let ingredients = originals
+            .into_iter()
+            .zip(&scaled.ingredients)
+            .enumerate()
+            .map(|(index, ((name, original, rounded), igr))| {
+                let outcome = outcomes.and_then(|o| o.get(index));
+                let change = match (&original, outcome) {
+                    (None, _) => ScaleChange::NoQuantity,
+                    (_, Some(ScaleOutcome::Error(ScaleError::TextValueError(_)))) => {
+                        ScaleChange::Text
+                    }
+                    (_, Some(ScaleOutcome::Error(_))) => ScaleChange::Error,
+                    (Some(q), _) => match q.value {
+                        ScalableValue::Fixed(_) => ScaleChange::Fixed,
+                        ScalableValue::ByServings(_) => ScaleChange::ByServings,
+                        ScalableValue::Linear(_) if rounded => ScaleChange::Rounded,
+                        ScalableValue::Linear(_) => ScaleChange::Linear,
+                    },
+                };
+                IngredientScaleReport {
+                    index,
+                    name,
+                    original,
+                    scaled: igr.quantity.clone(),
+                    change,
+                }
+            })
+            .collect();
+
+        (
+            scaled,
+            ScaleReport {
+                target,
+                ingredients,
+            },
+        )
+    }
+
     /// Scale the recipe to the default values
     ///
     /// The default values are the ones written in the recipe and the first one
@@ -197,6 +593,11 @@ impl ScalableRecipe {
             .map(Scale::default_scale)
             .collect();
         let timers = self.timers.into_iter().map(Scale::default_scale).collect();
+        let inline_quantities = self
+            .inline_quantities
+            .into_iter()
+            .map(Scale::default_scale)
+            .collect();
 
         ScaledRecipe {
             metadata: self.metadata,
@@ -204,12 +605,166 @@ impl ScalableRecipe {
             ingredients,
             cookware,
             timers,
-            inline_quantities: self.inline_quantities,
+            inline_quantities,
+            unresolved: self.unresolved,
             data: Scaled::DefaultScaling,
         }
     }
 }
 
+/// Summary of the changes after scaling a recipe
+///
+/// See [`ScalableRecipe::scale_with_report`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScaleReport {
+    /// What the target was
+    pub target: ScaleTarget,
+    /// Every ingredient of the recipe, in the same order
+    pub ingredients: Vec<IngredientScaleReport>,
+}
+
+/// How an ingredient changed after scaling. See [`ScaleReport`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IngredientScaleReport {
+    /// Index of the ingredient in the recipe
+    pub index: usize,
+    /// Name of the ingredient
+    pub name: String,
+    /// Quantity before scaling
+    pub original: Option<ScalableQuantity>,
+    /// Quantity after scaling
+    pub scaled: Option<ScaledQuantity>,
+    /// How the quantity changed
+    pub change: ScaleChange,
+}
+
+/// How a quantity changed after scaling. See [`IngredientScaleReport`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ScaleChange {
+    /// Multiplied by the scaling factor
+    Linear,
+    /// Multiplied by the scaling factor and rounded to a whole number, see
+    /// [`RoundingPolicy`]
+    Rounded,
+    /// Selected from the values for each serving size
+    ByServings,
+    /// Not changed because it's fixed
+    Fixed,
+    /// Not changed because it's text
+    Text,
+    /// There is no quantity
+    NoQuantity,
+    /// Not changed because of an error, see [`ScaleOutcome::Error`]
+    Error,
+}
+
+/// Checks if a linear value is rounded when scaled to `target`
+fn is_rounded(value: &Value, target: ScaleTarget) -> bool {
+    let factor = target.factor();
+    let rounded = |n: &Number| {
+        let scaled = target.rounding().scale(*n, factor).value();
+        (scaled - n.value() * factor).abs() > 1e-9
+    };
+    match value {
+        Value::Number(n) | Value::AtLeast(n) | Value::AtMost(n) => rounded(n),
+        Value::Range { start, end } => rounded(start) || rounded(end),
+        Value::Text(_) => false,
+    }
+}
+
+/// What to do when selecting a value for servings that is not in a
+/// [`ScalableValue::ByServings`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ByServingsPolicy {
+    /// Use the last value
+    #[default]
+    Clamp,
+    /// Keep the step between the last 2 values
+    ///
+    /// For `2|4|6`, the 4th value is `8`. With only 1 value, this is the
+    /// same as [`Self::Clamp`]. Text values can't be interpolated.
+    Interpolate,
+    /// Fail with [`ScaleError::ServingsOutOfRange`]
+    Error,
+}
+
+impl ScalableValue {
+    /// Get the value for the servings at `index`
+    ///
+    /// `index` is the position of the servings in the recipe metadata, like
+    /// the values in [`ScalableValue::ByServings`]. Out of range indices
+    /// are handled by `policy`. Other variants just return its value.
+    ///
+    /// ```
+    /// # use cooklang::{scale::ByServingsPolicy, ScalableValue, Value};
+    /// let value = ScalableValue::ByServings(vec![2.0.into(), 4.0.into(), 6.0.into()]);
+    /// assert_eq!(
+    ///     value.value_for_servings(1, ByServingsPolicy::Error).unwrap(),
+    ///     Value::from(4.0)
+    /// );
+    /// assert_eq!(
+    ///     value.value_for_servings(5, ByServingsPolicy::Clamp).unwrap(),
+    ///     Value::from(6.0)
+    /// );
+    /// assert_eq!(
+    ///     value.value_for_servings(3, ByServingsPolicy::Interpolate).unwrap(),
+    ///     Value::from(8.0)
+    /// );
+    /// assert!(value.value_for_servings(3, ByServingsPolicy::Error).is_err());
+    /// ```
+    pub fn value_for_servings(
+        &self,
+        index: usize,
+        policy: ByServingsPolicy,
+    ) -> Result<Value, ScaleError> {
+        let values = match self {
+            Self::Fixed(value) | Self::Linear(value) => return Ok(value.clone()),
+            Self::ByServings(values) => values,
+        };
+        if let Some(value) = values.get(index) {
+            return Ok(value.clone());
+        }
+        let len = values.len();
+        let last = values.last().expect("scalable value servings list empty");
+        match policy {
+            ByServingsPolicy::Clamp => Ok(last.clone()),
+            ByServingsPolicy::Error => Err(ScaleError::ServingsOutOfRange { index, len }),
+            ByServingsPolicy::Interpolate if len == 1 => Ok(last.clone()),
+            ByServingsPolicy::Interpolate => {
+                let steps = (index + 1 - len) as f64;
+                let extrapolate = |prev: f64, last: f64| Number::from(last + (last - prev) * steps);
+                match (&values[len - 2], last) {
+                    (Value::Number(prev), Value::Number(last)) => {
+                        Ok(Value::Number(extrapolate(prev.value(), last.value())))
+                    }
+                    (
+                        Value::Range { start, end },
+                        Value::Range {
+                            start: last_start,
+                            end: last_end,
+                        },
+                    ) => Ok(Value::Range {
+                        start: extrapolate(start.value(), last_start.value()),
+                        end: extrapolate(end.value(), last_end.value()),
+                    }),
+                    (Value::AtLeast(prev), Value::AtLeast(last)) => {
+                        Ok(Value::AtLeast(extrapolate(prev.value(), last.value())))
+                    }
+                    (Value::AtMost(prev), Value::AtMost(last)) => {
+                        Ok(Value::AtMost(extrapolate(prev.value(), last.value())))
+                    }
+                    _ => Err(ScaleError::NotScalable {
+                        value: self.clone(),
+                        reason: "only numbers or ranges can be interpolated",
+                    }),
+                }
+            }
+        }
+    }
+}
+
 trait Scale: Sized {
     type Output;
 
@@ -223,10 +778,12 @@ impl Scale for ScalableValue {
     fn scale(self, target: ScaleTarget) -> (Self::Output, ScaleOutcome) {
         match self {
             Self::Fixed(value) => (value, ScaleOutcome::Fixed),
-            Self::Linear(value) => match linear_scale(value.clone(), target.factor()) {
-                Ok(v) => (v, ScaleOutcome::Scaled),
-                Err(e) => (value, ScaleOutcome::Error(e)),
-            },
+            Self::Linear(value) => {
+                match rounded_linear_scale(value.clone(), target.factor(), target.rounding()) {
+                    Ok(v) => (v, ScaleOutcome::Scaled),
+                    Err(e) => (value, ScaleOutcome::Error(e)),
+                }
+            }
             Self::ByServings(ref values) => {
                 if let Some(index) = target.index {
                     let value = match values.get(index) {
@@ -267,14 +824,24 @@ impl Scale for ScalableValue {
     }
 }
 
-fn linear_scale(value: Value, factor: f64) -> Result<Value, ScaleError> {
+pub(crate) fn linear_scale(value: Value, factor: f64) -> Result<Value, ScaleError> {
+    rounded_linear_scale(value, factor, RoundingPolicy::default())
+}
+
+fn rounded_linear_scale(
+    value: Value,
+    factor: f64,
+    rounding: RoundingPolicy,
+) -> Result<Value, ScaleError> {
     match value {
-        Value::Number(n) => Ok(Value::Number((n.value() * factor).into())),
+        Value::Number(n) => Ok(Value::Number(rounding.scale(n, factor))),
         Value::Range { start, end } => {
-            let start = (start.value() * factor).into();
-            let end = (end.value() * factor).into();
+            let start = rounding.scale(start, factor);
+            let end = rounding.scale(end, factor);
             Ok(Value::Range { start, end })
         }
+        Value::AtLeast(start) => Ok(Value::AtLeast(rounding.scale(start, factor))),
+        Value::AtMost(end) => Ok(Value::AtMost(rounding.scale(end, factor))),
         v @ Value::Text(_) => Err(TextValueError(v).into()),
     }
 }
@@ -283,17 +850,36 @@ impl Scale for ScalableQuantity {
     type Output = ScaledQuantity;
 
     fn scale(self, target: ScaleTarget) -> (Self::Output, ScaleOutcome) {
-        let Self { value, unit } = self;
+        let Self {
+            value,
+            unit,
+            decimal,
+            source,
+        } = self;
         let (value, outcome) = value.scale(target);
-        let scaled = ScaledQuantity { value, unit };
+        // the value as written is only valid if it didn't change
+        let source = source.filter(|_| matches!(outcome, ScaleOutcome::Fixed));
+        let scaled = ScaledQuantity {
+            value,
+            unit,
+            decimal,
+            source,
+        };
         (scaled, outcome)
     }
 
     fn default_scale(self) -> Self::Output {
-        let Self { value, unit } = self;
+        let Self {
+            value,
+            unit,
+            decimal,
+            source,
+        } = self;
         Self::Output {
             value: value.default_scale(),
             unit,
+            decimal,
+            source,
         }
     }
 }
@@ -310,6 +896,8 @@ impl Scale for Ingredient<ScalableValue> {
             quantity,
             note: self.note,
             relation: self.relation,
+            origin: self.origin,
+            pantry: self.pantry,
             modifiers: self.modifiers,
         };
         (scaled, outcome)
@@ -322,6 +910,8 @@ impl Scale for Ingredient<ScalableValue> {
             quantity: self.quantity.map(Quantity::default_scale),
             note: self.note,
             relation: self.relation,
+            origin: self.origin,
+            pantry: self.pantry,
             modifiers: self.modifiers,
         }
     }
@@ -331,14 +921,19 @@ impl Scale for Cookware<ScalableValue> {
     type Output = Cookware<Value>;
 
     fn scale(self, target: ScaleTarget) -> (Self::Output, ScaleOutcome) {
+        // Cookware amounts are fixed by default, you don't need 4 pots for a
+        // double batch. They only scale with the explicit auto scale marker
+        // `#loaf tin{1*}`, the auto scale mode only affects ingredients.
         let (quantity, outcome) = self.quantity.map(|q| q.scale(target)).unzip();
         let outcome = outcome.unwrap_or(ScaleOutcome::NoQuantity);
         let scaled = Cookware {
             name: self.name,
             alias: self.alias,
             quantity,
+            unit: self.unit,
             note: self.note,
             relation: self.relation,
+            origin: self.origin,
             modifiers: self.modifiers,
         };
         (scaled, outcome)
@@ -349,8 +944,10 @@ impl Scale for Cookware<ScalableValue> {
             name: self.name,
             alias: self.alias,
             quantity: self.quantity.map(ScalableValue::default_scale),
+            unit: self.unit,
             note: self.note,
             relation: self.relation,
+            origin: self.origin,
             modifiers: self.modifiers,
         }
     }
@@ -365,6 +962,7 @@ impl Scale for Timer<ScalableValue> {
         let scaled = Timer {
             name: self.name,
             quantity,
+            origin: self.origin,
         };
         (scaled, outcome)
     }
@@ -373,6 +971,7 @@ impl Scale for Timer<ScalableValue> {
         Timer {
             name: self.name,
             quantity: self.quantity.map(Quantity::default_scale),
+            origin: self.origin,
         }
     }
 }
@@ -48,6 +48,42 @@ impl Span {
     pub fn is_empty(&self) -> bool {
         self.start == self.end
     }
+
+    /// Smallest span that covers both `a` and `b`
+    ///
+    /// If there is a gap between them, it is also covered.
+    ///
+    /// ```
+    /// # use cooklang::span::Span;
+    /// let a = Span::from(2..4);
+    /// let b = Span::from(7..9);
+    /// assert_eq!(Span::union(a, b), Span::from(2..9));
+    /// ```
+    pub fn union(a: Span, b: Span) -> Span {
+        Self::new(a.start.min(b.start), a.end.max(b.end))
+    }
+
+    /// Move the span `delta` bytes
+    ///
+    /// Useful to keep the spans valid when a source is placed after other
+    /// text.
+    ///
+    /// # Panics
+    /// If the span would start before offset 0 or overflow.
+    pub fn shift(self, delta: isize) -> Span {
+        let shift = |pos: usize| {
+            pos.checked_add_signed(delta)
+                .expect("span shifted out of bounds")
+        };
+        Self::new(shift(self.start), shift(self.end))
+    }
+
+    /// Check if the byte `offset` is inside the span
+    ///
+    /// The end is exclusive, so an empty span contains nothing.
+    pub fn contains(&self, offset: usize) -> bool {
+        self.start <= offset && offset < self.end
+    }
 }
 
 impl std::fmt::Debug for Span {
@@ -122,3 +158,42 @@ where
         self.span.end
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test_case(0..0, 0..0 => 0..0 ; "empty at 0")]
+    #[test_case(0..0, 3..5 => 0..5 ; "empty at 0 and other")]
+    #[test_case(0..2, 1..4 => 0..4 ; "overlap")]
+    #[test_case(5..8, 1..2 => 1..8 ; "gap and reversed")]
+    #[test_case(2..9, 3..4 => 2..9 ; "contained")]
+    fn union(a: Range<usize>, b: Range<usize>) -> Range<usize> {
+        Span::union(a.clone().into(), b.clone().into()).range()
+    }
+
+    #[test_case(0..0, 0 => 0..0 ; "zero at 0")]
+    #[test_case(0..3, 4 => 4..7 ; "forward from 0")]
+    #[test_case(4..7, -4 => 0..3 ; "back to 0")]
+    #[test_case(2..2, 3 => 5..5 ; "empty")]
+    fn shift(span: Range<usize>, delta: isize) -> Range<usize> {
+        Span::from(span).shift(delta).range()
+    }
+
+    #[test]
+    #[should_panic]
+    fn shift_before_0() {
+        Span::new(1, 3).shift(-2);
+    }
+
+    #[test]
+    fn contains() {
+        let span = Span::new(2, 4);
+        assert!(!span.contains(1));
+        assert!(span.contains(2));
+        assert!(span.contains(3));
+        assert!(!span.contains(4));
+        assert!(!Span::pos(0).contains(0));
+    }
+}
@@ -63,7 +63,6 @@ impl<'a> Text<'a> {
         }
     }
 
-    #[cfg(test)] // only used in tests
     pub(crate) fn from_str(s: &'a str, offset: usize) -> Self {
         let mut t = Self::empty(offset);
         t.append_fragment(TextFragment::new(s, offset));
@@ -91,6 +90,25 @@ impl<'a> Text<'a> {
         self.data.span()
     }
 
+    /// Get the span of the original input without the leading and trailing
+    /// whitespace
+    ///
+    /// If the text is empty or blank, the span is empty at the start of
+    /// [`Self::span`].
+    pub fn span_trimmed(&self) -> Span {
+        let fragments = self.fragments();
+        let first = fragments.iter().find(|f| !f.text.trim().is_empty());
+        let last = fragments.iter().rfind(|f| !f.text.trim().is_empty());
+        match (first, last) {
+            (Some(first), Some(last)) => {
+                let start = first.end() - first.text.trim_start().len();
+                let end = last.start() + last.text.trim_end().len();
+                Span::new(start, end)
+            }
+            _ => Span::pos(self.span().start()),
+        }
+    }
+
     /// Get the text of all the fragments concatenated
     ///
     /// A soft break is always rendered as a ascii whitespace.
@@ -109,6 +127,46 @@ impl<'a> Text<'a> {
         s
     }
 
+    /// Get the text as a reader would see it
+    ///
+    /// Like [`Self::text`], comments are skipped and soft breaks are rendered
+    /// as a single whitespace. The whitespace around the soft breaks is also
+    /// removed, so the indentation of a continuation line does not matter.
+    /// The start and end of the text are not trimmed.
+    pub fn rendered(&self) -> Cow<'a, str> {
+        let fragments = self.fragments();
+        if !fragments
+            .iter()
+            .any(|f| f.kind == TextFragmentKind::SoftBreak)
+        {
+            return self.text();
+        }
+
+        let mut s = String::new();
+        let mut after_break = false;
+        for f in fragments {
+            match f.kind {
+                TextFragmentKind::Text => {
+                    let text = if after_break {
+                        f.text.trim_start()
+                    } else {
+                        f.text
+                    };
+                    after_break &= text.is_empty();
+                    s += text;
+                }
+                TextFragmentKind::SoftBreak => {
+                    if !after_break {
+                        s.truncate(s.trim_end().len());
+                        s.push(' ');
+                    }
+                    after_break = true;
+                }
+            }
+        }
+        Cow::Owned(s)
+    }
+
     /// Get the text trimmed (start and end)
     pub fn text_outer_trimmed(&self) -> Cow<'a, str> {
         match self.text() {
@@ -118,21 +176,29 @@ impl<'a> Text<'a> {
     }
 
     /// Get the text trimmed from whitespaces before, after and in between words
+    ///
+    /// Tabs are whitespace like spaces, so any run of them in between words
+    /// is replaced by a single space. This is used for names and units, the
+    /// text of steps keeps its tabs.
     pub fn text_trimmed(&self) -> Cow<'a, str> {
         let t = self.text_outer_trimmed();
 
-        if !t.contains("  ") {
+        if !t.contains("  ") && !t.contains('\t') {
             return t;
         }
 
-        let mut t = t.into_owned();
-        let mut prev = ' ';
-        t.retain(|c| {
-            let r = c != ' ' || prev != ' ';
-            prev = c;
-            r
-        });
-        Cow::from(t)
+        let mut out = String::with_capacity(t.len());
+        let mut prev_ws = false;
+        for c in t.chars() {
+            let ws = c == ' ' || c == '\t';
+            if !ws {
+                out.push(c);
+            } else if !prev_ws {
+                out.push(' ');
+            }
+            prev_ws = ws;
+        }
+        Cow::from(out)
     }
 
     /// Checks that the text is not empty or blank, i.e. whitespace does not count
@@ -145,6 +211,24 @@ impl<'a> Text<'a> {
         self.data.as_slice()
     }
 
+    /// Same text borrowed from another source, where it starts `delta` bytes
+    /// later
+    ///
+    /// Returns [`None`] if the text is not in `source` at that position.
+    pub(crate) fn rebase<'b>(&self, source: &'b str, delta: isize) -> Option<Text<'b>> {
+        let mut t = Text::empty(self.span().shift(delta).start());
+        for f in self.fragments() {
+            let span = f.span().shift(delta);
+            let text = source.get(span.range()).filter(|text| *text == f.text)?;
+            t.data.push(TextFragment {
+                text,
+                offset: span.start(),
+                kind: f.kind,
+            });
+        }
+        Some(t)
+    }
+
     /// Convenience method to the the text in [`Located`]
     pub fn located_text_trimmed(&self) -> Located<Cow<str>> {
         Located::new(self.text_trimmed(), self.span())
@@ -188,7 +272,8 @@ impl From<Text<'_>> for Span {
 /// Fragment that compose a [`Text`]
 ///
 /// This implemets [`PartialEq`] and it will return true if the text matches, it
-/// ignores the location.
+/// ignores the location. Soft breaks are always equal, regardless of the line
+/// ending.
 #[derive(Clone, Copy, Serialize)]
 pub struct TextFragment<'a> {
     text: &'a str,
@@ -252,7 +337,10 @@ impl Debug for TextFragment<'_> {
 
 impl PartialEq for TextFragment<'_> {
     fn eq(&self, other: &Self) -> bool {
+        // a soft break is the same with "\n" or "\r\n"
         self.text == other.text
+            || (self.kind == TextFragmentKind::SoftBreak
+                && other.kind == TextFragmentKind::SoftBreak)
     }
 }
 
@@ -264,8 +352,36 @@ mod tests {
     #[test_case("a b c" => "a b c"; "no trim")]
     #[test_case("  a b c  " => "a b c"; "outer trim")]
     #[test_case("  a    b      c  " => "a b c"; "inner trim")]
+    #[test_case("\ta\t\tb \t c\t" => "a b c"; "tabs")]
     fn trim_whitespace(t: &str) -> String {
         let t = Text::from_str(t, 0);
         t.text_trimmed().into_owned()
     }
+
+    #[test]
+    fn rendered() {
+        use super::TextFragment;
+
+        // "  This is a step    -- comment\n   and this line continues  "
+        let mut t = Text::empty(0);
+        t.append_str("  This is a step    ", 0);
+        t.append_fragment(TextFragment::soft_break("\n", 30));
+        t.append_str("   and this line continues  ", 31);
+        assert_eq!(t.rendered(), "  This is a step and this line continues  ");
+        assert_eq!(
+            t.text(),
+            "  This is a step        and this line continues  "
+        );
+
+        let t = Text::from_str("no breaks  ", 0);
+        assert_eq!(t.rendered(), t.text());
+    }
+
+    #[test_case("a b" => 0..3; "no trim")]
+    #[test_case("  a b  " => 2..5; "trim")]
+    #[test_case("   " => 0..0; "blank")]
+    fn span_trimmed(t: &str) -> std::ops::Range<usize> {
+        let t = Text::from_str(t, 0);
+        t.span_trimmed().range()
+    }
 }
@@ -171,7 +171,9 @@ impl TestValue {
         match value {
             ScalableValue::Fixed(value) => match value {
                 Value::Number(num) => TestValue::Number(num.value()),
-                Value::Range { .. } => panic!("unexpected range value"),
+                Value::Range { .. } | Value::AtLeast(_) | Value::AtMost(_) => {
+                    panic!("unexpected range value")
+                }
                 Value::Text(value) => TestValue::Text(value),
             },
             ScalableValue::Linear(_) => panic!("unexpected linear value"),
@@ -1,4 +1,4 @@
-use cooklang::{convert::System, Converter, Quantity, Value};
+use cooklang::{convert::System, Converter, CooklangParser, Quantity, Value};
 use test_case::test_case;
 
 #[test_case(2.0, "tsp" => "2 tsp")]
@@ -15,3 +15,25 @@ fn imperial(value: f64, unit: &str) -> String {
     let _ = q.convert(System::Imperial, &converter);
     q.to_string()
 }
+
+#[test_case("1 1/2%cups", "tbsp" => ("24 tbsp".to_string(), "1 1/2 c".to_string()); "whole in target")]
+#[test_case("1/3%cup", "fl oz" => ("2 2/3 fl oz".to_string(), "1/3 c".to_string()); "fraction in target")]
+#[test_case("1 1/2%cups", "tsp" => ("72 tsp".to_string(), "1 1/2 c".to_string()); "over max whole")]
+#[test_case("1 1/2%cups", "ml" => ("354.882 ml".to_string(), "354.882 ml".to_string()); "fractions disabled")]
+fn convert_then_fit(quantity: &str, unit: &str) -> (String, String) {
+    let parser = CooklangParser::extended();
+    let recipe = parser
+        .parse(&format!("Add @milk{{{quantity}}}."))
+        .unwrap_output()
+        .default_scale();
+    let mut q = recipe.ingredients[0].quantity.clone().unwrap();
+    assert!(matches!(
+        q.value,
+        Value::Number(cooklang::quantity::Number::Fraction { .. })
+    ));
+
+    q.convert(unit, parser.converter()).unwrap();
+    let converted = q.to_string();
+    q.fit(parser.converter()).unwrap();
+    (converted, q.to_string())
+}
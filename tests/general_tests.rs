@@ -1,4 +1,22 @@
-use cooklang::{Content, CooklangParser, Extensions, Item};
+use cooklang::analysis::{
+    CheckResult, DuplicateDefinition, DuplicateDefinitionPolicy, DuplicateMetadataPolicy,
+    NonPositiveQuantity,
+};
+use cooklang::ast::{build_ast, Visitor};
+use cooklang::builder::{BuildError, RecipeBuilder};
+use cooklang::convert::System;
+use cooklang::error::Severity;
+use cooklang::ingredient_list::IngredientList;
+use cooklang::metadata::ListKeys;
+use cooklang::parser::{self, ComponentKind, ComponentMarkers, PullParser};
+use cooklang::quantity::{FormatOptions, FractionStyle, Number};
+use cooklang::scale::{
+    ByServingsPolicy, RoundingPolicy, ScaleChange, ScaleError, ScaleOutcome, ScaleTarget,
+};
+use cooklang::{
+    ComponentOrigin, Content, Converter, CooklangParser, CookwareQuantityKind, Extensions, Item,
+    Located, ParseOptions, Quantity, ScalableValue, ScaledRecipe, Text, UnresolvedKind, Value,
+};
 use indoc::indoc;
 use test_case::test_case;
 
@@ -186,9 +204,15 @@ fn multiple_temperatures() {
     let parser = CooklangParser::new(Extensions::all(), Default::default());
     let r = parser.parse(input).unwrap_output();
     assert_eq!(r.inline_quantities.len(), 2);
-    assert_eq!(r.inline_quantities[0].value, 2.0.into());
+    assert_eq!(
+        r.inline_quantities[0].value,
+        ScalableValue::Fixed(2.0.into())
+    );
     assert_eq!(r.inline_quantities[0].unit_text(), Some("ºC"));
-    assert_eq!(r.inline_quantities[1].value, 150.0.into());
+    assert_eq!(
+        r.inline_quantities[1].value,
+        ScalableValue::Fixed(150.0.into())
+    );
     assert_eq!(r.inline_quantities[1].unit_text(), Some("F"));
     let Content::Step(first_step) = &r.sections[0].content[0] else {
         panic!()
@@ -245,3 +269,1717 @@ fn text_steps_extension() {
         [Content::Step(_)]
     ));
 }
+
+#[test]
+fn parse_many_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let first = dir.path().join("first.cook");
+    let second = dir.path().join("second.cook");
+    let missing = dir.path().join("missing.cook");
+    std::fs::write(&first, "Add @salt.").unwrap();
+    std::fs::write(&second, "Add @pepper and @oil.").unwrap();
+
+    let parser = CooklangParser::new(Extensions::all(), Default::default());
+    let results = parser.parse_many(&[first.clone(), missing.clone(), second.clone()]);
+
+    assert_eq!(results.len(), 3);
+    assert_eq!(results[0].0, first);
+    assert_eq!(results[0].1.output().unwrap().ingredients.len(), 1);
+    assert_eq!(results[1].0, missing);
+    assert!(!results[1].1.has_output());
+    assert!(results[1].1.report().has_errors());
+    assert_eq!(results[2].0, second);
+    assert_eq!(results[2].1.output().unwrap().ingredients.len(), 2);
+}
+
+#[test]
+fn scale_metadata() {
+    let input = indoc! {r#"
+        >> scale: 1.5
+        Add @flour{200*%g}, @salt{1%tsp} and ~{10%min}.
+    "#};
+    let r = cooklang::parse(input).unwrap_output();
+    assert_eq!(r.metadata.scale(), Some(1.5));
+    let flour = r.ingredients[0].quantity.as_ref().unwrap();
+    assert_eq!(flour.value, ScalableValue::Linear(300.0.into()));
+    let salt = r.ingredients[1].quantity.as_ref().unwrap();
+    assert_eq!(salt.value, ScalableValue::Fixed(1.0.into()));
+
+    let r = cooklang::parse(">> scale: -2\nAdd @flour{200*%g}.");
+    assert!(r.report().has_errors());
+}
+
+#[test_case(DuplicateMetadataPolicy::Overwrite => ("b".to_string(), 0); "overwrite")]
+#[test_case(DuplicateMetadataPolicy::FirstWins => ("a".to_string(), 0); "first wins")]
+#[test_case(DuplicateMetadataPolicy::Append(", ".into()) => ("a, b".to_string(), 0); "append")]
+#[test_case(DuplicateMetadataPolicy::Warn => ("b".to_string(), 1); "warn")]
+fn duplicate_metadata(policy: DuplicateMetadataPolicy) -> (String, usize) {
+    let input = indoc! {r#"
+        >> category: a
+        >> category: b
+    "#};
+    let options = ParseOptions {
+        duplicate_metadata: policy,
+        ..Default::default()
+    };
+    let (r, report) = CooklangParser::default()
+        .parse_with_options(input, options)
+        .into_result()
+        .unwrap();
+    (
+        r.metadata.map["category"].clone(),
+        report.warnings().count(),
+    )
+}
+
+#[test_case(DuplicateDefinitionPolicy::Warn => (3, 1); "warn")]
+#[test_case(DuplicateDefinitionPolicy::Merge => (3, 1); "merge")]
+#[test_case(DuplicateDefinitionPolicy::Allow => (3, 0); "allow")]
+fn duplicate_definition(policy: DuplicateDefinitionPolicy) -> (usize, usize) {
+    let input = "Add @flour{200%g} and @salt. Then @Flour{100%g} and @&flour{1%g}.";
+    let options = ParseOptions {
+        duplicate_definition: policy,
+        ..Default::default()
+    };
+    let (r, report) = CooklangParser::default()
+        .parse_with_options(input, options)
+        .into_result()
+        .unwrap();
+
+    for w in report.warnings() {
+        assert_eq!(w.code, Some("duplicate-definition"));
+        let source = std::error::Error::source(w).unwrap();
+        let dup = source.downcast_ref::<DuplicateDefinition>().unwrap();
+        assert_eq!(dup.name, "Flour");
+        assert_eq!(&input[dup.first_span.range()], "@flour{200%g}");
+        assert_eq!(&input[dup.second_span.range()], "@Flour{100%g}");
+    }
+
+    let definitions = r.ingredients.iter().filter(|i| i.relation.is_definition());
+    let merged = policy == DuplicateDefinitionPolicy::Merge;
+    assert_eq!(definitions.count(), if merged { 2 } else { 3 });
+    (
+        r.ingredients.iter().filter(|i| i.name != "salt").count(),
+        report.warnings().count(),
+    )
+}
+
+#[test]
+fn metadata_lists() {
+    let input = indoc! {r#"
+        >> tags: a, b, "c, d"
+        >> category: dessert
+        >> diet: vegan, raw
+    "#};
+    let r = cooklang::parse(input).unwrap_output();
+    assert_eq!(r.metadata.list("tags").unwrap(), ["a", "b", "c, d"]);
+    assert_eq!(r.metadata.tags().unwrap(), ["a", "b", "c, d"]);
+    assert_eq!(r.metadata.list("category").unwrap(), ["dessert"]);
+    assert_eq!(r.metadata.list("diet"), None);
+
+    let mut list_keys = ListKeys::empty();
+    list_keys.add("diet");
+    let options = ParseOptions {
+        list_keys,
+        ..Default::default()
+    };
+    let r = CooklangParser::default()
+        .parse_with_options(input, options)
+        .unwrap_output();
+    assert_eq!(r.metadata.list("diet").unwrap(), ["vegan", "raw"]);
+    assert_eq!(r.metadata.list("category"), None);
+}
+
+#[test]
+fn metadata_spans() {
+    let input = indoc! {r#"
+        >> servings: 4
+        >>  source : grandma 
+        A step.
+    "#};
+    let r = cooklang::parse(input).unwrap_output();
+    let m = &r.metadata;
+    let key = m.key_span("servings").unwrap();
+    let value = m.value_span("servings").unwrap();
+    assert_eq!(key.range(), 3..11);
+    assert_eq!(&input[key.range()], "servings");
+    assert_eq!(value.range(), 13..14);
+    assert_eq!(&input[value.range()], "4");
+    assert_eq!(&input[m.key_span("source").unwrap().range()], "source");
+    assert_eq!(&input[m.value_span("source").unwrap().range()], "grandma");
+    assert!(m.key_span("missing").is_none());
+}
+
+#[test]
+fn non_positive_quantities() {
+    let input = "Add @salt{0%g} and @sugar{-1%g}.";
+    // with ranges `-1` is "up to 1"
+    let parser = CooklangParser::new(
+        Extensions::default() - Extensions::RANGE_VALUES,
+        Converter::default(),
+    );
+    let r = parser.parse(input);
+    let report = r.report();
+    assert_eq!(report.warnings().count(), 1);
+    assert_eq!(report.errors().count(), 1);
+    let error = report.errors().next().unwrap();
+    assert!(error.message.contains("negative"));
+
+    let options = ParseOptions {
+        non_positive_quantity: NonPositiveQuantity {
+            zero: Severity::Error,
+            negative: Severity::Warning,
+        },
+        ..Default::default()
+    };
+    let r = parser.parse_with_options(input, options);
+    let report = r.report();
+    assert_eq!(report.warnings().count(), 1);
+    assert_eq!(report.errors().count(), 1);
+    let error = report.errors().next().unwrap();
+    assert_eq!(&input[error.labels[0].0.range()], "0");
+}
+
+#[test]
+fn negative_fraction_scaling() {
+    let options = ParseOptions {
+        non_positive_quantity: NonPositiveQuantity {
+            zero: Severity::Warning,
+            negative: Severity::Warning,
+        },
+        ..Default::default()
+    };
+    let parser = CooklangParser::new(
+        Extensions::all() - Extensions::RANGE_VALUES,
+        Converter::default(),
+    );
+    let r = parser
+        .parse_with_options("Add @sugar{-1/2*%cup}.", options)
+        .unwrap_output();
+    let scaled = r.clone().default_scale();
+    let sugar = scaled.ingredients[0].quantity.as_ref().unwrap();
+    assert!(matches!(
+        sugar.value,
+        Value::Number(Number::Fraction { negative: true, .. })
+    ));
+    assert_eq!(sugar.to_string(), "-1/2 cup");
+
+    let scaled = r.scale_by(3.0, parser.converter());
+    let sugar = scaled.ingredients[0].quantity.as_ref().unwrap();
+    assert_eq!(sugar.value.to_string(), "-1 1/2");
+}
+
+#[test]
+fn diagnostics_json() {
+    let input = "Wait ~rest{}.";
+    let r = cooklang::parse(input);
+    let diagnostics = r.diagnostics();
+    assert_eq!(diagnostics.len(), 1);
+    let json = serde_json::to_value(&diagnostics[0]).unwrap();
+    assert_eq!(json["severity"], "error");
+    assert_eq!(json["code"], "timer-missing-quantity");
+    assert_eq!(json["message"], "Invalid timer: missing quantity");
+    assert_eq!(json["span"]["start"], 10);
+    assert_eq!(json["span"]["end"], 12);
+    assert_eq!(&input[10..12], "{}");
+}
+
+#[test_case(Extensions::all() => (Severity::Error, "timer-missing-quantity".to_string()); "requires time")]
+#[test_case(Extensions::all() ^ Extensions::TIMER_REQUIRES_TIME => (Severity::Warning, "empty-timer".to_string()); "empty")]
+fn empty_timer(extensions: Extensions) -> (Severity, String) {
+    let input = "Wait ~{}.";
+    let parser = CooklangParser::new(extensions, Default::default());
+    let r = parser.parse(input);
+    let diagnostics = r.diagnostics();
+    assert_eq!(diagnostics.len(), 1);
+    let span = diagnostics[0].span.unwrap();
+    assert_eq!(&input[span.range()], "{}");
+    if diagnostics[0].severity == Severity::Warning {
+        let timer = &r.output().unwrap().timers[0];
+        assert!(timer.name.is_none() && timer.quantity.is_none());
+    }
+    (
+        diagnostics[0].severity,
+        diagnostics[0].code.clone().unwrap(),
+    )
+}
+
+#[test]
+fn convert_all() {
+    let input = "Mix @flour{1%lb}, @salt{a pinch}, @sugar{1%handful} and @water{2} for ~{2%min}.\n\
+                 Pour it in a #baking dish{9%inch} with #skewers{12}.";
+    let converter = cooklang::Converter::bundled();
+    let recipe = CooklangParser::new(Extensions::all(), converter.clone())
+        .parse(input)
+        .unwrap_output()
+        .default_scale();
+
+    let (converted, warnings) = recipe.convert_all(System::Metric, &converter);
+
+    // the original is kept
+    let flour = recipe.ingredients[0].quantity.as_ref().unwrap();
+    assert_eq!(flour.unit_text(), Some("lb"));
+    let flour = converted.ingredients[0].quantity.as_ref().unwrap();
+    assert_eq!(flour.unit_text(), Some("g"));
+    assert_eq!(converted.ingredients[1], recipe.ingredients[1]);
+    assert_eq!(converted.ingredients[3], recipe.ingredients[3]);
+    assert_eq!(converted.timers, recipe.timers);
+    let dish = &converted.cookware[0];
+    assert_eq!(dish.unit.as_deref(), Some("cm"));
+    assert_eq!(dish.quantity, Some(Value::from(22.86)));
+    assert_eq!(recipe.cookware[0].unit.as_deref(), Some("inch"));
+    assert_eq!(converted.cookware[1], recipe.cookware[1]);
+
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].item, Item::Ingredient { index: 2 });
+}
+
+#[test]
+fn to_system() {
+    let input = "Mix @flour{500%g}, @milk{250%ml}, @eggs{2} and @sugar{1%handful}. \
+                 Bake in a #pan{24%cm}, or a #dish{1%span}, at 200 °C.";
+    let converter = cooklang::Converter::bundled();
+    let recipe = CooklangParser::new(Extensions::all(), converter.clone())
+        .parse(input)
+        .unwrap_output()
+        .default_scale();
+
+    let (recipe, warnings) = recipe.to_system(System::Imperial, &converter);
+
+    let unit = |i: usize| recipe.ingredients[i].quantity.as_ref().unwrap().unit_text();
+    assert_eq!(unit(0), Some("oz"));
+    assert_eq!(unit(1), Some("c"));
+    assert_eq!(unit(2), None);
+    assert_eq!(unit(3), Some("handful"));
+    assert_eq!(recipe.inline_quantities[0].unit_text(), Some("°F"));
+    assert_eq!(recipe.cookware[0].unit.as_deref(), Some("in"));
+    assert_eq!(recipe.cookware[1].unit.as_deref(), Some("span"));
+
+    assert_eq!(warnings.len(), 2);
+    assert_eq!(warnings[0].item, Item::Ingredient { index: 3 });
+    assert_eq!(warnings[1].item, Item::Cookware { index: 1 });
+}
+
+#[test]
+fn section_note() {
+    let input = indoc! {r#"
+        = Dough
+        > This makes enough for two pizzas.
+
+        Mix @flour{500%g} and @water{300%ml}.
+
+        = Sauce
+        Crush the tomatoes by hand, it's better.
+
+        Add @tomatoes{400%g} to a #pot.
+
+        = Topping
+        Add @cheese{}.
+    "#};
+    let r = cooklang::parse(input).unwrap_output();
+    assert_eq!(r.sections.len(), 3);
+    assert_eq!(
+        r.sections[0].note().as_deref(),
+        Some("This makes enough for two pizzas.")
+    );
+    assert_eq!(
+        r.sections[1].note().as_deref(),
+        Some("Crush the tomatoes by hand, it's better.")
+    );
+    assert_eq!(r.sections[2].note(), None);
+}
+
+#[test]
+fn component_origin() {
+    let input = indoc! {r#"
+        = Dough
+        Mix @flour{500%g} and @water{300%ml}.
+
+        Knead for ~{10%min}.
+
+        = Sauce
+        > Use ripe tomatoes.
+
+        Add @tomatoes{400%g} to a #pot.
+    "#};
+    let r = cooklang::parse(input).unwrap_output();
+    let origin = |c: ComponentOrigin| (c.section, c.step);
+    let ingredients: Vec<_> = r.ingredients.iter().map(|i| origin(i.origin)).collect();
+    assert_eq!(ingredients, [(0, 0), (0, 0), (1, 1)]);
+    assert_eq!(origin(r.cookware[0].origin), (1, 1));
+    assert_eq!(origin(r.timers[0].origin), (0, 1));
+    assert!(r.sections[1].content[1].is_step());
+}
+
+#[test]
+fn rename_ingredient() {
+    let input = indoc! {r#"
+        Chop @scallion{2}(thinly) and @garlic.
+
+        Add the @&Scallion{1} at the end.
+    "#};
+    let recipe = cooklang::parse(input).unwrap_output();
+
+    // the reference is renamed with its definition, even with another case
+    let (renamed, count) = recipe.rename_ingredient("scallion", "green onion", false);
+    assert_eq!(count, 2);
+    assert_eq!(renamed.ingredients[2].name, "green onion");
+    let (_, count) = recipe.rename_ingredient("Scallion", "green onion", false);
+    assert_eq!(count, 0);
+
+    let (renamed, count) = recipe.rename_ingredient("SCALLION", "green onion", true);
+    assert_eq!(count, 2);
+    let names: Vec<_> = renamed
+        .ingredients
+        .iter()
+        .map(|i| i.name.as_str())
+        .collect();
+    assert_eq!(names, ["green onion", "garlic", "green onion"]);
+    for (old, new) in recipe.ingredients.iter().zip(&renamed.ingredients) {
+        assert_eq!(old.quantity, new.quantity);
+        assert_eq!(old.note, new.note);
+        assert_eq!(old.modifiers(), new.modifiers());
+        assert_eq!(old.relation, new.relation);
+    }
+    assert_eq!(
+        renamed.ingredients[2].relation.references_to().unwrap().0,
+        0
+    );
+}
+
+#[test]
+fn preparations() {
+    let input = indoc! {r#"
+        Fry @onion{1}(diced) with @garlic{2%cloves}(minced).
+
+        Add @salt and @tomatoes{400%g}(peeled and crushed).
+
+        Add the rest of the @&onion.
+    "#};
+    let recipe = cooklang::parse(input).unwrap_output();
+    assert_eq!(
+        recipe.preparations(),
+        [
+            ("onion", "diced"),
+            ("garlic", "minced"),
+            ("tomatoes", "peeled and crushed")
+        ]
+    );
+}
+
+#[test]
+fn plain_text() {
+    let input = indoc! {r#"
+        = Dough
+        Mix @flour{200%g} and @water{} in a #bowl{2}.
+
+        > Let it rest.
+
+        = Bake
+        Bake for ~{20%min}.
+    "#};
+    let recipe = cooklang::parse(input).unwrap_output();
+    assert_eq!(
+        recipe.plain_text(false),
+        "Dough\nMix flour and water in a bowl.\nLet it rest.\nBake\nBake for 20 min."
+    );
+    assert_eq!(
+        recipe.plain_text(true),
+        "Dough\nMix 200 g flour and water in a 2 bowl.\nLet it rest.\nBake\nBake for 20 min."
+    );
+}
+
+#[test]
+fn step_component_counts() {
+    let input = indoc! {r#"
+        Mix @flour{200%g}, @water and @&flour{50%g} in a #bowl for ~{2%min}.
+
+        Let it rest.
+    "#};
+    let recipe = cooklang::parse(input).unwrap_output();
+    let step = recipe.sections[0].content[0].unwrap_step();
+    assert_eq!(step.ingredient_count, 3);
+    assert_eq!(step.cookware_count, 1);
+    assert_eq!(step.timer_count, 1);
+    let step = recipe.sections[0].content[1].unwrap_step();
+    assert_eq!(
+        (step.ingredient_count, step.cookware_count, step.timer_count),
+        (0, 0, 0)
+    );
+}
+
+#[test]
+fn leading_description() {
+    let input = indoc! {r#"
+        A quick weeknight soup.
+
+        Serve it with bread.
+
+        Boil the @water{1%l}.
+
+        Add the @noodles.
+    "#};
+    let recipe = cooklang::parse(input).unwrap_output();
+    assert_eq!(recipe.description(), None);
+    assert_eq!(recipe.sections[0].content.len(), 4);
+
+    let options = || ParseOptions {
+        leading_description: true,
+        ..Default::default()
+    };
+    let recipe = CooklangParser::default()
+        .parse_with_options(input, options())
+        .unwrap_output();
+    assert_eq!(
+        recipe.description(),
+        Some("A quick weeknight soup.\nServe it with bread.")
+    );
+    let content = &recipe.sections[0].content;
+    assert_eq!(content.len(), 2);
+    assert!(matches!(content[0], Content::Step(ref s) if s.number == 1));
+    assert!(matches!(content[1], Content::Step(ref s) if s.number == 2));
+
+    // an explicit description wins
+    let input = format!(">> description: Soup\n{input}");
+    let recipe = CooklangParser::default()
+        .parse_with_options(&input, options())
+        .unwrap_output();
+    assert_eq!(recipe.description(), Some("Soup"));
+    assert_eq!(recipe.sections[0].content.len(), 4);
+}
+
+#[test]
+fn custom_comment_prefix() {
+    let mut markers = ComponentMarkers::new();
+    markers.set_comment_prefix(";;").unwrap();
+    assert!(markers.clone().set_comment_prefix("#").is_err());
+    assert!(markers.clone().set_comment_prefix("").is_err());
+    assert!(markers.clone().set_comment_prefix("; ;").is_err());
+    assert!(markers.clone().set_comment_prefix("--").is_ok());
+    markers.add('$', ComponentKind::Cookware).unwrap();
+    assert!(markers.clone().set_comment_prefix("$$").is_err());
+    assert!(markers.clone().add(';', ComponentKind::Cookware).is_err());
+
+    let input = "Add @salt{1%tsp} and stir -- not a comment ;; the good one";
+    let parser = CooklangParser::extended().with_component_markers(markers);
+    let r = parser.parse(input).unwrap_output();
+    let text: String = r.sections[0].content[0]
+        .unwrap_step()
+        .items
+        .iter()
+        .filter_map(|i| match i {
+            Item::Text { value } => Some(value.as_str()),
+            _ => None,
+        })
+        .collect();
+    assert!(text.contains("and stir -- not a comment"));
+    assert!(!text.contains(";;") && !text.contains("good one"));
+    assert_eq!(r.ingredients.len(), 1);
+}
+
+#[test_case("//" ; "slash")]
+#[test_case("-" ; "minus")]
+#[test_case(">" ; "metadata")]
+#[test_case("==" ; "section")]
+#[test_case("%%" ; "unit separator")]
+#[test_case("|" ; "pipe")]
+#[test_case("{" ; "brace")]
+#[test_case("*" ; "star")]
+#[test_case("[" ; "block comment")]
+#[test_case("!!" ; "media")]
+fn reserved_comment_prefix(prefix: &str) {
+    assert!(ComponentMarkers::new().set_comment_prefix(prefix).is_err());
+}
+
+#[test]
+fn custom_component_markers() {
+    let mut markers = ComponentMarkers::new();
+    markers
+        .add('$', ComponentKind::Cookware)
+        .unwrap()
+        .add('^', ComponentKind::Ingredient)
+        .unwrap();
+    assert!(markers.clone().add('&', ComponentKind::Cookware).is_err());
+    assert!(markers.clone().add('a', ComponentKind::Cookware).is_err());
+    assert!(markers.clone().add('!', ComponentKind::Cookware).is_err());
+    assert!(markers.clone().add(',', ComponentKind::Cookware).is_err());
+
+    let parser = CooklangParser::extended().with_component_markers(markers);
+    let r = parser
+        .parse("Put ^salt{1%tsp} and @pepper in a $small pan{} with a #lid.")
+        .unwrap_output();
+    let ingredients: Vec<_> = r.ingredients.iter().map(|i| i.name.as_str()).collect();
+    assert_eq!(ingredients, ["salt", "pepper"]);
+    let cookware: Vec<_> = r.cookware.iter().map(|c| c.name.as_str()).collect();
+    assert_eq!(cookware, ["small pan", "lid"]);
+
+    // default parser is not affected
+    let r = cooklang::parse("Use a $pan.").unwrap_output();
+    assert!(r.cookware.is_empty());
+}
+
+#[test]
+fn components_disabled() {
+    let mut markers = ComponentMarkers::new();
+    markers.set_components_disabled(true);
+    let parser = CooklangParser::extended().with_component_markers(markers);
+    let input = indoc! {r#"
+        >> servings: 2
+        Mix @flour{200%g} in a #bowl for ~{5%min}.
+    "#};
+    let r = parser.parse(input).unwrap_output();
+    assert!(r.ingredients.is_empty());
+    assert!(r.cookware.is_empty());
+    assert!(r.timers.is_empty());
+    assert_eq!(r.metadata.servings(), Some([2].as_slice()));
+    assert_eq!(
+        r.sections[0].content[0].unwrap_step().items,
+        [Item::Text {
+            value: "Mix @flour{200%g} in a #bowl for ~{5%min}.".into()
+        }]
+    );
+}
+
+#[test]
+fn value_for_servings() {
+    let input = indoc! {r#"
+        >> servings: 1|2|3
+        Add @eggs{2|4|6}.
+    "#};
+    let r = cooklang::parse(input).unwrap_output();
+    let value = &r.ingredients[0].quantity.as_ref().unwrap().value;
+    let select = |index, policy| value.value_for_servings(index, policy);
+    assert_eq!(
+        select(1, ByServingsPolicy::Error).unwrap(),
+        Value::from(4.0)
+    );
+    assert_eq!(
+        select(3, ByServingsPolicy::Clamp).unwrap(),
+        Value::from(6.0)
+    );
+    assert_eq!(
+        select(4, ByServingsPolicy::Interpolate).unwrap(),
+        Value::from(10.0)
+    );
+    assert!(select(3, ByServingsPolicy::Error).is_err());
+}
+
+#[test]
+fn strict_mode() {
+    let input = "Add @salt{0%g}.";
+    let r = cooklang::parse(input);
+    assert!(r.is_valid());
+    assert_eq!(r.report().warnings().count(), 1);
+
+    let options = ParseOptions {
+        strict: true,
+        ..Default::default()
+    };
+    let r = CooklangParser::default().parse_with_options(input, options);
+    assert!(!r.is_valid());
+    assert_eq!(r.report().warnings().count(), 0);
+    assert_eq!(r.report().errors().count(), 1);
+}
+
+#[test]
+fn baker_percentage() {
+    let input = "Mix @flour{100%baker} with @water{65*%baker}.";
+    let r = cooklang::parse(input);
+    assert_eq!(r.report().warnings().count(), 2);
+    let recipe = r.unwrap_output();
+    assert_eq!(
+        recipe.ingredients[1].quantity.as_ref().unwrap().unit_text(),
+        Some("baker")
+    );
+
+    let options = ParseOptions {
+        baker_percentage: Some(500.0),
+        ..Default::default()
+    };
+    let r = CooklangParser::default().parse_with_options(input, options);
+    assert!(!r.report().has_warnings());
+    let recipe = r.unwrap_output();
+    let q = |i: usize| recipe.ingredients[i].quantity.clone().unwrap();
+    assert_eq!(q(0).value, ScalableValue::Fixed(500.0.into()));
+    assert_eq!(q(1).value, ScalableValue::Linear(325.0.into()));
+    assert_eq!(q(1).unit_text(), Some("g"));
+}
+
+#[test]
+fn line_endings_and_bom() {
+    struct Collect(Vec<String>);
+
+    impl<'a> Visitor<'a> for Collect {
+        fn visit_metadata(&mut self, key: &Text<'a>, value: &Text<'a>) {
+            self.0.push(format!("{key}: {value}"));
+        }
+        fn visit_section(&mut self, name: Option<&Text<'a>>) {
+            let name = name.map(Text::text).unwrap_or_default();
+            self.0.push(format!("= {name}"));
+        }
+        fn visit_text(&mut self, text: &Text<'a>) {
+            self.0.push(text.to_string());
+        }
+        fn visit_ingredient(&mut self, ingredient: &Located<parser::Ingredient<'a>>) {
+            self.0.push(format!("@{}", ingredient.name));
+        }
+        fn visit_cookware(&mut self, cookware: &Located<parser::Cookware<'a>>) {
+            self.0.push(format!("#{}", cookware.name));
+        }
+    }
+
+    let lf = indoc! {r#"
+        >> title: Pancakes
+        >> servings: 2
+
+        = Batter
+        Mix @flour{200%g} -- comment
+        and @milk{300%ml} in a #bowl.
+
+        > Let it rest.
+    "#};
+    let crlf = lf.replace('\n', "\r\n");
+    let bom = format!("\u{FEFF}{crlf}");
+
+    let collect = |input: &str| {
+        let ast = build_ast(PullParser::new(input, Extensions::all())).unwrap_output();
+        let mut c = Collect(Vec::new());
+        ast.walk(&mut c);
+        (ast.blocks.len(), c.0)
+    };
+    let expected = collect(lf);
+    assert_eq!(collect(&crlf), expected);
+    assert_eq!(collect(&bom), expected);
+
+    let recipe = cooklang::parse(lf).unwrap_output();
+    for input in [&crlf, &bom] {
+        let other = cooklang::parse(input).unwrap_output();
+        assert_eq!(other.metadata.map, recipe.metadata.map);
+        assert_eq!(other.sections, recipe.sections);
+        assert_eq!(other.ingredients, recipe.ingredients);
+        assert_eq!(other.cookware, recipe.cookware);
+    }
+    let with_bom = cooklang::parse(&bom).unwrap_output();
+    let key_span = with_bom.metadata.key_span("title").unwrap();
+    assert_eq!(&bom[key_span.range()], "title");
+}
+
+#[test]
+fn scale_by_factor() {
+    let recipe = cooklang::parse("Add @eggs{2*} and @milk{0.5*}.").unwrap_output();
+    let converter = Converter::empty();
+    let value =
+        |r: &ScaledRecipe, i: usize| r.ingredients[i].quantity.as_ref().unwrap().value.clone();
+
+    let scaled = recipe.clone().scale_by(1.5, &converter);
+    assert_eq!(value(&scaled, 0), Value::from(3.0));
+    assert_eq!(value(&scaled, 1), Value::from(0.75));
+
+    let scaled = recipe.clone().scale_by(1.3, &converter);
+    assert_eq!(value(&scaled, 0), Value::from(2.6));
+
+    let target = ScaleTarget::from_factor(1, 1.3).with_rounding(RoundingPolicy::Nearest);
+    let scaled = recipe.scale_to_target(target, &converter);
+    assert_eq!(value(&scaled, 0), Value::from(3.0));
+    assert_eq!(value(&scaled, 1), Value::from(0.65));
+}
+
+#[test_case(RoundingPolicy::Up => (3.0, 3.0, 187.5); "up")]
+#[test_case(RoundingPolicy::Nearest => (3.0, 3.0, 187.5); "nearest")]
+#[test_case(RoundingPolicy::Down => (2.0, 2.0, 187.5); "down")]
+#[test_case(RoundingPolicy::Decimal => (2.5, 2.5, 187.5); "decimal")]
+fn scale_count_rounding(rounding: RoundingPolicy) -> (f64, f64, f64) {
+    let parser = CooklangParser::extended();
+    let recipe = parser
+        .parse("Mix @eggs{2*}, @garlic{2*%cloves} and @flour{150*%g}.")
+        .unwrap_output();
+    let target = ScaleTarget::from_factor(4, 1.25).with_count_rounding(rounding);
+    let scaled = recipe.scale_to_target(target, parser.converter());
+    let value = |i: usize| {
+        let q = scaled.ingredients[i].quantity.as_ref().unwrap();
+        f64::try_from(&q.value).unwrap()
+    };
+    (value(0), value(1), value(2))
+}
+
+#[test]
+fn scale_cookware() {
+    let input = indoc! {r#"
+        >> [auto scale]: true
+        Boil @water{1%l} in a #pot{1} and bake @dough{500%g} in a #loaf tin{1*}.
+    "#};
+    let parser = CooklangParser::new(Extensions::all(), Default::default());
+    let recipe = parser.parse(input).unwrap_output();
+    let scaled = recipe.scale_by(2.0, parser.converter());
+
+    let water = scaled.ingredients[0].quantity.as_ref().unwrap();
+    assert_eq!(water.value, Value::from(2.0));
+    assert_eq!(scaled.cookware[0].quantity, Some(Value::from(1.0)));
+    assert_eq!(scaled.cookware[1].quantity, Some(Value::from(2.0)));
+    let data = scaled.scaled_data().unwrap();
+    assert!(matches!(data.cookware[0], ScaleOutcome::Fixed));
+    assert!(matches!(data.cookware[1], ScaleOutcome::Scaled));
+}
+
+#[test]
+fn scale_open_range() {
+    let input = "Add @salt{1-*%tsp}, @pepper{2-%pinch} and @sugar{-2*%tbsp}.";
+    let parser = CooklangParser::new(Extensions::all(), Default::default());
+    let recipe = parser.parse(input).unwrap_output();
+    let scaled = recipe.scale_by(2.0, parser.converter());
+
+    let salt = scaled.ingredients[0].quantity.as_ref().unwrap();
+    assert_eq!(salt.value, Value::AtLeast(2.0.into()));
+    assert_eq!(salt.to_string(), "at least 2 tsp");
+    let pepper = scaled.ingredients[1].quantity.as_ref().unwrap();
+    assert_eq!(pepper.value, Value::AtLeast(2.0.into()));
+    let sugar = scaled.ingredients[2].quantity.as_ref().unwrap();
+    assert_eq!(sugar.value, Value::AtMost(4.0.into()));
+    assert_eq!(sugar.to_string(), "up to 4 tbsp");
+}
+
+#[test]
+fn scale_report() {
+    let input = indoc! {"
+        >> servings: 2
+        Mix @flour{200*%g}, @salt{1%tsp}, @eggs{1*}, @milk{a splash*} and @water.
+    "};
+    let recipe = cooklang::parse(input).unwrap_output();
+    let target = ScaleTarget::new(2, 3, &[2]).with_count_rounding(RoundingPolicy::Up);
+    let (scaled, report) = recipe.scale_with_report(target, &Converter::bundled());
+
+    assert_eq!(report.target.target_servings(), 3);
+    assert_eq!(report.ingredients.len(), scaled.ingredients.len());
+    let changes = report
+        .ingredients
+        .iter()
+        .map(|i| (i.name.as_str(), i.change))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        changes,
+        [
+            ("flour", ScaleChange::Linear),
+            ("salt", ScaleChange::Fixed),
+            ("eggs", ScaleChange::Rounded),
+            ("milk", ScaleChange::Text),
+            ("water", ScaleChange::NoQuantity),
+        ]
+    );
+
+    let flour = &report.ingredients[0];
+    assert_eq!(flour.original.as_ref().unwrap().to_string(), "200* g");
+    assert_eq!(flour.scaled.as_ref().unwrap().to_string(), "300 g");
+    let salt = &report.ingredients[1];
+    assert_eq!(salt.original.as_ref().unwrap().to_string(), "1 tsp");
+    assert_eq!(salt.scaled.as_ref().unwrap().to_string(), "1 tsp");
+    let eggs = report.ingredients[2].scaled.as_ref().unwrap();
+    assert_eq!(eggs.value, Value::from(2.0));
+}
+
+#[test]
+fn scale_inline_quantities() {
+    let input = "Bake @dough{500*%g} at 180ºC for ~{30%min}.";
+    let parser = CooklangParser::new(Extensions::all(), Default::default());
+    let recipe = parser.parse(input).unwrap_output();
+    let scaled = recipe.scale_by(2.0, parser.converter());
+
+    let dough = scaled.ingredients[0].quantity.as_ref().unwrap();
+    assert_eq!(dough.to_string(), "1 kg");
+    // temperatures are fixed
+    assert_eq!(scaled.inline_quantities.len(), 1);
+    assert_eq!(scaled.inline_quantities[0].value, Value::from(180.0));
+    let data = scaled.scaled_data().unwrap();
+    assert!(matches!(data.inline_quantities[0], ScaleOutcome::Fixed));
+
+    // written inline quantities are linear like ingredients
+    let input = "Add @{2%cups} of the mixture.";
+    let recipe = parser.parse(input).unwrap_output();
+    let scaled = recipe.scale_by(2.0, parser.converter());
+    assert_eq!(scaled.inline_quantities[0].value, Value::from(4.0));
+    assert_eq!(scaled.inline_quantities[0].to_string(), "4 cups");
+    let data = scaled.scaled_data().unwrap();
+    assert!(matches!(data.inline_quantities[0], ScaleOutcome::Scaled));
+}
+
+#[test]
+fn tabs_as_whitespace() {
+    let input = "Add\t@ground\tblack pepper{1\t%\ttsp}.";
+    let recipe = cooklang::parse(input).unwrap_output();
+    let pepper = &recipe.ingredients[0];
+    assert_eq!(pepper.name, "ground black pepper");
+    assert_eq!(pepper.quantity.as_ref().unwrap().to_string(), "1 tsp");
+    // step text is kept as is
+    let step = recipe.sections[0].content[0].unwrap_step();
+    assert_eq!(
+        step.items[0],
+        Item::Text {
+            value: "Add\t".into()
+        }
+    );
+}
+
+#[test]
+fn ingredient_alias() {
+    let parser = CooklangParser::new(Extensions::all(), Converter::bundled());
+    let recipe = parser
+        .parse("Add @flour|all-purpose{200%g}.")
+        .unwrap_output();
+    let flour = &recipe.ingredients[0];
+    assert_eq!(flour.name, "flour");
+    assert_eq!(flour.alias.as_deref(), Some("all-purpose"));
+    assert_eq!(flour.display_name(), "all-purpose");
+    assert_eq!(flour.quantity.as_ref().unwrap().to_string(), "200 g");
+
+    let mut list = IngredientList::new();
+    for input in [
+        "Add @flour|all-purpose{200%g}.",
+        "Add @flour{100%g}.",
+        "Add @all-purpose{50%g}.",
+    ] {
+        let recipe = parser.parse(input).unwrap_output().default_scale();
+        list.add_recipe(&recipe, parser.converter());
+    }
+    assert_eq!(list.iter().count(), 1);
+    let (name, quantity) = list.iter().next().unwrap();
+    assert_eq!(name, "all-purpose");
+    assert_eq!(quantity.to_string(), "350 g");
+    assert!(list.get("flour").is_some());
+    assert!(list.get("all-purpose").is_some());
+}
+
+#[test]
+fn cookware_list_dedup() {
+    let input = indoc! {r#"
+        Put the @flour in a #bowl{1} and the @eggs in another #bowl{1}.
+        Use a #whisk, and then the #whisk again.
+        Clean the #&bowl{} and the #-counter.
+    "#};
+    let parser = CooklangParser::new(Extensions::all(), Converter::bundled());
+    let recipe = parser.parse(input).unwrap_output().default_scale();
+    assert_eq!(recipe.cookware.len(), 6);
+    let list = recipe.cookware_list();
+    let names = list
+        .iter()
+        .map(|c| c.cookware.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["bowl", "whisk"]);
+    assert_eq!(list[0].index, 0);
+    assert_eq!(list[0].amount.to_string(), "2");
+    assert!(list[1].amount.is_empty());
+}
+
+#[test]
+fn cookware_quantity_kind() {
+    let input = indoc! {r#"
+        Thread the meat in #skewers{12} and put them in a #baking dish{9%inch}.
+
+        Use another #&baking dish{9%inch} and the #&skewers{4}.
+
+        Clean the #counter.
+    "#};
+    let parser = CooklangParser::extended();
+    let recipe = parser.parse(input).unwrap_output().default_scale();
+
+    let skewers = &recipe.cookware[0];
+    assert_eq!(skewers.quantity_kind(), Some(CookwareQuantityKind::Count));
+    assert_eq!(skewers.unit, None);
+    let dish = &recipe.cookware[1];
+    assert_eq!(dish.quantity_kind(), Some(CookwareQuantityKind::Descriptor));
+    assert_eq!(dish.quantity, Some(Value::from(9.0)));
+    assert_eq!(dish.unit.as_deref(), Some("inch"));
+    assert_eq!(recipe.cookware[4].quantity_kind(), None);
+
+    // descriptors are not added up
+    assert_eq!(skewers.group_amounts(&recipe.cookware).to_string(), "16");
+    assert!(dish.group_amounts(&recipe.cookware).is_empty());
+
+    assert_eq!(
+        recipe.plain_text(true).lines().next(),
+        Some("Thread the meat in 12 skewers and put them in a 9 inch baking dish.")
+    );
+}
+
+#[test]
+fn thousands_separator() {
+    use cooklang::analysis::ThousandsSeparator;
+
+    let parse = |input: &str, separator| {
+        let options = ParseOptions {
+            thousands_separator: Some(separator),
+            ..Default::default()
+        };
+        let r = CooklangParser::extended().parse_with_options(input, options);
+        let warnings = r.report().warnings().count();
+        let recipe = r.into_output().unwrap();
+        let value = recipe.ingredients[0]
+            .quantity
+            .as_ref()
+            .unwrap()
+            .value
+            .clone();
+        (value, warnings)
+    };
+
+    let (value, warnings) = parse("@flour{1,000%g}", ThousandsSeparator::Comma);
+    assert_eq!(value, ScalableValue::Fixed(Value::from(1000.0)));
+    assert_eq!(warnings, 0);
+
+    let (value, warnings) = parse("@milk{1.000,5%ml}", ThousandsSeparator::Dot);
+    assert_eq!(value, ScalableValue::Fixed(Value::from(1000.5)));
+    assert_eq!(warnings, 0);
+
+    let (value, warnings) = parse("@sugar{1,5%kg}", ThousandsSeparator::Comma);
+    assert_eq!(value, ScalableValue::Fixed(Value::from("1,5".to_string())));
+    assert_eq!(warnings, 1);
+
+    // off by default
+    let r = CooklangParser::extended()
+        .parse("@flour{1,000%g}")
+        .unwrap_output();
+    assert!(matches!(
+        r.ingredients[0].quantity.as_ref().unwrap().value,
+        ScalableValue::Fixed(Value::Text(_))
+    ));
+}
+
+#[test]
+fn pantry_ingredients() {
+    let pantry = std::collections::HashSet::from(["water", "salt"]);
+    let options = ParseOptions {
+        pantry_check: Some(Box::new(|name: &str| pantry.contains(name))),
+        ..Default::default()
+    };
+    let parser = CooklangParser::extended();
+    let recipe = parser
+        .parse_with_options("Mix @flour{500%g} with @water{300%ml}.", options)
+        .unwrap_output()
+        .default_scale();
+    assert!(!recipe.ingredients[0].pantry);
+    assert!(recipe.ingredients[1].pantry);
+
+    let mut list = IngredientList::new();
+    list.add_recipe(&recipe, parser.converter());
+    let names = list
+        .iter()
+        .map(|(name, _)| name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["flour"]);
+
+    let mut list = IngredientList::new();
+    list.set_include_pantry(true);
+    list.add_recipe(&recipe, parser.converter());
+    assert_eq!(list.iter().count(), 2);
+}
+
+#[test]
+fn metadata_continuation() {
+    let input = indoc! {r#"
+        >> description: A long description
+           that continues here.
+        >> servings: 2
+        Mix @flour{200%g}.
+    "#};
+    let parser = CooklangParser::extended();
+    let r = parser.parse(input).unwrap_output();
+    assert_eq!(
+        r.metadata.description(),
+        Some("A long description that continues here.")
+    );
+    assert_eq!(r.metadata.servings(), Some([2].as_slice()));
+    assert_eq!(r.sections[0].content.len(), 1);
+
+    let meta = parser.parse_metadata(input).unwrap_output();
+    assert_eq!(
+        meta.description(),
+        Some("A long description that continues here.")
+    );
+
+    let parser = CooklangParser::new(Extensions::empty(), Converter::empty());
+    let r = parser.parse(input).unwrap_output();
+    assert_eq!(r.metadata.description(), Some("A long description"));
+    assert_eq!(r.sections[0].content.len(), 2);
+
+    // an indented `>>` starts a new entry
+    let input = indoc! {r#"
+        >> description: A long description
+           that continues here.
+           >> servings: 2
+        Mix @flour{200%g}.
+    "#};
+    let parser = CooklangParser::extended();
+    let r = parser.parse(input).unwrap_output();
+    assert_eq!(
+        r.metadata.description(),
+        Some("A long description that continues here.")
+    );
+    assert_eq!(r.metadata.servings(), Some([2].as_slice()));
+    assert_eq!(r.sections[0].content.len(), 1);
+    let meta = parser.parse_metadata(input).unwrap_output();
+    assert_eq!(meta.servings(), Some([2].as_slice()));
+}
+
+#[test]
+fn metadata_trailing_comment() {
+    let input = ">> servings: 4 -- serves a family\n>> [- quick -] course: main\n";
+    let parser = CooklangParser::extended();
+    let r = parser.parse(input).unwrap_output();
+    assert_eq!(r.metadata.map["servings"], "4");
+    assert_eq!(r.metadata.servings(), Some([4].as_slice()));
+    assert_eq!(r.metadata.map["course"], "main");
+    let meta = parser.parse_metadata(input).unwrap_output();
+    assert_eq!(meta.map["servings"], "4");
+
+    // kept as a comment event
+    let comments = PullParser::new(input, Extensions::COMMENT_EVENTS)
+        .filter_map(|ev| match ev {
+            parser::Event::Comment { text, .. } => Some(text.text_trimmed().into_owned()),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(comments, ["serves a family", "quick"]);
+}
+
+#[test]
+fn unresolved_references() {
+    let parser = CooklangParser::extended();
+
+    let input = "Add @salt{1%g} and @&salt{1%g} to the #pot.";
+    let r = parser.parse(input).unwrap_output();
+    assert!(r.unresolved().is_empty());
+
+    let input = "Add @salt{1%g} and @&ghost{} to the #&pan.";
+    let r = parser.parse(input).into_output().unwrap();
+    let unresolved = r.unresolved();
+    assert_eq!(unresolved.len(), 2);
+    assert_eq!(unresolved[0].kind, UnresolvedKind::Ingredient);
+    assert_eq!(unresolved[0].name, "ghost");
+    assert_eq!(&input[unresolved[0].span.range()], "@&ghost{}");
+    assert_eq!(unresolved[1].kind, UnresolvedKind::Cookware);
+    assert_eq!(unresolved[1].name, "pan");
+
+    let options = ParseOptions {
+        recipe_ref_check: Some(Box::new(|name: &str| {
+            if name == "pizza dough" {
+                CheckResult::Ok
+            } else {
+                CheckResult::Warning(vec![])
+            }
+        })),
+        ..Default::default()
+    };
+    let input = "Use @@pizza dough{} and @@tomato sauce{}.";
+    let r = parser.parse_with_options(input, options).unwrap_output();
+    let unresolved = r.unresolved();
+    assert_eq!(unresolved.len(), 1);
+    assert_eq!(unresolved[0].kind, UnresolvedKind::Recipe);
+    assert_eq!(unresolved[0].name, "tomato sauce");
+    // still unresolved after scaling
+    assert_eq!(r.default_scale().unresolved().len(), 1);
+}
+
+#[test]
+fn decimal_marker() {
+    let parser = CooklangParser::extended();
+    let recipe = parser
+        .parse("Add @milk{0.5!%cup}, @water{0.5%cup} and @salt{a pinch!}.")
+        .unwrap_output()
+        .default_scale();
+    let quantity = |i: usize| recipe.ingredients[i].quantity.clone().unwrap();
+    let options = FormatOptions {
+        fraction_style: FractionStyle::Auto,
+        ..Default::default()
+    };
+
+    let mut milk = quantity(0);
+    assert!(milk.is_decimal());
+    assert_eq!(
+        milk.display_with(parser.converter(), options).to_string(),
+        "0.5 cup"
+    );
+    assert!(!milk.try_fraction(parser.converter()));
+    assert_eq!(milk.to_string(), "0.5 cup");
+
+    let water = quantity(1);
+    assert!(!water.is_decimal());
+    assert_eq!(
+        water.display_with(parser.converter(), options).to_string(),
+        "1/2 cup"
+    );
+
+    let salt = quantity(2);
+    assert!(!salt.is_decimal());
+    assert_eq!(salt.value, Value::from("a pinch!".to_string()));
+
+    // the marker is not mistaken for step media
+    let recipe = parser
+        .parse("Add @milk{0.5!%cup} ![milk](milk.jpg)")
+        .unwrap_output();
+    let milk = recipe.ingredients[0].quantity.as_ref().unwrap();
+    assert!(milk.is_decimal());
+    assert_eq!(recipe.sections[0].content.len(), 1);
+
+    // without the extension the marker is text
+    let parser = CooklangParser::new(Extensions::empty(), Converter::bundled());
+    let recipe = parser.parse("Add @milk{0.5!%cup}.").unwrap_output();
+    let milk = recipe.ingredients[0].quantity.as_ref().unwrap();
+    assert!(!milk.is_decimal());
+}
+
+#[test]
+fn metadata_reader_stops_at_first_step() {
+    struct LimitedReader<'a> {
+        lines: std::vec::IntoIter<&'a str>,
+        read: usize,
+    }
+
+    impl std::io::Read for LimitedReader<'_> {
+        fn read(&mut self, _: &mut [u8]) -> std::io::Result<usize> {
+            unreachable!("only read_line is used")
+        }
+    }
+
+    impl std::io::BufRead for LimitedReader<'_> {
+        fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
+            Ok(self.lines.as_slice().first().map_or(&[], |l| l.as_bytes()))
+        }
+
+        fn consume(&mut self, amt: usize) {
+            let line = self.lines.next().expect("consumed past the end");
+            assert_eq!(amt, line.len(), "partial line consumed");
+            self.read += 1;
+        }
+    }
+
+    let input = indoc! {"
+        >> servings: 2
+        -- a comment
+        >> description: long
+          and continued
+
+        Mix @flour{200%g}.
+        >> late: not read
+    "};
+    let parser = CooklangParser::extended();
+    let mut reader = LimitedReader {
+        lines: input.split_inclusive('\n').collect::<Vec<_>>().into_iter(),
+        read: 0,
+    };
+    let meta = parser
+        .parse_metadata_reader(&mut reader)
+        .unwrap()
+        .unwrap_output();
+    // the first step line is read, the rest of the input is not
+    assert_eq!(reader.read, 6);
+    assert_eq!(meta.servings(), Some([2].as_slice()));
+    assert_eq!(meta.description(), Some("long and continued"));
+    assert!(!meta.map.contains_key("late"));
+
+    let top = indoc! {"
+        >> servings: 2
+        >> description: long
+          and continued
+    "};
+    assert_eq!(meta, parser.parse_metadata(top).unwrap_output());
+}
+
+#[test]
+fn quantity_source_value() {
+    let input = indoc! {"
+        Mix @flour{1 1/2%cups}, @salt{a pinch}, @milk{ 0.50 %l},
+        @eggs{2*} and @water{1|2}. Wait ~{10%min}.
+    "};
+    let parser = CooklangParser::extended();
+    let recipe = parser.parse(input).unwrap_output();
+    let source = |i: usize| {
+        recipe.ingredients[i]
+            .quantity
+            .as_ref()
+            .unwrap()
+            .source_value()
+    };
+    assert_eq!(source(0), Some("1 1/2"));
+    assert_eq!(source(1), Some("a pinch"));
+    assert_eq!(source(2), Some("0.50"));
+    assert_eq!(source(3), Some("2"));
+    assert_eq!(source(4), None);
+    assert_eq!(
+        recipe.timers[0].quantity.as_ref().unwrap().source_value(),
+        Some("10")
+    );
+
+    // fixed values keep it, scaled values don't
+    let scaled = recipe.clone().scale(2, parser.converter());
+    let source = |i: usize| {
+        scaled.ingredients[i]
+            .quantity
+            .as_ref()
+            .unwrap()
+            .source_value()
+    };
+    assert_eq!(source(0), Some("1 1/2"));
+    assert_eq!(source(3), None);
+
+    let mut recipe = recipe.default_scale();
+    let flour = recipe.ingredients[0].quantity.as_mut().unwrap();
+    assert_eq!(flour.source_value(), Some("1 1/2"));
+    flour.convert("ml", parser.converter()).unwrap();
+    assert_eq!(flour.source_value(), None);
+
+    // the value as written is not compared
+    let a = parser.parse("@flour{200%g}").unwrap_output();
+    let b = parser.parse("@flour{200.0%g}").unwrap_output();
+    assert_eq!(a.ingredients[0].quantity, b.ingredients[0].quantity);
+}
+
+#[test]
+fn step_media() {
+    let parser = CooklangParser::extended();
+    let input = "Plate the @pasta{} ![images/plating.jpg] and serve.";
+    let r = parser.parse(input).unwrap_output();
+    let Content::Step(step) = &r.sections[0].content[0] else {
+        panic!()
+    };
+    assert_eq!(step.media, ["images/plating.jpg"]);
+    assert_eq!(r.ingredients.len(), 1);
+    assert_eq!(
+        step.items,
+        vec![
+            Item::Text {
+                value: "Plate the ".into()
+            },
+            Item::Ingredient { index: 0 },
+            Item::Text {
+                value: "  and serve.".into()
+            },
+        ]
+    );
+    let span = step.item_span(2).unwrap();
+    assert_eq!(&input[span.range()], " ![images/plating.jpg] and serve.");
+
+    // without the extension it's text
+    let parser = CooklangParser::new(Extensions::empty(), Converter::empty());
+    let r = parser.parse("Serve ![plating.jpg]").unwrap_output();
+    let Content::Step(step) = &r.sections[0].content[0] else {
+        panic!()
+    };
+    assert!(step.media.is_empty());
+    assert_eq!(
+        step.items,
+        vec![Item::Text {
+            value: "Serve ![plating.jpg]".into()
+        }]
+    );
+}
+
+#[test]
+fn inline_quantities() {
+    let parser = CooklangParser::extended();
+    let r = parser
+        .parse("Add @{2%cups} of the mixture.")
+        .unwrap_output();
+    assert!(r.ingredients.is_empty());
+    assert_eq!(r.inline_quantities.len(), 1);
+    // linear by default
+    assert_eq!(r.inline_quantities[0].to_string(), "2* cups");
+    let Content::Step(step) = &r.sections[0].content[0] else {
+        panic!()
+    };
+    assert_eq!(
+        step.items,
+        vec![
+            Item::Text {
+                value: "Add ".into()
+            },
+            Item::InlineQuantity { index: 0 },
+            Item::Text {
+                value: " of the mixture.".into()
+            },
+        ]
+    );
+
+    // scaled like an ingredient
+    let r = parser
+        .parse(">> servings: 2\nAdd @{2*%cups} of the mixture.")
+        .unwrap_output()
+        .scale(4, parser.converter());
+    assert_eq!(r.inline_quantities[0].to_string(), "4 cups");
+
+    // empty is an error
+    let r = parser.parse("Add @{} of the mixture.");
+    assert!(!r.is_valid());
+    let error = r.report().errors().next().unwrap();
+    assert_eq!(error.to_string(), "Invalid inline quantity: is empty");
+
+    // a name, modifiers or no extension make it an ingredient
+    let r = parser.parse("Add @?{2%cups}.");
+    assert!(!r.is_valid());
+    let parser = CooklangParser::new(Extensions::empty(), Converter::empty());
+    let r = parser.parse("Add @{2%cups} of the mixture.");
+    assert_eq!(
+        r.report().errors().next().unwrap().to_string(),
+        "Invalid ingredient name: is empty"
+    );
+}
+
+#[test]
+fn fraction_denominator_metadata() {
+    let input = indoc! {"
+        >> fraction denominator: 8
+        Add @milk{0.375*%cup}, @water{0.3%cup}, @oil{0.75%cup} and @sugar{0.4%cup}.
+    "};
+    let parser = CooklangParser::extended();
+    let recipe = parser.parse(input).unwrap_output();
+    assert_eq!(recipe.metadata.fraction_denominator(), Some(8));
+    assert_eq!(
+        recipe.plain_text(true),
+        "Add 3/8* cup milk, 1/4 cup water, 3/4 cup oil and 3/8 cup sugar."
+    );
+
+    let scaled = recipe.clone().default_scale();
+    let t = |i: usize, fraction_denominator| {
+        let options = FormatOptions {
+            fraction_style: FractionStyle::Auto,
+            fraction_denominator,
+            ..Default::default()
+        };
+        scaled.ingredients[i]
+            .quantity
+            .as_ref()
+            .unwrap()
+            .display_with(parser.converter(), options)
+            .to_string()
+    };
+    // 0.3 is closer to 2/8 than to 3/8
+    assert_eq!(t(1, None), "1/4 cup");
+    assert_eq!(t(1, Some(10)), "3/10 cup");
+    assert_eq!(t(3, None), "3/8 cup");
+
+    let scaled = recipe.scale_by(2.0, parser.converter());
+    let milk = scaled.ingredients[0].quantity.as_ref().unwrap();
+    assert_eq!(milk.to_string(), "3/4 c");
+
+    // without the key cups only go to fourths
+    let r = parser
+        .parse("Add @milk{0.375%cup}.")
+        .unwrap_output()
+        .default_scale();
+    let milk = r.ingredients[0].quantity.as_ref().unwrap();
+    assert_eq!(milk.to_string(), "0.375 cup");
+
+    let r = parser.parse(">> fraction denominator: 100\nAdd @milk{0.375%cup}.");
+    assert!(r.is_valid());
+    assert_eq!(r.report().warnings().count(), 1);
+    assert_eq!(r.output().unwrap().metadata.fraction_denominator(), None);
+}
+
+#[test]
+fn ingredient_list_contributions() {
+    let parser = CooklangParser::extended();
+    let recipe = |input: &str| parser.parse(input).unwrap_output().default_scale();
+
+    let mut list = IngredientList::new();
+    let bread = recipe("Mix @flour{200%g} and @water{100%ml}.");
+    list.add_named_recipe("Bread", &bread, parser.converter());
+    let cake = recipe("Mix @flour{250%g}, @eggs{2} and more @flour{50%g}.");
+    list.add_named_recipe("Cake", &cake, parser.converter());
+    list.add_recipe(&recipe("Dust with @flour{100%g}."), parser.converter());
+
+    assert_eq!(list.get("flour").unwrap().to_string(), "600 g");
+    let contributions = |list: &IngredientList, name: &str| {
+        list.contributions(name)
+            .iter()
+            .map(|(id, q)| format!("{} from {id}", q))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        contributions(&list, "flour"),
+        ["200 g from Bread", "300 g from Cake"]
+    );
+    assert_eq!(contributions(&list, "water"), ["100 ml from Bread"]);
+    assert_eq!(contributions(&list, "eggs"), ["2 from Cake"]);
+    assert!(list.contributions("salt").is_empty());
+
+    let aisle = cooklang::aisle::parse("[baking]\nflour\n").unwrap();
+    let categorized = list.categorize(&aisle);
+    assert_eq!(
+        contributions(&categorized.categories["baking"], "flour"),
+        ["200 g from Bread", "300 g from Cake"]
+    );
+    assert_eq!(contributions(&categorized.other, "eggs"), ["2 from Cake"]);
+}
+
+#[test]
+fn ingredient_list_ignores_case() {
+    let parser = CooklangParser::extended();
+    let mut list = IngredientList::new();
+    for input in [
+        "Mix @Flour{100%g}.",
+        "Mix @flour{200%g}.",
+        "Mix @FLOUR{300%g}.",
+    ] {
+        let recipe = parser.parse(input).unwrap_output().default_scale();
+        list.add_recipe(&recipe, parser.converter());
+    }
+    // the first name is listed
+    let entries = list
+        .iter()
+        .map(|(name, q)| format!("{name}: {q}"))
+        .collect::<Vec<_>>();
+    assert_eq!(entries, ["Flour: 600 g"]);
+    assert!(list.get("flour").is_some());
+    assert!(list.get("fLoUr").is_some());
+
+    // exact matching
+    let mut list = IngredientList::new();
+    list.set_normalizer(|name: &str| name.to_string());
+    for input in ["Mix @Flour{100%g}.", "Mix @flour{200%g}."] {
+        let recipe = parser.parse(input).unwrap_output().default_scale();
+        list.add_recipe(&recipe, parser.converter());
+    }
+    assert_eq!(list.iter().count(), 2);
+
+    // changing the normalizer matches the names already added
+    let mut list = IngredientList::new();
+    for input in ["Mix @Flour{100%g}.", "Mix @sugar|Caster{50%g}."] {
+        let recipe = parser.parse(input).unwrap_output().default_scale();
+        list.add_recipe(&recipe, parser.converter());
+    }
+    list.set_normalizer(|name: &str| name.to_uppercase().replace(' ', ""));
+    assert!(list.get("FLOUR").is_some());
+    assert!(list.get("SUGAR").is_some());
+    let recipe = parser
+        .parse("Mix @flour{200%g}.")
+        .unwrap_output()
+        .default_scale();
+    list.add_recipe(&recipe, parser.converter());
+    let entries = list
+        .iter()
+        .map(|(name, q)| format!("{name}: {q}"))
+        .collect::<Vec<_>>();
+    assert_eq!(entries, ["Caster: 50 g", "Flour: 300 g"]);
+}
+
+#[test]
+fn timer_duration_from_metadata() {
+    let input = indoc! {"
+        >> rest time: 30 min
+        Let the dough ~rest{}.
+        Bake for ~{20%min} and ~cool{}.
+        >> cool time: 1 hour
+    "};
+    let parser = CooklangParser::extended();
+    let r = parser.parse(input);
+    assert!(r.is_valid());
+    let recipe = r.into_output().unwrap();
+    let duration = |i: usize| recipe.timers[i].quantity.as_ref().map(|q| q.to_string());
+    assert_eq!(duration(0).as_deref(), Some("30 min"));
+    assert_eq!(duration(1).as_deref(), Some("20 min"));
+    assert_eq!(duration(2).as_deref(), Some("60 min"));
+
+    // missing entry
+    let r = parser.parse("Let the dough ~rest{}.");
+    assert!(!r.is_valid());
+    let parser = CooklangParser::new(
+        Extensions::all() ^ Extensions::TIMER_REQUIRES_TIME,
+        Converter::bundled(),
+    );
+    let r = parser.parse("Let the dough ~rest{}.");
+    assert!(r.is_valid());
+    assert_eq!(r.report().warnings().count(), 1);
+    assert!(r.output().unwrap().timers[0].quantity.is_none());
+}
+
+#[test]
+fn component_unit_mismatch() {
+    let parser = CooklangParser::new(
+        Extensions::all().difference(Extensions::LENIENT_UNITS),
+        Converter::bundled(),
+    );
+    let input = "Wait ~t{5%g}.";
+    let r = parser.parse(input);
+    assert!(r.is_valid());
+    let warnings = r.report().warnings().collect::<Vec<_>>();
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].code, Some("component-unit-mismatch"));
+    assert_eq!(&input[warnings[0].labels[0].0.range()], "g");
+
+    let r = parser.parse("Wait ~t{5%min}.");
+    assert!(r.report().is_empty());
+
+    let options = ParseOptions {
+        allow_component_unit_mismatch: true,
+        ..Default::default()
+    };
+    let r = parser.parse_with_options(input, options);
+    assert!(r.report().is_empty());
+
+    // also with the default extensions
+    let r = CooklangParser::extended().parse(input);
+    assert!(r.is_valid());
+    let warnings = r.report().warnings().collect::<Vec<_>>();
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].code, Some("component-unit-mismatch"));
+
+    let parser = CooklangParser::new(
+        Extensions::default() | Extensions::COOKWARE_UNITS,
+        Converter::bundled(),
+    );
+    let input = "Heat the #pan{2%ml}.";
+    let r = parser.parse(input);
+    assert!(r.is_valid());
+    let warnings = r.report().warnings().collect::<Vec<_>>();
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].code, Some("component-unit-mismatch"));
+    assert_eq!(&input[warnings[0].labels[0].0.range()], "ml");
+    assert!(parser.parse("Heat the #pan{2%pieces}.").report().is_empty());
+    let options = ParseOptions {
+        allow_component_unit_mismatch: true,
+        ..Default::default()
+    };
+    assert!(parser
+        .parse_with_options(input, options)
+        .report()
+        .is_empty());
+
+    // cookware can't have units without the extension
+    let parser = CooklangParser::new(
+        Extensions::all() - Extensions::COOKWARE_UNITS,
+        Converter::bundled(),
+    );
+    let r = parser.parse("Heat the #pan{2%ml}.");
+    assert!(!r.is_valid());
+    let error = r.report().errors().next().unwrap();
+    assert_eq!(error.message, "Invalid cookware quantity: unit");
+}
+
+#[test]
+fn scaled_quantity_single_ingredient() {
+    let input = indoc! {"
+        >> servings: 2
+        Mix @flour{300*%g}, @salt{1%tsp} and @eggs{2*}.
+    "};
+    let converter = Converter::bundled();
+    let recipe = cooklang::parse(input).unwrap_output();
+    let q = |i: usize| {
+        recipe
+            .scaled_quantity(i, 1.5, &converter)
+            .map(|q| q.to_string())
+    };
+    assert_eq!(q(0).as_deref(), Some("450 g"));
+    assert_eq!(q(1).as_deref(), Some("1 tsp"));
+    assert_eq!(q(2).as_deref(), Some("3"));
+    assert_eq!(q(3), None);
+
+    // same as scaling the whole recipe and the recipe is untouched
+    let scaled = recipe.clone().scale_by(1.5, &converter);
+    assert_eq!(
+        scaled.ingredients[0]
+            .quantity
+            .as_ref()
+            .map(|q| q.to_string()),
+        q(0)
+    );
+    assert!(matches!(
+        recipe.ingredients[0].quantity.as_ref().unwrap().value,
+        ScalableValue::Linear(_)
+    ));
+}
+
+#[test]
+fn recipe_builder() {
+    let q = |v: ScalableValue, unit: &str| Some(Quantity::new(v, Some(unit.to_string())));
+    let recipe = RecipeBuilder::new()
+        .metadata("servings", "2|4")
+        .metadata("time", "1 hour")
+        .step()
+        .text("Mix ")
+        .ingredient("flour", q(ScalableValue::Linear(200.0.into()), "g"))
+        .text(" and ")
+        .ingredient("water", None)
+        .text(" in a ")
+        .cookware("bowl", None)
+        .text(".")
+        .step()
+        .text("Rest for ")
+        .timer(None, q(ScalableValue::Fixed(10.0.into()), "min"))
+        .text(".")
+        .finish(&Converter::bundled())
+        .unwrap();
+
+    let input = indoc! {"
+        >> servings: 2|4
+        >> time: 1 hour
+        Mix @flour{200*%g} and @water in a #bowl.
+
+        Rest for ~{10%min}.
+    "};
+    let parsed = cooklang::parse(input).unwrap_output();
+    assert_eq!(recipe.plain_text(true), parsed.plain_text(true));
+    assert_eq!(recipe.metadata.servings(), parsed.metadata.servings());
+    let steps = |r: &cooklang::ScalableRecipe| {
+        r.sections[0]
+            .content
+            .iter()
+            .map(|c| c.unwrap_step().number)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(steps(&recipe), [1, 2]);
+    assert!(recipe.metadata.time().is_some());
+    assert_eq!(recipe.metadata.time(), parsed.metadata.time());
+    assert_eq!(steps(&recipe), steps(&parsed));
+    assert_eq!(recipe.ingredients[1].origin, parsed.ingredients[1].origin);
+    assert_eq!(recipe.timers[0].origin, parsed.timers[0].origin);
+
+    let by_servings = ScalableValue::ByServings(vec![1.0.into(), 2.0.into(), 3.0.into()]);
+    let err = RecipeBuilder::new()
+        .metadata("servings", "2|4")
+        .ingredient("eggs", Some(Quantity::new(by_servings, None)))
+        .finish(&Converter::bundled())
+        .unwrap_err();
+    assert_eq!(
+        err,
+        BuildError::ServingsMismatch {
+            servings: 2,
+            values: 3
+        }
+    );
+}
+
+#[test]
+fn scale_to_ingredient() {
+    let input = indoc! {"
+        Mix @flour{500*%g}, @water{350*%ml}, @salt{10*%g} and @yeast{1%packet}.
+        Add more @&flour{50*%g} if needed.
+    "};
+    let converter = Converter::bundled();
+    let recipe = cooklang::parse(input).unwrap_output();
+    let kg = Quantity::new(Value::from(1.0), Some("kg".to_string()));
+    let scaled = recipe
+        .clone()
+        .scale_to_ingredient("flour", kg.clone(), &converter)
+        .unwrap();
+    let q = |i: usize| scaled.ingredients[i].quantity.as_ref().unwrap().to_string();
+    assert_eq!(q(0), "1 kg");
+    assert_eq!(q(1), "700 ml");
+    assert_eq!(q(2), "20 g");
+    assert_eq!(q(3), "1 packet");
+    assert_eq!(q(4), "100 g");
+
+    let missing = recipe
+        .clone()
+        .scale_to_ingredient("rye", kg.clone(), &converter)
+        .unwrap_err();
+    assert!(matches!(missing, ScaleError::IngredientNotFound { .. }));
+
+    let text = cooklang::parse("Add @flour{some} and @water{350*%ml}.").unwrap_output();
+    let err = text
+        .scale_to_ingredient("flour", kg.clone(), &converter)
+        .unwrap_err();
+    assert!(matches!(err, ScaleError::TextValueError(_)));
+
+    let liters = Quantity::new(Value::from(1.0), Some("l".to_string()));
+    let err = recipe
+        .scale_to_ingredient("flour", liters, &converter)
+        .unwrap_err();
+    assert!(matches!(err, ScaleError::InvalidIngredientTarget { .. }));
+}
@@ -25,3 +25,25 @@ fn serde_test() {
 
     assert_eq!(recipe, deserialized);
 }
+
+#[cfg(feature = "binary")]
+#[test]
+fn binary_test() {
+    use cooklang::{ScalableRecipe, ScaledRecipe};
+
+    let recipe = parse(RECIPE).into_output().unwrap();
+    let bytes = recipe.to_bytes();
+    assert_eq!(ScalableRecipe::from_bytes(&bytes).unwrap(), recipe);
+    assert!(bytes.len() < serde_json::to_vec(&recipe).unwrap().len());
+
+    let scaled = recipe.default_scale();
+    let bytes = scaled.to_bytes();
+    // the scaled data is not comparable, the scale errors are not serialized
+    let json = |r: &ScaledRecipe| serde_json::to_value(r).unwrap();
+    assert_eq!(
+        json(&ScaledRecipe::from_bytes(&bytes).unwrap()),
+        json(&scaled)
+    );
+
+    assert!(ScaledRecipe::from_bytes(&bytes[..bytes.len() / 2]).is_err());
+}